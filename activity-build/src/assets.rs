@@ -0,0 +1,322 @@
+// Copies static assets into the bundle. Stylesheets and images get the hash of their content in
+// their file name, so they can be cached forever and still update with a new build.
+
+use std::{
+    collections::BTreeMap,
+    fs,
+    path::{Path, PathBuf},
+};
+
+use anyhow::Result;
+use sha2::{Digest, Sha256};
+
+use crate::{activity_path, config::BuildSettings};
+
+const HASHED_EXTENSIONS: &[&str] = &["css", "png", "jpg", "jpeg", "gif", "svg", "webp", "ico"];
+
+#[derive(Default)]
+pub struct Assets {
+    /// URLs of the copied assets by their path in the public directory.
+    pub urls: BTreeMap<String, String>,
+    /// URLs of the stylesheets, they are linked from the index.html.
+    pub styles: Vec<String>,
+    pub favicon: Option<String>,
+    /// URLs of the assets with the hash of their content in their name, they never change.
+    pub hashed: Vec<String>,
+}
+
+pub fn copy_assets(settings: &BuildSettings) -> Result<Assets> {
+    let mut assets = Assets::default();
+
+    if let Some(public_dir) = &settings.public_dir {
+        let mut files = Vec::new();
+        list_files(public_dir, &mut files)?;
+
+        files.sort();
+
+        // Stylesheets come last so their references to images can be rewritten.
+        let mut stylesheets = Vec::new();
+        for file in files {
+            let name = file
+                .strip_prefix(public_dir)?
+                .to_string_lossy()
+                .replace('\\', "/");
+
+            let contents = fs::read(&file)?;
+            if is_css(&file) {
+                stylesheets.push((name, String::from_utf8_lossy(&contents).into_owned()));
+                continue;
+            }
+
+            let url = copy_asset(settings, &mut assets, &name, &contents)?;
+            assets.urls.insert(name, url);
+        }
+
+        copy_stylesheets(settings, &mut assets, stylesheets)?;
+    }
+
+    if let Some(favicon) = &settings.favicon {
+        let contents = fs::read(favicon)
+            .map_err(|err| anyhow::anyhow!("failed to read {}: {err}", favicon.display()))?;
+        let name = format!(
+            "favicon.{}",
+            favicon
+                .extension()
+                .and_then(|e| e.to_str())
+                .unwrap_or("ico")
+        );
+        assets.favicon = Some(copy_asset(settings, &mut assets, &name, &contents)?);
+    }
+
+    Ok(assets)
+}
+
+// Imported stylesheets are copied before the ones that import them, so their new names are known,
+// and only the others are linked from the index.html. Stylesheets that import each other keep
+// their references.
+fn copy_stylesheets(
+    settings: &BuildSettings,
+    assets: &mut Assets,
+    mut pending: Vec<(String, String)>,
+) -> Result<()> {
+    let imported: Vec<String> = pending
+        .iter()
+        .flat_map(|(name, css)| css_imports(css, name))
+        .collect();
+
+    while !pending.is_empty() {
+        let next = pending
+            .iter()
+            .position(|(name, css)| {
+                css_imports(css, name).iter().all(|import| {
+                    import == name || pending.iter().all(|(other, _)| other != import)
+                })
+            })
+            .unwrap_or(0);
+
+        let (name, css) = pending.remove(next);
+        let css = rewrite_css_urls(&css, &name, &assets.urls);
+        let url = copy_asset(settings, assets, &name, css.as_bytes())?;
+        if !imported.contains(&name) {
+            assets.styles.push(url.clone());
+        }
+        assets.urls.insert(name, url);
+    }
+
+    Ok(())
+}
+
+// Writes the asset into the bundle and returns the URL it's served from.
+fn copy_asset(
+    settings: &BuildSettings,
+    assets: &mut Assets,
+    name: &str,
+    contents: &[u8],
+) -> Result<String> {
+    let hashed = settings.hash_assets && should_hash(Path::new(name));
+    let name = match hashed {
+        true => hashed_name(name, contents),
+        false => name.to_string(),
+    };
+
+    let dest = activity_path(&settings.out_dir, &name);
+    if let Some(parent) = dest.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    fs::write(dest, contents)?;
+
+    let url = format!("/{name}");
+    if hashed {
+        assets.hashed.push(url.clone());
+    }
+    Ok(url)
+}
+
+// `images/logo.png` becomes `images/logo.1a2b3c4d.png`.
+fn hashed_name(name: &str, contents: &[u8]) -> String {
+    let hash = Sha256::digest(contents);
+    let hash: String = hash[..4].iter().map(|b| format!("{b:02x}")).collect();
+
+    match name.rsplit_once('.') {
+        Some((stem, ext)) if !stem.ends_with('/') => format!("{stem}.{hash}.{ext}"),
+        _ => format!("{name}.{hash}"),
+    }
+}
+
+// Points `url(...)` and `@import "..."` references to assets at their hashed names.
+fn rewrite_css_urls(css: &str, css_name: &str, urls: &BTreeMap<String, String>) -> String {
+    let css_dir = css_dir(css_name);
+    let css = rewrite_css_imports(css, css_dir, urls);
+
+    let mut result = String::with_capacity(css.len());
+    let mut rest = css.as_str();
+
+    while let Some(start) = rest.find("url(") {
+        let (before, after) = rest.split_at(start + "url(".len());
+        result.push_str(before);
+
+        let Some(end) = after.find(')') else {
+            rest = after;
+            break;
+        };
+
+        let reference = after[..end].trim().trim_matches(|c| c == '"' || c == '\'');
+        match resolve_reference(css_dir, reference).and_then(|name| urls.get(&name)) {
+            Some(url) => result.push_str(&format!("\"{url}\"")),
+            None => result.push_str(&after[..end]),
+        }
+
+        rest = &after[end..];
+    }
+
+    result.push_str(rest);
+    result
+}
+
+// `@import` can take a plain string instead of `url(...)`.
+fn rewrite_css_imports(css: &str, css_dir: &str, urls: &BTreeMap<String, String>) -> String {
+    let mut result = String::with_capacity(css.len());
+    let mut rest = css;
+
+    while let Some(start) = rest.find("@import") {
+        let (before, after) = rest.split_at(start + "@import".len());
+        result.push_str(before);
+        rest = after;
+
+        let Some((start, end)) = string_import(after) else {
+            continue;
+        };
+        result.push_str(&after[..start]);
+        match resolve_reference(css_dir, &after[start + 1..end - 1])
+            .and_then(|name| urls.get(&name))
+        {
+            Some(url) => result.push_str(&format!("\"{url}\"")),
+            None => result.push_str(&after[start..end]),
+        }
+        rest = &after[end..];
+    }
+
+    result.push_str(rest);
+    result
+}
+
+// The range of the quoted string after an `@import`, with the quotes.
+fn string_import(after_import: &str) -> Option<(usize, usize)> {
+    let start = after_import.len() - after_import.trim_start().len();
+    let quote = after_import[start..]
+        .chars()
+        .next()
+        .filter(|c| *c == '"' || *c == '\'')?;
+    let end = after_import[start + 1..].find(quote)? + start + 2;
+    Some((start, end))
+}
+
+// The stylesheets that a stylesheet imports, as paths in the public directory.
+fn css_imports(css: &str, css_name: &str) -> Vec<String> {
+    css.split("@import")
+        .skip(1)
+        .filter_map(|after| {
+            let reference = match string_import(after) {
+                Some((start, end)) => &after[start + 1..end - 1],
+                None => {
+                    let url = after.trim_start().strip_prefix("url(")?;
+                    url[..url.find(')')?]
+                        .trim()
+                        .trim_matches(|c| c == '"' || c == '\'')
+                }
+            };
+            resolve_reference(css_dir(css_name), reference)
+        })
+        .collect()
+}
+
+fn css_dir(css_name: &str) -> &str {
+    css_name.rsplit_once('/').map(|(dir, _)| dir).unwrap_or("")
+}
+
+// Resolves a reference relative to the directory of the stylesheet to a path in the public
+// directory.
+fn resolve_reference(dir: &str, reference: &str) -> Option<String> {
+    let (base, reference) = match reference.strip_prefix('/') {
+        Some(absolute) => ("", absolute),
+        None => (dir, reference),
+    };
+
+    let mut segments: Vec<&str> = base.split('/').filter(|s| !s.is_empty()).collect();
+    for segment in reference.split('/') {
+        match segment {
+            "" | "." => {}
+            ".." => {
+                segments.pop()?;
+            }
+            segment => segments.push(segment),
+        }
+    }
+
+    Some(segments.join("/"))
+}
+
+fn list_files(dir: &Path, files: &mut Vec<PathBuf>) -> Result<()> {
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path.is_dir() {
+            list_files(&path, files)?;
+        } else {
+            files.push(path);
+        }
+    }
+
+    Ok(())
+}
+
+fn extension(path: &Path) -> String {
+    path.extension()
+        .and_then(|e| e.to_str())
+        .unwrap_or_default()
+        .to_ascii_lowercase()
+}
+
+fn is_css(path: &Path) -> bool {
+    extension(path) == "css"
+}
+
+fn should_hash(path: &Path) -> bool {
+    HASHED_EXTENSIONS.contains(&extension(path).as_str())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use super::{css_imports, rewrite_css_urls};
+
+    #[test]
+    fn rewrites_imports_and_urls() {
+        let urls = BTreeMap::from([
+            ("theme.css".to_string(), "/theme.1a2b3c4d.css".to_string()),
+            (
+                "images/bg.png".to_string(),
+                "/images/bg.5e6f7a8b.png".to_string(),
+            ),
+        ]);
+        let css = "@import \"../theme.css\";\n@import url('../theme.css');\n\
+                   @import \"https://example.com/font.css\";\n\
+                   body { background: url(../images/bg.png); }";
+
+        assert_eq!(
+            rewrite_css_urls(css, "styles/app.css", &urls),
+            "@import \"/theme.1a2b3c4d.css\";\n@import url(\"/theme.1a2b3c4d.css\");\n\
+             @import \"https://example.com/font.css\";\n\
+             body { background: url(\"/images/bg.5e6f7a8b.png\"); }"
+        );
+    }
+
+    #[test]
+    fn finds_imported_stylesheets() {
+        let css = "@import 'base.css';\n@import url(\"/theme.css\") screen;\nbody {}";
+        assert_eq!(
+            css_imports(css, "styles/app.css"),
+            ["styles/base.css", "theme.css"]
+        );
+    }
+}
@@ -0,0 +1,635 @@
+// Built-in alternative to esbuild. It links the ES modules of the activity (the shim, the SDK,
+// the wasm-bindgen glue and its snippets) into a single script by turning every module into a
+// function that returns its exports.
+//
+// Only static imports and exports of relative modules are supported, which is all the generated
+// code uses.
+
+use std::{
+    collections::HashMap,
+    fmt::Write,
+    fs,
+    path::{Path, PathBuf},
+};
+
+use anyhow::{Context, Result};
+
+use crate::js::{self, Token, TokenKind};
+
+const DEFAULT_EXPORT: &str = "__default";
+
+pub fn bundle(entry: &Path, minify: bool) -> Result<String> {
+    let mut linker = Linker::default();
+    linker.load(&entry.canonicalize()?)?;
+
+    let mut out = String::from("(() => {\n\"use strict\";\n");
+    for module in &linker.modules {
+        out.push_str(&module.code);
+    }
+    out.push_str("})();\n");
+
+    if minify {
+        out = js::minify(&out)?;
+    }
+
+    Ok(out)
+}
+
+struct Module {
+    code: String,
+}
+
+#[derive(Default)]
+struct Linker {
+    /// Modules in the order they have to run, dependencies first.
+    modules: Vec<Module>,
+    /// Indices into `modules` by path, `None` while the module is being loaded.
+    ids: HashMap<PathBuf, Option<usize>>,
+}
+
+/// What a module exports, by exported name.
+enum Export {
+    /// A binding of the module itself.
+    Local(String),
+    /// A binding of another module.
+    Reexport { module: usize, name: String },
+    /// Everything another module exports.
+    All(usize),
+}
+
+impl Linker {
+    fn load(&mut self, path: &Path) -> Result<usize> {
+        match self.ids.get(path) {
+            Some(Some(id)) => return Ok(*id),
+            Some(None) => anyhow::bail!(
+                "circular import of {}, which the built-in bundler doesn't support",
+                path.display()
+            ),
+            None => {}
+        }
+        self.ids.insert(path.to_path_buf(), None);
+
+        let src = fs::read_to_string(path)
+            .with_context(|| format!("failed to read {}", path.display()))?;
+        let code = self
+            .link(path, &src)
+            .with_context(|| format!("failed to bundle {}", path.display()))?;
+
+        let id = self.modules.len();
+        self.modules.push(Module { code });
+        self.ids.insert(path.to_path_buf(), Some(id));
+
+        Ok(id)
+    }
+
+    // Rewrites the module into a function that returns its exports.
+    fn link(&mut self, path: &Path, src: &str) -> Result<String> {
+        let src = replace_import_meta_url(src)?;
+        let tokens = js::tokenize(&src)?;
+        let dir = path.parent().unwrap_or(Path::new("."));
+
+        let mut imports = String::new();
+        let mut body = String::new();
+        let mut exports: Vec<(String, Export)> = Vec::new();
+
+        let mut copied = 0;
+        let mut i = 0;
+        while i < tokens.len() {
+            let token = tokens[i];
+            let is_statement = token.depth == 0
+                && (token.is_word(&src, "export")
+                    || (token.is_word(&src, "import")
+                        && !matches!(
+                            next(&tokens, i).map(|t| t.kind),
+                            Some(TokenKind::Punct('(' | '.'))
+                        )));
+            if !is_statement {
+                i += 1;
+                continue;
+            }
+
+            body.push_str(&src[copied..token.start]);
+            let mut statement = Statement {
+                src: &src,
+                tokens: &tokens,
+                pos: i + 1,
+            };
+
+            if token.is_word(&src, "import") {
+                let (bindings, specifier) = statement.import_clause()?;
+                let module = self.load(&resolve(dir, &specifier)?)?;
+                for binding in bindings {
+                    let value = match &binding {
+                        Binding::Namespace(_) => format!("__module_{module}"),
+                        Binding::Named { imported, .. } => {
+                            format!("__module_{module}{}", export_name(imported))
+                        }
+                    };
+                    writeln!(imports, "const {} = {value};", binding.local())?;
+                }
+                copied = statement.end_statement();
+            } else {
+                copied = self.export(&mut statement, dir, &mut body, &mut exports)?;
+            }
+
+            i = statement.pos;
+        }
+        body.push_str(&src[copied..]);
+
+        let mut code = String::new();
+        writeln!(
+            code,
+            "// {}",
+            path.file_name().unwrap_or_default().to_string_lossy()
+        )?;
+        writeln!(code, "const __module_{} = (() => {{", self.modules.len())?;
+        code.push_str(&imports);
+        code.push_str(&body);
+        code.push_str("\nconst __exports = {};\n");
+        for (name, export) in &exports {
+            let getter = match export {
+                Export::Local(local) => local.clone(),
+                Export::Reexport { module, name } => {
+                    format!("__module_{module}{}", export_name(name))
+                }
+                Export::All(module) => {
+                    writeln!(
+                        code,
+                        "for (const key of Object.keys(__module_{module})) if (key !== \"default\" && !(key in __exports)) Object.defineProperty(__exports, key, {{ get: () => __module_{module}[key], enumerable: true }});"
+                    )?;
+                    continue;
+                }
+            };
+            writeln!(
+                code,
+                "Object.defineProperty(__exports, {name:?}, {{ get: () => {getter}, enumerable: true }});"
+            )?;
+        }
+        code.push_str("return __exports;\n})();\n");
+
+        Ok(code)
+    }
+
+    // Handles an export statement and returns where the rest of the source continues.
+    fn export(
+        &mut self,
+        statement: &mut Statement,
+        dir: &Path,
+        body: &mut String,
+        exports: &mut Vec<(String, Export)>,
+    ) -> Result<usize> {
+        let src = statement.src;
+        let first = statement.peek()?;
+
+        // export { a, b as c } [from "./x"];
+        // export * [as ns] from "./x";
+        if matches!(first.kind, TokenKind::Punct('{' | '*')) {
+            let (bindings, specifier) = statement.export_clause()?;
+            let module = specifier
+                .map(|specifier| self.load(&resolve(dir, &specifier)?))
+                .transpose()?;
+
+            for binding in bindings {
+                let export = match (&binding, module) {
+                    (Binding::Namespace(None), Some(module)) => Export::All(module),
+                    (Binding::Namespace(Some(_)), Some(module)) => {
+                        Export::Local(format!("__module_{module}"))
+                    }
+                    (Binding::Named { imported, .. }, Some(module)) => Export::Reexport {
+                        module,
+                        name: imported.clone(),
+                    },
+                    (Binding::Named { imported, .. }, None) => Export::Local(imported.clone()),
+                    (Binding::Namespace(_), None) => anyhow::bail!("`export *` needs a module"),
+                };
+                let name = match &binding {
+                    Binding::Named { local, .. } => local.clone(),
+                    Binding::Namespace(name) => name.clone().unwrap_or_default(),
+                };
+                exports.push((name, export));
+            }
+
+            return Ok(statement.end_statement());
+        }
+
+        // export default ...
+        if first.is_word(src, "default") {
+            statement.next()?;
+            let declaration = statement.peek()?;
+            let named = self.declaration_name(statement, declaration);
+
+            let local = match named {
+                Some(name) => name,
+                None => {
+                    body.push_str(&format!("const {DEFAULT_EXPORT} = "));
+                    DEFAULT_EXPORT.to_string()
+                }
+            };
+            exports.push(("default".into(), Export::Local(local)));
+            return Ok(declaration.start);
+        }
+
+        // export function / class / const / let / var
+        let names = if ["const", "let", "var"]
+            .iter()
+            .any(|w| first.is_word(src, w))
+        {
+            statement.variable_names()?
+        } else {
+            self.declaration_name(statement, first)
+                .into_iter()
+                .collect::<Vec<_>>()
+        };
+        if names.is_empty() {
+            anyhow::bail!("unsupported export at byte {}", first.start);
+        }
+
+        for name in names {
+            exports.push((name.clone(), Export::Local(name)));
+        }
+
+        Ok(first.start)
+    }
+
+    // The name of a function or class declaration that starts with the given token.
+    fn declaration_name(&self, statement: &Statement, start: Token) -> Option<String> {
+        let src = statement.src;
+        let mut tokens = statement.tokens[statement.index_of(start)..]
+            .iter()
+            .filter(|t| !t.is_trivia());
+
+        let mut token = tokens.next()?;
+        if token.is_word(src, "async") {
+            token = tokens.next()?;
+        }
+        if !(token.is_word(src, "function") || token.is_word(src, "class")) {
+            return None;
+        }
+
+        let mut token = tokens.next()?;
+        if token.kind == TokenKind::Punct('*') {
+            token = tokens.next()?;
+        }
+
+        (token.kind == TokenKind::Word && token.text(src) != "extends")
+            .then(|| token.text(src).to_string())
+    }
+}
+
+enum Binding {
+    /// `* as ns`, the name is missing for `export * from`.
+    Namespace(Option<String>),
+    Named {
+        imported: String,
+        local: String,
+    },
+}
+
+impl Binding {
+    fn local(&self) -> &str {
+        match self {
+            Binding::Namespace(name) => name.as_deref().unwrap_or_default(),
+            Binding::Named { local, .. } => local,
+        }
+    }
+}
+
+/// A cursor over the tokens of an import or export statement.
+struct Statement<'a> {
+    src: &'a str,
+    tokens: &'a [Token],
+    pos: usize,
+}
+
+impl<'a> Statement<'a> {
+    fn index_of(&self, token: Token) -> usize {
+        self.tokens
+            .iter()
+            .position(|t| t.start == token.start)
+            .unwrap_or(self.pos)
+    }
+
+    fn peek(&self) -> Result<Token> {
+        next(self.tokens, self.pos - 1).ok_or_else(|| anyhow::anyhow!("unexpected end of module"))
+    }
+
+    fn next(&mut self) -> Result<Token> {
+        let token = self.peek()?;
+        self.pos = self.index_of(token) + 1;
+        Ok(token)
+    }
+
+    fn next_text(&mut self) -> Result<&'a str> {
+        let token = self.next()?;
+        Ok(token.text(self.src))
+    }
+
+    fn expect(&mut self, text: &str) -> Result<()> {
+        let token = self.next()?;
+        if token.text(self.src) != text {
+            anyhow::bail!(
+                "expected `{text}` but found `{}` at byte {}",
+                token.text(self.src),
+                token.start
+            );
+        }
+        Ok(())
+    }
+
+    fn string(&mut self) -> Result<String> {
+        let token = self.next()?;
+        if token.kind != TokenKind::String {
+            anyhow::bail!("expected a module specifier at byte {}", token.start);
+        }
+        let text = token.text(self.src);
+        Ok(text[1..text.len() - 1].to_string())
+    }
+
+    // Consumes an optional semicolon and returns where the statement ends.
+    fn end_statement(&mut self) -> usize {
+        match self.peek() {
+            Ok(token) if token.kind == TokenKind::Punct(';') => {
+                self.pos = self.index_of(token) + 1;
+                token.end
+            }
+            _ => self.tokens[self.pos - 1].end,
+        }
+    }
+
+    // `"./x"`, `a from "./x"`, `* as ns from "./x"`, `a, { b as c } from "./x"`
+    fn import_clause(&mut self) -> Result<(Vec<Binding>, String)> {
+        let mut bindings = Vec::new();
+
+        if self.peek()?.kind == TokenKind::String {
+            return Ok((bindings, self.string()?));
+        }
+
+        loop {
+            let token = self.next()?;
+            match token.kind {
+                TokenKind::Punct('*') => {
+                    self.expect("as")?;
+                    bindings.push(Binding::Namespace(Some(self.next_text()?.to_string())));
+                }
+                TokenKind::Punct('{') => bindings.extend(self.named_bindings()?),
+                TokenKind::Word => bindings.push(Binding::Named {
+                    imported: "default".into(),
+                    local: token.text(self.src).to_string(),
+                }),
+                _ => anyhow::bail!("unsupported import at byte {}", token.start),
+            }
+
+            if self.peek()?.kind == TokenKind::Punct(',') {
+                self.next()?;
+            } else {
+                break;
+            }
+        }
+
+        self.expect("from")?;
+        Ok((bindings, self.string()?))
+    }
+
+    // `{ a, b as c }`, `{ a } from "./x"`, `* from "./x"`, `* as ns from "./x"`
+    fn export_clause(&mut self) -> Result<(Vec<Binding>, Option<String>)> {
+        let token = self.next()?;
+        let bindings = if token.kind == TokenKind::Punct('*') {
+            let name = if self.peek()?.is_word(self.src, "as") {
+                self.next()?;
+                Some(self.next_text()?.to_string())
+            } else {
+                None
+            };
+            vec![Binding::Namespace(name)]
+        } else {
+            self.named_bindings()?
+        };
+
+        let specifier = match self.peek() {
+            Ok(token) if token.is_word(self.src, "from") => {
+                self.next()?;
+                Some(self.string()?)
+            }
+            _ => None,
+        };
+
+        Ok((bindings, specifier))
+    }
+
+    // The part of `{ a, b as c }` after the opening brace.
+    fn named_bindings(&mut self) -> Result<Vec<Binding>> {
+        let mut bindings = Vec::new();
+        loop {
+            let token = self.next()?;
+            match token.kind {
+                TokenKind::Punct('}') => break,
+                TokenKind::Punct(',') => continue,
+                TokenKind::Word | TokenKind::String => {
+                    let imported = unquote(token.text(self.src));
+                    let local = if self.peek()?.is_word(self.src, "as") {
+                        self.next()?;
+                        unquote(self.next_text()?)
+                    } else {
+                        imported.clone()
+                    };
+                    bindings.push(Binding::Named { imported, local });
+                }
+                _ => anyhow::bail!(
+                    "unexpected `{}` at byte {}",
+                    token.text(self.src),
+                    token.start
+                ),
+            }
+        }
+        Ok(bindings)
+    }
+
+    // The names declared by `const a = 1, b = 2`. Destructuring isn't supported.
+    fn variable_names(&self) -> Result<Vec<String>> {
+        let start = self.index_of(self.peek()?);
+        let depth = self.tokens[start].depth;
+        let mut names = Vec::new();
+        let mut expect_name = true;
+
+        for token in self.tokens[start + 1..].iter().filter(|t| !t.is_trivia()) {
+            if token.depth < depth || (token.depth == depth && token.kind == TokenKind::Punct(';'))
+            {
+                break;
+            }
+            if token.depth > depth {
+                continue;
+            }
+
+            match token.kind {
+                TokenKind::Word if expect_name => {
+                    names.push(token.text(self.src).to_string());
+                    expect_name = false;
+                }
+                TokenKind::Punct('{' | '[') if expect_name => {
+                    anyhow::bail!(
+                        "destructuring exports aren't supported at byte {}",
+                        token.start
+                    )
+                }
+                TokenKind::Punct(',') => expect_name = true,
+                _ => {}
+            }
+
+            // Without semicolons the declaration can only be told apart from the next statement
+            // by a line break that isn't followed by a comma, which is good enough here.
+            if !expect_name && names.len() == 1 && self.ends_line(token) {
+                break;
+            }
+        }
+
+        Ok(names)
+    }
+
+    fn ends_line(&self, token: &Token) -> bool {
+        let index = self.index_of(*token);
+        let rest = &self.tokens[index + 1..];
+        let newline = rest
+            .iter()
+            .take_while(|t| t.is_trivia())
+            .any(|t| t.kind == TokenKind::Whitespace(true));
+        let continues = rest
+            .iter()
+            .find(|t| !t.is_trivia())
+            .is_some_and(|t| matches!(t.kind, TokenKind::Punct(',' | '=' | '.' | '(')));
+        newline && !continues && token.kind != TokenKind::Punct('=')
+    }
+}
+
+// The bundle is a classic script, whose URL is the one of the page as far as the generated code is
+// concerned. Only the expression is replaced, not strings or comments that mention it.
+fn replace_import_meta_url(src: &str) -> Result<String> {
+    let tokens: Vec<_> = js::tokenize(src)?
+        .into_iter()
+        .filter(|t| !t.is_trivia())
+        .collect();
+
+    let mut out = String::with_capacity(src.len());
+    let mut copied = 0;
+    for window in tokens.windows(5) {
+        let [import, dot, meta, dot2, url] = window else {
+            unreachable!()
+        };
+        if import.is_word(src, "import")
+            && dot.kind == TokenKind::Punct('.')
+            && meta.is_word(src, "meta")
+            && dot2.kind == TokenKind::Punct('.')
+            && url.is_word(src, "url")
+        {
+            out.push_str(&src[copied..import.start]);
+            out.push_str("location.href");
+            copied = url.end;
+        }
+    }
+    out.push_str(&src[copied..]);
+
+    Ok(out)
+}
+
+// The next token after `index` that isn't trivia.
+fn next(tokens: &[Token], index: usize) -> Option<Token> {
+    tokens[index + 1..].iter().find(|t| !t.is_trivia()).copied()
+}
+
+fn unquote(text: &str) -> String {
+    text.trim_matches(|c| c == '"' || c == '\'').to_string()
+}
+
+// Exported names can be any string, `default` included.
+fn export_name(name: &str) -> String {
+    format!("[{name:?}]")
+}
+
+// Resolves a relative module specifier like a bundler would.
+fn resolve(dir: &Path, specifier: &str) -> Result<PathBuf> {
+    if !(specifier.starts_with("./") || specifier.starts_with("../")) {
+        anyhow::bail!(
+            "can't resolve `{specifier}`, the built-in bundler only supports relative imports, use esbuild instead"
+        );
+    }
+
+    let path = dir.join(specifier);
+    [
+        path.clone(),
+        path.with_extension("js"),
+        path.with_extension("mjs"),
+        path.join("index.js"),
+    ]
+    .into_iter()
+    .find(|p| p.is_file())
+    .ok_or_else(|| anyhow::anyhow!("can't resolve `{specifier}` from {}", dir.display()))?
+    .canonicalize()
+    .map_err(Into::into)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn replaces_only_the_import_meta_url_expression() {
+        let src = "new URL(\"a.wasm\", import.meta.url);\n\
+                   // import.meta.url\n\
+                   const s = \"import.meta.url\", t = `${import.meta.url}`;\n\
+                   import . meta\n.url;";
+        assert_eq!(
+            replace_import_meta_url(src).unwrap(),
+            "new URL(\"a.wasm\", location.href);\n\
+             // import.meta.url\n\
+             const s = \"import.meta.url\", t = `${location.href}`;\n\
+             location.href;"
+        );
+    }
+
+    #[test]
+    fn bundles_imports_and_exports() {
+        let dir =
+            std::env::temp_dir().join(format!("activity-build-bundler-{}", std::process::id()));
+        fs::create_dir_all(dir.join("snippets")).unwrap();
+        fs::write(
+            dir.join("shim.js"),
+            "import init, { start } from \"./glue.js\";\ninit().then(() => start());\n",
+        )
+        .unwrap();
+        fs::write(
+            dir.join("glue.js"),
+            "import { log } from './snippets/log.js';\n\
+             export function start() { log(\"import.meta.url\"); }\n\
+             export default async function init() { return new URL(\"a.wasm\", import.meta.url); }\n",
+        )
+        .unwrap();
+        fs::write(
+            dir.join("snippets/log.js"),
+            "export const log = (m) => console.log(m)\n",
+        )
+        .unwrap();
+
+        let code = bundle(&dir.join("shim.js"), false).unwrap();
+        fs::remove_dir_all(&dir).unwrap();
+
+        assert!(!code.contains("import ") && !code.contains("export "));
+        assert!(code.contains("new URL(\"a.wasm\", location.href)"));
+        assert!(code.contains("log(\"import.meta.url\")"));
+        // Dependencies run first.
+        let log = code.find("// log.js").unwrap();
+        let glue = code.find("// glue.js").unwrap();
+        let shim = code.find("// shim.js").unwrap();
+        assert!(log < glue && glue < shim);
+    }
+
+    #[test]
+    fn rejects_bare_imports() {
+        let dir = std::env::temp_dir().join(format!("activity-build-bare-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join("index.js"), "import { z } from \"zod\";\n").unwrap();
+
+        let error = bundle(&dir.join("index.js"), false).unwrap_err();
+        fs::remove_dir_all(&dir).unwrap();
+
+        assert!(format!("{error:#}").contains("only supports relative imports"));
+    }
+}
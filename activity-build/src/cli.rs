@@ -0,0 +1,203 @@
+use std::{ffi::OsString, path::PathBuf};
+
+use clap::{Args, Parser, Subcommand, ValueEnum};
+use serde::Deserialize;
+
+/// Build tool for Discord activities in Rust.
+///
+/// Running it without a subcommand is the same as `activity-build build`.
+#[derive(Parser)]
+#[command(version, args_conflicts_with_subcommands = true)]
+pub struct Cli {
+    #[command(subcommand)]
+    pub command: Option<Commands>,
+
+    #[command(flatten)]
+    pub build: BuildArgs,
+}
+
+#[derive(Subcommand)]
+pub enum Commands {
+    /// Build the activity into a bundle that can be deployed.
+    Build(BuildArgs),
+    /// Serve the activity, rebuild it when the sources change and reload the page.
+    Serve(ServeArgs),
+    /// Remove the output directory.
+    Clean(CleanArgs),
+    /// Create a new activity crate.
+    Init(InitArgs),
+    /// Check that everything needed to build an activity is installed.
+    Doctor,
+    /// Turn code offsets of the Wasm module from a stack trace into Rust source locations.
+    Symbolicate(SymbolicateArgs),
+    /// Work with coredumps of activities built with `--coredump`.
+    #[command(subcommand)]
+    Coredump(CoredumpCommand),
+}
+
+#[derive(Args, Clone, Default)]
+pub struct BuildArgs {
+    /// Directory to write the build output to [default: build].
+    #[arg(long)]
+    pub out_dir: Option<PathBuf>,
+
+    /// Build with optimizations, this is the default.
+    #[arg(long, conflicts_with = "dev")]
+    pub release: bool,
+
+    /// Build without optimizations and skip minification unless `--minify` is passed.
+    #[arg(long)]
+    pub dev: bool,
+
+    /// Minify the bundled JavaScript, the default for release builds unless configured otherwise.
+    #[arg(long, overrides_with = "no_minify")]
+    pub minify: bool,
+
+    /// Don't minify the bundled JavaScript.
+    #[arg(long, overrides_with = "minify")]
+    pub no_minify: bool,
+
+    /// Rewrite the Wasm module to produce a coredump when it traps.
+    #[arg(long)]
+    pub coredump: bool,
+
+    /// Flags passed to wasm-coredump-rewriter.
+    #[arg(long, allow_hyphen_values = true)]
+    pub coredump_flags: Option<String>,
+
+    /// Keep the DWARF debug info of the .wasm file, in the file itself or in a separate one
+    /// [default: none].
+    #[arg(long, value_enum)]
+    pub debug_info: Option<DebugInfo>,
+
+    /// Don't optimize the .wasm file with wasm-opt.
+    #[arg(long)]
+    pub no_wasm_opt: bool,
+
+    /// Print the N largest functions and data segments of the .wasm file.
+    #[arg(long, value_name = "N", default_value_t = 0)]
+    pub top: usize,
+
+    /// Write `.gz` and `.br` files next to the files of the bundle that compress well.
+    #[arg(long)]
+    pub precompress: bool,
+
+    /// The bundler for the JavaScript, the built-in one doesn't need esbuild [default: esbuild].
+    #[arg(long, value_enum)]
+    pub bundler: Option<Bundler>,
+
+    /// Don't access the network, esbuild has to be available locally.
+    #[arg(long)]
+    pub offline: bool,
+
+    /// How build results are reported, `json` prints one JSON object per build to stdout.
+    #[arg(long, value_enum, default_value_t = MessageFormat::Human)]
+    pub message_format: MessageFormat,
+
+    /// Extra arguments passed to `cargo build`, e.g. `-- --features foo`.
+    #[arg(last = true)]
+    pub cargo_args: Vec<OsString>,
+}
+
+impl BuildArgs {
+    /// Whether minification was turned on or off explicitly.
+    pub fn minify(&self) -> Option<bool> {
+        (self.minify || self.no_minify).then_some(self.minify)
+    }
+}
+
+#[derive(ValueEnum, Deserialize, Clone, Copy, PartialEq, Eq, Debug, Default)]
+#[serde(rename_all = "kebab-case")]
+pub enum Bundler {
+    #[default]
+    Esbuild,
+    Builtin,
+}
+
+#[derive(ValueEnum, Deserialize, Clone, Copy, PartialEq, Eq, Debug, Default)]
+#[serde(rename_all = "kebab-case")]
+pub enum DebugInfo {
+    #[default]
+    None,
+    /// The debug info stays in the .wasm file of the bundle.
+    Embedded,
+    /// The debug info is moved to a .debug.wasm file next to the bundle, which the bundled
+    /// .wasm file links to.
+    Separate,
+}
+
+#[derive(ValueEnum, Clone, Copy, PartialEq, Eq, Default)]
+pub enum MessageFormat {
+    #[default]
+    Human,
+    Json,
+}
+
+#[derive(Args)]
+pub struct ServeArgs {
+    /// Port to serve the activity on.
+    #[arg(long, default_value_t = 3000)]
+    pub port: u16,
+
+    #[command(flatten)]
+    pub build: BuildArgs,
+}
+
+#[derive(Args)]
+pub struct CleanArgs {
+    /// The output directory to remove [default: build].
+    #[arg(long)]
+    pub out_dir: Option<PathBuf>,
+}
+
+#[derive(Args)]
+pub struct InitArgs {
+    /// Name of the crate, it's created in a directory of the same name.
+    pub name: String,
+
+    /// What the activity starts with.
+    #[arg(long, value_enum, default_value_t)]
+    pub template: Template,
+}
+
+#[derive(ValueEnum, Clone, Copy, Default)]
+pub enum Template {
+    /// An activity that logs the channel it runs in.
+    #[default]
+    Minimal,
+    /// Authenticates the user, with a server that exchanges the OAuth2 code for a token.
+    Auth,
+    /// A user interface built with Yew.
+    Yew,
+    /// Draws on a canvas every frame.
+    Canvas,
+}
+
+#[derive(Args)]
+pub struct SymbolicateArgs {
+    /// The .wasm file with debug info [default: the debug file or the bundled .wasm file of the
+    /// last build].
+    #[arg(long)]
+    pub wasm: Option<PathBuf>,
+
+    /// Offsets in the module like `0x1a2b`, as they appear in stack traces after
+    /// `wasm-function[..]:`. A stack trace is read from stdin if there are none.
+    pub offsets: Vec<String>,
+}
+
+#[derive(Subcommand)]
+pub enum CoredumpCommand {
+    /// Print the stack frames and locals of a coredump.
+    Inspect(InspectArgs),
+}
+
+#[derive(Args)]
+pub struct InspectArgs {
+    /// The coredump, as uploaded by `activity::install_coredump_hook`.
+    pub file: PathBuf,
+
+    /// The .wasm file the coredump was taken from [default: the debug file or the bundled .wasm
+    /// file of the last build].
+    #[arg(long)]
+    pub wasm: Option<PathBuf>,
+}
@@ -0,0 +1,270 @@
+// Compiles the crate to Wasm and generates the JavaScript glue with wasm-bindgen.
+
+use std::{
+    env, fs,
+    io::{self, BufRead, BufReader},
+    path::{Path, PathBuf},
+    process::{Command, Stdio},
+};
+
+use anyhow::{Context, Result};
+use serde::Deserialize;
+
+use crate::{
+    cli::DebugInfo,
+    config::{BuildSettings, WasmOpt},
+    install, output_path,
+    sdk::{self, Sdk},
+};
+
+pub const WASM_TARGET: &str = "wasm32-unknown-unknown";
+
+const WASM_OPT_DEFAULT_PASSES: &[&str] = &["-Oz"];
+const BINARYEN_RELEASES: &str = "https://github.com/WebAssembly/binaryen/releases";
+
+/// The subset of cargo's JSON messages we care about.
+/// https://doc.rust-lang.org/cargo/reference/external-tools.html#json-messages
+#[derive(Deserialize)]
+struct CargoMessage {
+    reason: String,
+    #[serde(default)]
+    target: Option<CargoTarget>,
+    #[serde(default)]
+    filenames: Vec<PathBuf>,
+}
+
+#[derive(Deserialize)]
+struct CargoTarget {
+    kind: Vec<String>,
+}
+
+#[derive(Deserialize)]
+struct Lockfile {
+    #[serde(default)]
+    package: Vec<LockedPackage>,
+}
+
+#[derive(Deserialize)]
+struct LockedPackage {
+    name: String,
+    version: String,
+}
+
+/// Builds the crate for the Wasm target and returns the path of the .wasm file.
+pub fn cargo_build(settings: &BuildSettings, sdk: &Sdk) -> Result<PathBuf> {
+    let cargo = env::var_os("CARGO").unwrap_or_else(|| "cargo".into());
+
+    let mut command = Command::new(cargo);
+    command
+        .args(["build", "--lib", "--target", WASM_TARGET])
+        .arg("--message-format=json-render-diagnostics");
+
+    if !settings.dev {
+        command.arg("--release");
+    }
+    if settings.offline {
+        command.arg("--offline");
+    }
+    if settings.debug_info != DebugInfo::None {
+        let profile = if settings.dev { "DEV" } else { "RELEASE" };
+        command.env(format!("CARGO_PROFILE_{profile}_DEBUG"), "true");
+    }
+    command.envs(&settings.env);
+    command.env(sdk::VERSION_ENV, &sdk.version);
+    command.args(&settings.cargo_args);
+
+    // Diagnostics are rendered to stderr, stdout only has the JSON messages.
+    let mut child = command.stdout(Stdio::piped()).spawn()?;
+    let stdout = child.stdout.take().expect("stdout is piped");
+
+    let mut wasm = None;
+    for line in BufReader::new(stdout).lines() {
+        let line = line?;
+        let Ok(message) = serde_json::from_str::<CargoMessage>(&line) else {
+            continue;
+        };
+
+        let is_cdylib = message
+            .target
+            .is_some_and(|t| t.kind.iter().any(|k| k == "cdylib"));
+        if message.reason == "compiler-artifact" && is_cdylib {
+            wasm = message
+                .filenames
+                .into_iter()
+                .find(|f| f.extension().is_some_and(|e| e == "wasm"));
+        }
+    }
+
+    let exit_status = child.wait()?;
+    if !exit_status.success() {
+        anyhow::bail!("cargo build exited with status {}", exit_status);
+    }
+
+    wasm.ok_or_else(|| {
+        anyhow::anyhow!(
+            "cargo didn't produce a .wasm file, make sure the crate has `crate-type = [\"cdylib\"]` in the [lib] section"
+        )
+    })
+}
+
+/// Generates the JavaScript glue for the .wasm file into the output directory.
+pub fn wasm_bindgen(settings: &BuildSettings, wasm: &Path) -> Result<()> {
+    ensure_wasm_bindgen()?;
+
+    let mut command = Command::new("wasm-bindgen");
+    command
+        .arg(wasm)
+        .arg("--out-dir")
+        .arg(&settings.out_dir)
+        .arg("--out-name")
+        .arg(&settings.out_name)
+        .args(["--target", "web", "--no-typescript"]);
+
+    if settings.dev {
+        command.arg("--debug");
+    }
+    if settings.debug_info != DebugInfo::None {
+        command.arg("--keep-debug");
+    }
+
+    // Keep stdout free for machine readable output.
+    let exit_status = command.stdout(io::stderr()).spawn()?.wait()?;
+
+    match exit_status.success() {
+        true => Ok(()),
+        false => anyhow::bail!("wasm-bindgen exited with status {}", exit_status),
+    }
+}
+
+/// Optimizes the generated .wasm file in place with wasm-opt from binaryen.
+pub fn wasm_opt(settings: &BuildSettings) -> Result<()> {
+    let default_passes = || {
+        WASM_OPT_DEFAULT_PASSES
+            .iter()
+            .map(|p| p.to_string())
+            .collect()
+    };
+    // Only a missing wasm-opt that was asked for explicitly fails the build.
+    let (mut passes, required): (Vec<String>, bool) = match &settings.wasm_opt {
+        Some(WasmOpt::Enabled(false)) => return Ok(()),
+        Some(WasmOpt::Enabled(true)) => (default_passes(), true),
+        Some(WasmOpt::Passes(passes)) => (passes.clone(), true),
+        None if settings.dev => return Ok(()),
+        None => (default_passes(), false),
+    };
+
+    if install::is_installed("wasm-opt")?.is_none() {
+        if required {
+            anyhow::bail!(
+                "wasm-opt is not installed, install binaryen from {BINARYEN_RELEASES} or set `wasm-opt = false`"
+            );
+        }
+        eprintln!(
+            "warning: wasm-opt is not installed, so the .wasm file isn't optimized. \
+             Install binaryen from {BINARYEN_RELEASES} or set `wasm-opt = false` to silence this warning"
+        );
+        return Ok(());
+    }
+
+    // Without `-g` wasm-opt removes the debug info.
+    if settings.debug_info != DebugInfo::None && !passes.iter().any(|p| p == "-g") {
+        passes.push("-g".into());
+    }
+
+    let wasm = output_path(&settings.out_dir, format!("{}_bg.wasm", settings.out_name));
+    eprintln!(
+        "Optimizing {} with wasm-opt {}",
+        wasm.display(),
+        passes.join(" ")
+    );
+
+    let exit_status = Command::new("wasm-opt")
+        .args(&passes)
+        .arg(&wasm)
+        .arg("-o")
+        .arg(&wasm)
+        .stdout(io::stderr())
+        .spawn()?
+        .wait()?;
+
+    match exit_status.success() {
+        true => Ok(()),
+        false => anyhow::bail!("wasm-opt exited with status {}", exit_status),
+    }
+}
+
+/// Checks that the wasm-bindgen CLI has the same version as the wasm-bindgen crate, it refuses
+/// to process modules of other versions.
+pub fn ensure_wasm_bindgen() -> Result<()> {
+    let locked = locked_wasm_bindgen_version()?;
+    let install = format!("cargo install -f wasm-bindgen-cli --version {locked}");
+
+    let Some(installed) = installed_wasm_bindgen_version()? else {
+        anyhow::bail!("wasm-bindgen is not installed, install it with `{install}`");
+    };
+
+    if installed != locked {
+        anyhow::bail!(
+            "the installed wasm-bindgen CLI has version {installed}, but the crate uses wasm-bindgen {locked} according to Cargo.lock. \
+             Both have to match exactly, install the right version with `{install}` \
+             or update the crate with `cargo update -p wasm-bindgen --precise {installed}`"
+        );
+    }
+
+    Ok(())
+}
+
+/// Returns the version of the wasm-bindgen CLI, `None` if it isn't installed.
+pub fn installed_wasm_bindgen_version() -> Result<Option<String>> {
+    let output = match Command::new("wasm-bindgen").arg("--version").output() {
+        Ok(output) => output,
+        Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(None),
+        Err(err) => return Err(err.into()),
+    };
+
+    // The output looks like `wasm-bindgen 0.2.92`.
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let version = stdout.split_whitespace().nth(1).ok_or_else(|| {
+        anyhow::anyhow!("unexpected output of `wasm-bindgen --version`: {stdout}")
+    })?;
+
+    Ok(Some(version.to_string()))
+}
+
+/// Returns the version of the wasm-bindgen crate from the Cargo.lock of the crate or its
+/// workspace.
+pub fn locked_wasm_bindgen_version() -> Result<String> {
+    let mut versions = locked_versions("wasm-bindgen")?;
+
+    match versions.len() {
+        0 => anyhow::bail!("the crate doesn't depend on wasm-bindgen"),
+        1 => Ok(versions.remove(0)),
+        _ => anyhow::bail!(
+            "the crate depends on multiple versions of wasm-bindgen ({}), there has to be exactly one",
+            versions.join(", ")
+        ),
+    }
+}
+
+/// Returns the versions of a package in the Cargo.lock of the crate or its workspace.
+pub fn locked_versions(name: &str) -> Result<Vec<String>> {
+    let lockfile_path = find_lockfile()?;
+    let lockfile: Lockfile = toml::from_str(&fs::read_to_string(&lockfile_path)?)
+        .with_context(|| format!("failed to parse {}", lockfile_path.display()))?;
+
+    Ok(lockfile
+        .package
+        .into_iter()
+        .filter(|p| p.name == name)
+        .map(|p| p.version)
+        .collect())
+}
+
+// The lockfile is next to the Cargo.toml of the workspace, which can be in any parent directory.
+fn find_lockfile() -> Result<PathBuf> {
+    let cwd = env::current_dir()?;
+    cwd.ancestors()
+        .map(|dir| dir.join("Cargo.lock"))
+        .find(|path| path.is_file())
+        .ok_or_else(|| anyhow::anyhow!("no Cargo.lock found, run `cargo generate-lockfile`"))
+}
@@ -0,0 +1,439 @@
+// Project configuration from `Activity.toml` or the `[package.metadata.activity]` table of the
+// crate's Cargo.toml.
+
+use std::{
+    collections::BTreeMap,
+    env,
+    ffi::OsString,
+    path::{Path, PathBuf},
+    sync::Mutex,
+};
+
+use anyhow::{Context, Result};
+use serde::Deserialize;
+
+use crate::{
+    cli::{BuildArgs, Bundler, DebugInfo},
+    dotenv, OUT_DIR, OUT_NAME,
+};
+
+const MANIFEST: &str = "Cargo.toml";
+const ACTIVITY_MANIFEST: &str = "Activity.toml";
+
+const DEFAULT_TITLE: &str = "My Activity";
+const DEFAULT_PUBLIC_DIR: &str = "public";
+const DEFAULT_TEMPLATE: &str = "index.html";
+const DEFAULT_EXPORT: &str = "start";
+
+#[derive(Deserialize, Default, Debug)]
+#[serde(rename_all = "kebab-case", deny_unknown_fields)]
+pub struct ActivityConfig {
+    /// The title of the generated index.html.
+    pub title: Option<String>,
+    /// An icon that is copied into the bundle and linked from the generated index.html.
+    pub favicon: Option<PathBuf>,
+    pub out_dir: Option<PathBuf>,
+    /// The base name of the generated .js and .wasm files.
+    pub out_name: Option<String>,
+    /// The JavaScript name of the `#[activity]` function, `start` by default. It has to match the
+    /// `export` option of the macro.
+    pub export: Option<String>,
+    /// A directory whose content is copied into the bundle, `public` by default.
+    pub public_dir: Option<PathBuf>,
+    /// Whether stylesheets and images get the hash of their content in their file name.
+    pub hash_assets: Option<bool>,
+    /// The template of the index.html, `index.html` by default.
+    pub template: Option<PathBuf>,
+    /// URL mappings as configured in the developer portal, prefix -> target.
+    #[serde(default)]
+    pub url_mappings: BTreeMap<String, String>,
+    /// `esbuild` or `builtin`.
+    pub bundler: Option<Bundler>,
+    #[serde(default)]
+    pub esbuild: EsbuildConfig,
+    #[serde(default)]
+    pub sdk: SdkConfig,
+    /// Whether esbuild writes a source map of the bundled JavaScript, `true` by default for dev
+    /// builds unless the built-in bundler is used.
+    pub source_map: Option<bool>,
+    pub debug_info: Option<DebugInfo>,
+    /// Where the bundled .wasm file says its separate debug info is, the path of the debug file
+    /// by default.
+    pub debug_info_url: Option<String>,
+    /// Whether and how the .wasm file is optimized with wasm-opt.
+    pub wasm_opt: Option<WasmOpt>,
+    #[serde(default)]
+    pub deploy: DeployConfig,
+    /// Size budgets of release builds by file name in the bundle, which can be a glob pattern.
+    #[serde(default)]
+    pub budgets: BTreeMap<String, Budget>,
+    /// Environment variables for cargo, `.env` files and the shell override them.
+    #[serde(default)]
+    pub env: BTreeMap<String, String>,
+    /// Environment variables the build fails without, e.g. the ones read with `build_env!`.
+    #[serde(default)]
+    pub required_env: Vec<String>,
+    /// Settings that only apply to `release` or `dev` builds.
+    #[serde(default)]
+    pub profile: BTreeMap<String, ProfileConfig>,
+}
+
+#[derive(Deserialize, Default, Debug, Clone)]
+#[serde(rename_all = "kebab-case", deny_unknown_fields)]
+pub struct ProfileConfig {
+    pub title: Option<String>,
+    pub minify: Option<bool>,
+    pub coredump: Option<bool>,
+    pub coredump_flags: Option<String>,
+    pub source_map: Option<bool>,
+    pub debug_info: Option<DebugInfo>,
+    pub wasm_opt: Option<WasmOpt>,
+    #[serde(default)]
+    pub env: BTreeMap<String, String>,
+}
+
+#[derive(Deserialize, Default, Debug, Clone)]
+#[serde(rename_all = "kebab-case", deny_unknown_fields)]
+pub struct EsbuildConfig {
+    /// A vendored esbuild binary or npm tarball of it, used instead of downloading it.
+    pub path: Option<PathBuf>,
+    /// Pinned checksums of the vendored file or downloaded npm tarball by esbuild platform, e.g.
+    /// `linux-64`.
+    #[serde(default)]
+    pub sha256: BTreeMap<String, String>,
+}
+
+/// The Discord SDK that is bundled, the one built into activity-build by default.
+#[derive(Deserialize, Default, Debug, Clone)]
+#[serde(rename_all = "kebab-case", deny_unknown_fields)]
+pub struct SdkConfig {
+    /// The version the build fails without. Other versions than the built-in one are taken from
+    /// `node_modules/@discord/embedded-app-sdk`.
+    pub version: Option<String>,
+    /// A vendored npm tarball of the SDK, used instead of `node_modules`.
+    pub path: Option<PathBuf>,
+}
+
+/// Extra output for deploying the bundle to a static host as it is.
+#[derive(Deserialize, Default, Debug, Clone)]
+#[serde(rename_all = "kebab-case", deny_unknown_fields)]
+pub struct DeployConfig {
+    /// Write `.gz` and `.br` files next to the files that compress well.
+    #[serde(default)]
+    pub precompress: bool,
+    /// The static hosts to write header files for.
+    #[serde(default)]
+    pub headers: Vec<StaticHost>,
+}
+
+#[derive(Deserialize, Clone, Copy, PartialEq, Eq, Debug)]
+#[serde(rename_all = "kebab-case")]
+pub enum StaticHost {
+    /// A `_headers` file.
+    Netlify,
+    /// A `_headers` file, Cloudflare Pages uses the same format as Netlify.
+    Cloudflare,
+    /// A `vercel.json` file.
+    Vercel,
+}
+
+/// `false` turns wasm-opt off, `true` runs it with the default passes and a list of flags runs it
+/// with those. It only runs for release builds unless configured otherwise.
+#[derive(Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(untagged)]
+pub enum WasmOpt {
+    Enabled(bool),
+    Passes(Vec<String>),
+}
+
+/// The maximum sizes of a file, uncompressed and compressed.
+#[derive(Deserialize, Default, Debug, Clone, Copy)]
+#[serde(rename_all = "kebab-case", deny_unknown_fields)]
+pub struct Budget {
+    pub raw: Option<ByteSize>,
+    pub gzip: Option<ByteSize>,
+    pub brotli: Option<ByteSize>,
+}
+
+/// A number of bytes, written as a number or a string like `"250 KiB"`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct ByteSize(pub u64);
+
+impl<'de> Deserialize<'de> for ByteSize {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        #[derive(Deserialize)]
+        #[serde(untagged)]
+        enum Value {
+            Bytes(u64),
+            Text(String),
+        }
+
+        match Value::deserialize(deserializer)? {
+            Value::Bytes(bytes) => Ok(ByteSize(bytes)),
+            Value::Text(text) => parse_size(&text).map_err(serde::de::Error::custom),
+        }
+    }
+}
+
+fn parse_size(text: &str) -> Result<ByteSize> {
+    let text = text.trim();
+    let split = text
+        .find(|c: char| !(c.is_ascii_digit() || c == '.'))
+        .unwrap_or(text.len());
+    let (number, unit) = text.split_at(split);
+
+    let number: f64 = number
+        .parse()
+        .map_err(|_| anyhow::anyhow!("invalid size `{text}`"))?;
+    let factor = match unit.trim() {
+        "" | "B" => 1,
+        "kB" | "KB" => 1000,
+        "KiB" => 1024,
+        "MB" => 1000 * 1000,
+        "MiB" => 1024 * 1024,
+        _ => {
+            anyhow::bail!("invalid size `{text}`, the unit has to be one of B, kB, KiB, MB or MiB")
+        }
+    };
+
+    Ok(ByteSize((number * factor as f64) as u64))
+}
+
+#[derive(Deserialize)]
+struct Manifest {
+    package: Option<Package>,
+}
+
+#[derive(Deserialize)]
+struct Package {
+    metadata: Option<Metadata>,
+}
+
+#[derive(Deserialize)]
+struct Metadata {
+    activity: Option<ActivityConfig>,
+}
+
+/// Loads the configuration from `Activity.toml` if it exists, Cargo.toml otherwise.
+pub fn load() -> Result<ActivityConfig> {
+    let path = Path::new(ACTIVITY_MANIFEST);
+    if path.exists() {
+        return toml::from_str(&std::fs::read_to_string(path)?)
+            .with_context(|| format!("failed to parse {ACTIVITY_MANIFEST}"));
+    }
+
+    let path = Path::new(MANIFEST);
+    if !path.exists() {
+        return Ok(ActivityConfig::default());
+    }
+
+    let manifest: Manifest = toml::from_str(&std::fs::read_to_string(path)?)
+        .with_context(|| format!("failed to parse {MANIFEST}"))?;
+
+    Ok(manifest
+        .package
+        .and_then(|p| p.metadata)
+        .and_then(|m| m.activity)
+        .unwrap_or_default())
+}
+
+impl ActivityConfig {
+    pub fn out_dir(&self, arg: Option<&Path>) -> PathBuf {
+        arg.or(self.out_dir.as_deref())
+            .unwrap_or(Path::new(OUT_DIR))
+            .to_path_buf()
+    }
+}
+
+/// Everything a single build needs to know, the command line takes precedence over the
+/// profile settings, which take precedence over the rest of the configuration.
+#[derive(Clone)]
+pub struct BuildSettings {
+    pub out_dir: PathBuf,
+    pub out_name: String,
+    pub export: String,
+    pub dev: bool,
+    pub minify: bool,
+    pub coredump: bool,
+    pub coredump_flags: Option<String>,
+    pub title: String,
+    pub favicon: Option<PathBuf>,
+    pub public_dir: Option<PathBuf>,
+    pub hash_assets: bool,
+    pub template: Option<PathBuf>,
+    pub bundler: Bundler,
+    pub esbuild: EsbuildConfig,
+    pub sdk: SdkConfig,
+    pub source_map: bool,
+    pub debug_info: DebugInfo,
+    pub debug_info_url: Option<String>,
+    /// `None` if it's neither configured nor turned off on the command line.
+    pub wasm_opt: Option<WasmOpt>,
+    pub budgets: BTreeMap<String, Budget>,
+    pub precompress: bool,
+    pub headers: Vec<StaticHost>,
+    /// The variables cargo gets on top of the environment.
+    pub env: BTreeMap<String, String>,
+    pub required_env: Vec<String>,
+    /// Don't access the network, like cargo's `--offline`.
+    pub offline: bool,
+    pub cargo_args: Vec<OsString>,
+}
+
+impl BuildSettings {
+    pub fn load(args: &BuildArgs) -> Result<Self> {
+        let mut settings = Self::new(args, load()?);
+        settings.env.extend(dotenv::load(settings.profile_name())?);
+        // Variables of the shell take precedence, cargo inherits them.
+        settings.env.retain(|name, _| env::var_os(name).is_none());
+        dotenv::check_required(&settings)?;
+        Ok(settings)
+    }
+
+    pub fn new(args: &BuildArgs, config: ActivityConfig) -> Self {
+        let profile_name = profile_name(args.dev);
+        let profile = config
+            .profile
+            .get(profile_name)
+            .cloned()
+            .unwrap_or_default();
+
+        let bundler = args.bundler.or(config.bundler).unwrap_or_default();
+
+        Self {
+            out_dir: config.out_dir(args.out_dir.as_deref()),
+            out_name: config.out_name.unwrap_or_else(|| OUT_NAME.into()),
+            export: config.export.unwrap_or_else(|| DEFAULT_EXPORT.into()),
+            dev: args.dev,
+            minify: args
+                .minify()
+                .or(profile.minify)
+                .or_else(|| deprecated_env("NO_MINIFY", "`--no-minify`").map(|_| false))
+                .unwrap_or(!args.dev),
+            coredump: args.coredump
+                || profile
+                    .coredump
+                    .or_else(|| deprecated_env("COREDUMP", "`--coredump`").map(|_| true))
+                    .unwrap_or_default(),
+            coredump_flags: args
+                .coredump_flags
+                .clone()
+                .or(profile.coredump_flags)
+                .or_else(|| deprecated_env("COREDUMP_FLAGS", "`--coredump-flags`")),
+            title: profile
+                .title
+                .or(config.title)
+                .unwrap_or_else(|| DEFAULT_TITLE.into()),
+            favicon: config.favicon,
+            public_dir: config.public_dir.or_else(|| existing(DEFAULT_PUBLIC_DIR)),
+            hash_assets: config.hash_assets.unwrap_or(true),
+            template: config.template.or_else(|| existing(DEFAULT_TEMPLATE)),
+            bundler,
+            esbuild: config.esbuild,
+            sdk: config.sdk,
+            // Release builds are deployed, so they don't publish the sources unless asked to, and
+            // the built-in bundler doesn't write source maps.
+            source_map: profile
+                .source_map
+                .or(config.source_map)
+                .unwrap_or(args.dev && bundler == Bundler::Esbuild),
+            debug_info: args
+                .debug_info
+                .or(profile.debug_info)
+                .or(config.debug_info)
+                .unwrap_or_default(),
+            debug_info_url: config.debug_info_url,
+            wasm_opt: args
+                .no_wasm_opt
+                .then_some(WasmOpt::Enabled(false))
+                .or(profile.wasm_opt)
+                .or(config.wasm_opt),
+            budgets: config.budgets,
+            precompress: args.precompress || config.deploy.precompress,
+            headers: config.deploy.headers,
+            env: config.env.into_iter().chain(profile.env).collect(),
+            required_env: config.required_env,
+            offline: args.offline
+                || env::var("CARGO_NET_OFFLINE").is_ok_and(|v| v == "true" || v == "1"),
+            cargo_args: args.cargo_args.clone(),
+        }
+    }
+
+    /// `dev` or `release`.
+    pub fn profile_name(&self) -> &'static str {
+        profile_name(self.dev)
+    }
+}
+
+fn profile_name(dev: bool) -> &'static str {
+    if dev {
+        "dev"
+    } else {
+        "release"
+    }
+}
+
+// The variables that turned these settings on before there were options for them. They still do
+// when neither the command line nor the configuration sets them. The warning is only printed once,
+// `serve` loads the settings for every build.
+fn deprecated_env(name: &'static str, option: &str) -> Option<String> {
+    static WARNED: Mutex<Vec<&str>> = Mutex::new(Vec::new());
+
+    let value = env::var(name).ok()?;
+    let mut warned = WARNED.lock().unwrap();
+    if !warned.contains(&name) {
+        warned.push(name);
+        eprintln!("warning: {name} is deprecated, use {option} or the configuration instead");
+    }
+    Some(value)
+}
+
+// The conventional paths are only used when they exist.
+fn existing(path: &str) -> Option<PathBuf> {
+    let path = PathBuf::from(path);
+    path.exists().then_some(path)
+}
+
+#[cfg(test)]
+mod tests {
+    use clap::Parser;
+
+    use super::*;
+
+    fn settings(args: &[&str], config: &str) -> BuildSettings {
+        #[derive(Parser)]
+        struct Cli {
+            #[command(flatten)]
+            build: BuildArgs,
+        }
+
+        let cli = Cli::parse_from(std::iter::once("activity-build").chain(args.iter().copied()));
+        BuildSettings::new(&cli.build, toml::from_str(config).unwrap())
+    }
+
+    #[test]
+    fn source_maps_default_to_dev_builds_with_esbuild() {
+        assert!(settings(&["--dev"], "").source_map);
+        assert!(!settings(&[], "").source_map);
+        assert!(!settings(&["--dev", "--bundler", "builtin"], "").source_map);
+        assert!(!settings(&["--dev"], "bundler = \"builtin\"").source_map);
+        assert!(settings(&[], "source-map = true").source_map);
+        // Requested ones are kept, so the build can warn about them.
+        assert!(settings(&["--bundler", "builtin"], "source-map = true").source_map);
+    }
+
+    #[test]
+    fn sizes() {
+        assert_eq!(parse_size("250").unwrap(), ByteSize(250));
+        assert_eq!(parse_size("250 B").unwrap(), ByteSize(250));
+        assert_eq!(parse_size("2kB").unwrap(), ByteSize(2000));
+        assert_eq!(parse_size("2 KB").unwrap(), ByteSize(2000));
+        assert_eq!(parse_size("1.5 KiB").unwrap(), ByteSize(1536));
+        assert_eq!(parse_size(" 3 MB ").unwrap(), ByteSize(3_000_000));
+        assert_eq!(parse_size("1 MiB").unwrap(), ByteSize(1024 * 1024));
+
+        for text in ["", "KiB", "1 GB", "1 kib", "1..2 kB", "-1 B"] {
+            assert!(parse_size(text).is_err(), "{text}");
+        }
+    }
+}
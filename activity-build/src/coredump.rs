@@ -0,0 +1,259 @@
+// Reads the coredumps that modules rewritten by wasm-coredump-rewriter write when they trap and
+// prints their stack with the debug info of the module.
+// https://github.com/WebAssembly/tool-conventions/blob/main/Coredump.md
+
+use std::{fmt::Display, fs};
+
+use anyhow::{Context, Result};
+
+use crate::{
+    cli::InspectArgs,
+    debug::{self, Symbolicator},
+    wasm,
+};
+
+const HEADER_LEN: usize = 8;
+const LAST_SECTION_ID: u8 = 12;
+
+struct Coredump {
+    executable: Option<String>,
+    modules: Vec<String>,
+    thread: String,
+    frames: Vec<Frame>,
+}
+
+struct Frame {
+    function: u32,
+    code_offset: u32,
+    locals: Vec<Value>,
+    stack: Vec<Value>,
+}
+
+enum Value {
+    /// The value wasn't known when the dump was taken.
+    Missing,
+    I32(i32),
+    I64(i64),
+    F32(f32),
+    F64(f64),
+}
+
+impl Display for Value {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Value::Missing => write!(f, "<missing>"),
+            Value::I32(v) => write!(f, "i32 {v} ({:#x})", *v as u32),
+            Value::I64(v) => write!(f, "i64 {v} ({:#x})", *v as u64),
+            Value::F32(v) => write!(f, "f32 {v}"),
+            Value::F64(v) => write!(f, "f64 {v}"),
+        }
+    }
+}
+
+pub fn inspect(args: &InspectArgs) -> Result<()> {
+    let data =
+        fs::read(&args.file).with_context(|| format!("failed to read {}", args.file.display()))?;
+    let dump = parse(&data).with_context(|| {
+        format!(
+            "{} is not a coredump, was the activity built with `--coredump`?",
+            args.file.display()
+        )
+    })?;
+
+    let (path, wasm) = debug::read_debug_wasm(args.wasm.as_deref())?;
+    let symbolicator = Symbolicator::new(&wasm)?;
+    symbolicator.warn_without_dwarf(&path);
+
+    // Frames of functions the module doesn't have mean the dump is of another build.
+    for frame in &dump.frames {
+        if symbolicator.info.function(frame.function).is_none() {
+            anyhow::bail!(
+                "the coredump doesn't match {}, it has no function {}. \
+                 Pass the .wasm file of the build that crashed with `--wasm`",
+                path.display(),
+                frame.function
+            );
+        }
+    }
+
+    let executable = dump.executable.as_deref().unwrap_or("<unknown>");
+    println!("Coredump of {executable} on thread {}", dump.thread);
+    if !dump.modules.is_empty() {
+        println!("Modules: {}", dump.modules.join(", "));
+    }
+
+    for (i, frame) in dump.frames.iter().enumerate() {
+        let function = symbolicator.info.function(frame.function).unwrap();
+        let offset = function.range.start + frame.code_offset as usize;
+
+        println!("\n#{i} function[{}] at {offset:#x}", frame.function);
+        for location in symbolicator.frames(offset)? {
+            println!("    {location}");
+        }
+
+        let names = symbolicator.local_names(offset)?;
+        if !frame.locals.is_empty() {
+            println!("    locals:");
+        }
+        for (index, value) in frame.locals.iter().enumerate() {
+            match names.get(&(index as u32)) {
+                Some(name) => println!("      {index}: {name} = {value}"),
+                None => println!("      {index}: {value}"),
+            }
+        }
+
+        if !frame.stack.is_empty() {
+            println!("    stack:");
+        }
+        for value in &frame.stack {
+            println!("      {value}");
+        }
+    }
+
+    Ok(())
+}
+
+fn parse(data: &[u8]) -> Result<Coredump> {
+    // Uploaded dumps are the whole memory of the module, which only starts with the dump.
+    let data = &data[..dump_len(data)?];
+    let sections = wasm::custom_sections(data)?;
+
+    let executable = match sections.get("core") {
+        Some(content) => {
+            let pos = &mut 0;
+            expect_zero(content, pos)?;
+            Some(read_name(content, pos)?)
+        }
+        None => None,
+    };
+
+    let mut modules = Vec::new();
+    if let Some(content) = sections.get("coremodules") {
+        let pos = &mut 0;
+        for _ in 0..wasm::read_u32(content, pos)? {
+            expect_zero(content, pos)?;
+            modules.push(read_name(content, pos)?);
+        }
+    }
+
+    let content = sections
+        .get("corestack")
+        .ok_or_else(|| anyhow::anyhow!("the `corestack` section is missing"))?;
+    let pos = &mut 0;
+    expect_zero(content, pos)?;
+    let thread = read_name(content, pos)?;
+
+    let mut frames = Vec::new();
+    for _ in 0..wasm::read_u32(content, pos)? {
+        expect_zero(content, pos)?;
+        let _instance = wasm::read_u32(content, pos)?;
+        frames.push(Frame {
+            function: wasm::read_u32(content, pos)?,
+            code_offset: wasm::read_u32(content, pos)?,
+            locals: read_values(content, pos)?,
+            stack: read_values(content, pos)?,
+        });
+    }
+
+    Ok(Coredump {
+        executable,
+        modules,
+        thread,
+        frames,
+    })
+}
+
+// The length of the sections at the start of the data, anything after them isn't part of the dump.
+fn dump_len(data: &[u8]) -> Result<usize> {
+    if !data.starts_with(b"\0asm") || data.len() < HEADER_LEN {
+        anyhow::bail!("it doesn't start with a Wasm header");
+    }
+
+    let mut pos = HEADER_LEN;
+    while pos < data.len() {
+        let id = data[pos];
+        let mut content_pos = pos + 1;
+        let Ok(size) = wasm::read_u32(data, &mut content_pos) else {
+            break;
+        };
+        let Some(content) = data.get(content_pos..content_pos + size as usize) else {
+            break;
+        };
+        let valid = match id {
+            0 => read_name(content, &mut 0).is_ok(),
+            id => id <= LAST_SECTION_ID,
+        };
+        if !valid {
+            break;
+        }
+        pos = content_pos + content.len();
+    }
+
+    Ok(pos)
+}
+
+fn read_name(bytes: &[u8], pos: &mut usize) -> Result<String> {
+    let name = wasm::decode_string(&bytes[*pos..])?;
+    *pos += wasm::encode_string(name).len();
+    Ok(name.to_owned())
+}
+
+fn expect_zero(bytes: &[u8], pos: &mut usize) -> Result<()> {
+    match bytes.get(*pos) {
+        Some(0) => {
+            *pos += 1;
+            Ok(())
+        }
+        _ => anyhow::bail!("unsupported coredump version at byte {pos}"),
+    }
+}
+
+fn read_values(bytes: &[u8], pos: &mut usize) -> Result<Vec<Value>> {
+    let count = wasm::read_u32(bytes, pos)?;
+    let mut values = Vec::new();
+    for _ in 0..count {
+        let kind = *bytes
+            .get(*pos)
+            .ok_or_else(|| anyhow::anyhow!("unexpected end of coredump"))?;
+        *pos += 1;
+        values.push(match kind {
+            0x01 => Value::Missing,
+            0x7f => Value::I32(read_i64(bytes, pos)? as i32),
+            0x7e => Value::I64(read_i64(bytes, pos)?),
+            0x7d => Value::F32(f32::from_le_bytes(read_bytes(bytes, pos)?)),
+            0x7c => Value::F64(f64::from_le_bytes(read_bytes(bytes, pos)?)),
+            kind => anyhow::bail!("unsupported value type {kind:#x} at byte {}", *pos - 1),
+        });
+    }
+    Ok(values)
+}
+
+// Reads a signed LEB128 number.
+fn read_i64(bytes: &[u8], pos: &mut usize) -> Result<i64> {
+    let mut result: i64 = 0;
+    let mut shift = 0;
+    loop {
+        let byte = *bytes
+            .get(*pos)
+            .ok_or_else(|| anyhow::anyhow!("unexpected end of coredump"))?;
+        *pos += 1;
+        if shift < 64 {
+            result |= ((byte & 0x7f) as i64) << shift;
+        }
+        shift += 7;
+        if byte & 0x80 == 0 {
+            if shift < 64 && byte & 0x40 != 0 {
+                result |= -1 << shift;
+            }
+            return Ok(result);
+        }
+    }
+}
+
+fn read_bytes<const N: usize>(bytes: &[u8], pos: &mut usize) -> Result<[u8; N]> {
+    let value = bytes
+        .get(*pos..*pos + N)
+        .ok_or_else(|| anyhow::anyhow!("unexpected end of coredump"))?;
+    *pos += N;
+    Ok(value.try_into().unwrap())
+}
@@ -0,0 +1,358 @@
+// DWARF debug info of the Wasm module: moving it into a separate file that the bundled module
+// links to, and turning code offsets from stack traces into Rust source locations with it.
+
+use std::{
+    collections::BTreeMap,
+    fs,
+    io::{self, BufRead},
+    path::{Path, PathBuf},
+};
+
+use addr2line::{
+    gimli::{self, EndianSlice, LittleEndian},
+    Context,
+};
+use anyhow::{Context as _, Result};
+
+use crate::{
+    activity_path,
+    cli::SymbolicateArgs,
+    config::{self, BuildSettings},
+    output_path,
+    wasm::{self, ModuleInfo},
+    OUT_NAME,
+};
+
+/// The custom section that tells debuggers where the debug info of a module is.
+/// https://github.com/WebAssembly/tool-conventions/blob/main/Debugging.md#external-dwarf
+const EXTERNAL_DEBUG_INFO: &str = "external_debug_info";
+
+/// The separate debug info is written next to the bundle, so it isn't deployed.
+pub fn debug_file_path(out_dir: &Path, out_name: &str) -> PathBuf {
+    output_path(out_dir, format!("{out_name}_bg.debug.wasm"))
+}
+
+/// Moves the DWARF sections of the generated .wasm file into a separate file and links to it.
+pub fn split_debug_info(settings: &BuildSettings) -> Result<()> {
+    let wasm_path = output_path(&settings.out_dir, format!("{}_bg.wasm", settings.out_name));
+    let debug_path = debug_file_path(&settings.out_dir, &settings.out_name);
+
+    let wasm = fs::read(&wasm_path)?;
+    fs::write(&debug_path, &wasm)?;
+
+    let url = match &settings.debug_info_url {
+        Some(url) => url.clone(),
+        None => file_url(&debug_path.canonicalize()?),
+    };
+
+    // The debug sections come after the code, so code offsets stay the same in both files.
+    let mut stripped = wasm::remove_custom_sections(&wasm, |name| {
+        name.starts_with(".debug_") || name == EXTERNAL_DEBUG_INFO
+    })?;
+    stripped.extend(wasm::custom_section(
+        EXTERNAL_DEBUG_INFO,
+        &wasm::encode_string(&url),
+    ));
+    fs::write(&wasm_path, stripped)?;
+
+    eprintln!("Wrote the debug info to {}", debug_path.display());
+
+    Ok(())
+}
+
+fn file_url(path: &Path) -> String {
+    let path = path.to_string_lossy().replace('\\', "/");
+    match path.starts_with('/') {
+        true => format!("file://{path}"),
+        false => format!("file:///{path}"),
+    }
+}
+
+pub fn symbolicate(args: &SymbolicateArgs) -> Result<()> {
+    let (path, wasm) = read_debug_wasm(args.wasm.as_deref())?;
+    let symbolicator = Symbolicator::new(&wasm)?;
+    symbolicator.warn_without_dwarf(&path);
+
+    if !args.offsets.is_empty() {
+        for offset in &args.offsets {
+            println!("{offset}");
+            for frame in symbolicator.frames(parse_offset(offset)?)? {
+                println!("    {frame}");
+            }
+        }
+        return Ok(());
+    }
+
+    // Annotate every frame of a stack trace on stdin.
+    for line in io::stdin().lock().lines() {
+        let line = line?;
+        println!("{line}");
+        if let Some(offset) = offset_in_line(&line) {
+            for frame in symbolicator.frames(offset)? {
+                println!("        {frame}");
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Reads the given module or the one of the last build, or the separate debug info it links to.
+pub fn read_debug_wasm(path: Option<&Path>) -> Result<(PathBuf, Vec<u8>)> {
+    let path = match path {
+        Some(path) => path.to_owned(),
+        None => default_wasm_path()?,
+    };
+    let wasm = fs::read(&path).with_context(|| format!("failed to read {}", path.display()))?;
+
+    // A bundled module with separate debug info can point to it.
+    match external_debug_file(&wasm)? {
+        Some(debug_path) => {
+            let debug_wasm = fs::read(&debug_path)
+                .with_context(|| format!("failed to read {}", debug_path.display()))?;
+            Ok((debug_path, debug_wasm))
+        }
+        None => Ok((path, wasm)),
+    }
+}
+
+// The separate debug file of the last build if there is one, the bundled module otherwise.
+fn default_wasm_path() -> Result<PathBuf> {
+    let config = config::load()?;
+    let out_dir = config.out_dir(None);
+    let out_name = config.out_name.as_deref().unwrap_or(OUT_NAME);
+
+    let debug_path = debug_file_path(&out_dir, out_name);
+    if debug_path.is_file() {
+        return Ok(debug_path);
+    }
+
+    Ok(activity_path(&out_dir, format!("{out_name}_bg.wasm")))
+}
+
+// The file the `external_debug_info` section of the module points to, if it's a local one.
+fn external_debug_file(wasm: &[u8]) -> Result<Option<PathBuf>> {
+    let sections = wasm::custom_sections(wasm)?;
+    if sections.contains_key(".debug_info") {
+        return Ok(None);
+    }
+
+    let Some(content) = sections.get(EXTERNAL_DEBUG_INFO) else {
+        return Ok(None);
+    };
+    let Some(path) = wasm::decode_string(content)?.strip_prefix("file://") else {
+        return Ok(None);
+    };
+
+    // `file:///C:/...` on Windows.
+    let path = match path.get(2..3) {
+        Some(":") => &path[1..],
+        _ => path,
+    };
+    let path = PathBuf::from(path);
+    Ok(path.is_file().then_some(path))
+}
+
+// Offsets are hexadecimal with a `0x` prefix, like in stack traces, or decimal.
+fn parse_offset(offset: &str) -> Result<usize> {
+    let parsed = match offset.strip_prefix("0x") {
+        Some(hex) => usize::from_str_radix(hex, 16),
+        None => offset.parse(),
+    };
+    parsed.map_err(|_| anyhow::anyhow!("invalid offset `{offset}`, expected something like 0x1a2b"))
+}
+
+// Chrome and Firefox show frames in Wasm code like `wasm-function[42]:0x1a2b`.
+fn offset_in_line(line: &str) -> Option<usize> {
+    let rest = &line[line.find("wasm-function[")?..];
+    let hex = &rest[rest.find("]:0x")? + "]:0x".len()..];
+    let end = hex
+        .find(|c: char| !c.is_ascii_hexdigit())
+        .unwrap_or(hex.len());
+    usize::from_str_radix(&hex[..end], 16).ok()
+}
+
+/// Looks up the Rust functions and source locations of code offsets in a module.
+pub struct Symbolicator<'a> {
+    pub info: ModuleInfo,
+    context: Option<Context<EndianSlice<'a, LittleEndian>>>,
+}
+
+impl<'a> Symbolicator<'a> {
+    pub fn new(wasm: &'a [u8]) -> Result<Self> {
+        let info = ModuleInfo::parse(wasm)?;
+        let sections = wasm::custom_sections(wasm)?;
+
+        let context = match sections.contains_key(".debug_info") {
+            true => {
+                let dwarf = gimli::Dwarf::load(|id| -> Result<_, gimli::Error> {
+                    let data = sections.get(id.name()).copied().unwrap_or_default();
+                    Ok(EndianSlice::new(data, LittleEndian))
+                })?;
+                Some(Context::from_dwarf(dwarf)?)
+            }
+            false => None,
+        };
+
+        Ok(Self { info, context })
+    }
+
+    pub fn warn_without_dwarf(&self, path: &Path) {
+        if self.context.is_none() {
+            eprintln!(
+                "warning: {} has no DWARF debug info, so only function names can be shown. \
+                 Build with `--debug-info separate` or `--debug-info embedded` to get source locations",
+                path.display()
+            );
+        }
+    }
+
+    /// The frames at the offset in the module, inlined functions first.
+    pub fn frames(&self, offset: usize) -> Result<Vec<String>> {
+        let mut frames = Vec::new();
+
+        // DWARF addresses are relative to the start of the code section.
+        let address = self.info.code_offset.and_then(|o| offset.checked_sub(o));
+        if let (Some(context), Some(address)) = (&self.context, address) {
+            let mut iter = context.find_frames(address as u64).skip_all_loads()?;
+            while let Some(frame) = iter.next()? {
+                let function = match &frame.function {
+                    Some(function) => wasm::demangle(&function.raw_name()?),
+                    None => "<unknown>".into(),
+                };
+                let frame = match frame.location {
+                    Some(location) => format!(
+                        "{function} at {}:{}:{}",
+                        location.file.unwrap_or("<unknown>"),
+                        location.line.unwrap_or_default(),
+                        location.column.unwrap_or_default()
+                    ),
+                    None => function,
+                };
+                frames.push(frame);
+            }
+        }
+
+        if frames.is_empty() {
+            let function = self.info.function_at(offset);
+            frames.push(match function {
+                Some(function) => match &function.name {
+                    Some(name) => name.clone(),
+                    None => format!("function[{}]", function.index),
+                },
+                None => "<not in a function>".into(),
+            });
+        }
+
+        Ok(frames)
+    }
+
+    /// The names of the Wasm locals that hold variables at the offset, by local index.
+    pub fn local_names(&self, offset: usize) -> Result<BTreeMap<u32, String>> {
+        let mut names = BTreeMap::new();
+
+        let address = self.info.code_offset.and_then(|o| offset.checked_sub(o));
+        let (Some(context), Some(address)) = (&self.context, address) else {
+            return Ok(names);
+        };
+        let address = address as u64;
+        let Some((dwarf, unit)) = context.find_dwarf_and_unit(address).skip_all_loads() else {
+            return Ok(names);
+        };
+
+        let mut tree = unit.entries_tree(None)?;
+        collect_local_names(dwarf, unit, tree.root()?, address, &mut names)?;
+        Ok(names)
+    }
+}
+
+type Reader<'a> = EndianSlice<'a, LittleEndian>;
+
+// Walks down the functions, inlined functions and blocks that contain the address and collects
+// the variables that are in a Wasm local there. Inner scopes are visited last, so their variables
+// win over shadowed ones.
+fn collect_local_names(
+    dwarf: &gimli::Dwarf<Reader>,
+    unit: &gimli::Unit<Reader>,
+    node: gimli::EntriesTreeNode<Reader>,
+    address: u64,
+    names: &mut BTreeMap<u32, String>,
+) -> Result<()> {
+    let mut children = node.children();
+    while let Some(child) = children.next()? {
+        let entry = child.entry();
+        match entry.tag() {
+            gimli::DW_TAG_formal_parameter | gimli::DW_TAG_variable => {
+                if let Some((index, name)) = local_variable(dwarf, unit, entry, address)? {
+                    names.insert(index, name);
+                }
+            }
+            gimli::DW_TAG_subprogram
+            | gimli::DW_TAG_inlined_subroutine
+            | gimli::DW_TAG_lexical_block => {
+                let mut ranges = dwarf.die_ranges(unit, entry)?;
+                let mut contains = false;
+                while let Some(range) = ranges.next()? {
+                    contains |= (range.begin..range.end).contains(&address);
+                }
+                if contains {
+                    collect_local_names(dwarf, unit, child, address, names)?;
+                }
+            }
+            // Namespaces, types and the like can contain functions.
+            _ => collect_local_names(dwarf, unit, child, address, names)?,
+        }
+    }
+    Ok(())
+}
+
+// The local index and name of a variable whose location at the address is a Wasm local.
+fn local_variable(
+    dwarf: &gimli::Dwarf<Reader>,
+    unit: &gimli::Unit<Reader>,
+    entry: &gimli::DebuggingInformationEntry<Reader>,
+    address: u64,
+) -> Result<Option<(u32, String)>> {
+    let (Some(name), Some(location)) = (
+        entry.attr_value(gimli::DW_AT_name)?,
+        entry.attr_value(gimli::DW_AT_location)?,
+    ) else {
+        return Ok(None);
+    };
+
+    let expression = match location {
+        gimli::AttributeValue::Exprloc(expression) => Some(expression),
+        location => {
+            let mut expression = None;
+            if let Some(mut locations) = dwarf.attr_locations(unit, location)? {
+                while let Some(entry) = locations.next()? {
+                    if (entry.range.begin..entry.range.end).contains(&address) {
+                        expression = Some(entry.data);
+                    }
+                }
+            }
+            expression
+        }
+    };
+
+    let Some(index) = expression.and_then(|e| wasm_local(e.0.slice())) else {
+        return Ok(None);
+    };
+    let name = dwarf
+        .attr_string(unit, name)?
+        .to_string_lossy()
+        .into_owned();
+    Ok(Some((index, name)))
+}
+
+// `DW_OP_WASM_location 0x0 index` puts a variable in a Wasm local.
+// https://yurydelendik.github.io/webassembly-dwarf/#DWARF-expressions-and-location-descriptions
+fn wasm_local(expression: &[u8]) -> Option<u32> {
+    const DW_OP_WASM_LOCATION: u8 = 0xed;
+    const LOCAL: u8 = 0x0;
+
+    match expression {
+        [DW_OP_WASM_LOCATION, LOCAL, index @ ..] => wasm::read_u32(index, &mut 0).ok(),
+        _ => None,
+    }
+}
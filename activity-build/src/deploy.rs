@@ -0,0 +1,250 @@
+// Output that lets static hosts serve the bundle as it is: precompressed copies of the files, a
+// manifest of the bundle and files with the headers the files should be served with.
+
+use std::{
+    collections::BTreeMap,
+    fs,
+    path::{Path, PathBuf},
+};
+
+use anyhow::Result;
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+
+use crate::{
+    activity_path,
+    assets::Assets,
+    config::{BuildSettings, StaticHost},
+    sdk::Sdk,
+    serve::content_type,
+    size, ACTIVITY_SUBDIR,
+};
+
+const MANIFEST: &str = "manifest.json";
+
+const COMPRESSED_EXTENSIONS: &[&str] = &[
+    "html", "js", "mjs", "css", "wasm", "json", "map", "svg", "txt",
+];
+
+const IMMUTABLE: &str = "public, max-age=31536000, immutable";
+const REVALIDATE: &str = "no-cache";
+
+#[derive(Serialize)]
+struct Manifest {
+    /// The version of the Discord SDK in the bundle.
+    sdk_version: String,
+    files: BTreeMap<String, ManifestEntry>,
+}
+
+#[derive(Serialize)]
+struct ManifestEntry {
+    size: u64,
+    sha256: String,
+    content_type: &'static str,
+    /// `gzip` or `br` for precompressed copies.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    content_encoding: Option<&'static str>,
+    /// Whether the name of the file changes with its content.
+    immutable: bool,
+}
+
+/// Writes the precompressed files, the header files and the manifest into the bundle.
+pub fn write_deploy_files(settings: &BuildSettings, assets: &Assets, sdk: &Sdk) -> Result<()> {
+    let activity_dir = settings.out_dir.join(ACTIVITY_SUBDIR);
+    // Precompressed copies don't get headers of their own, hosts serve them for the original.
+    let files = list_files(&activity_dir)?;
+
+    if settings.precompress {
+        for (_, path) in &files {
+            precompress(path)?;
+        }
+    }
+
+    let headers: Vec<(String, Vec<(&str, &str)>)> = files
+        .iter()
+        .map(|(name, path)| (format!("/{name}"), file_headers(assets, name, path)))
+        .collect();
+
+    let mut written = Vec::new();
+    for host in &settings.headers {
+        let (name, contents) = match host {
+            StaticHost::Netlify | StaticHost::Cloudflare => ("_headers", headers_file(&headers)),
+            StaticHost::Vercel => ("vercel.json", vercel_json(&headers)?),
+        };
+        if !written.contains(&name) {
+            fs::write(activity_path(&settings.out_dir, name), contents)?;
+            written.push(name);
+        }
+    }
+
+    write_manifest(&activity_dir, assets, sdk)
+}
+
+// Lists the files of the bundle by their path in it.
+fn list_files(activity_dir: &Path) -> Result<Vec<(String, PathBuf)>> {
+    fn visit(dir: &Path, files: &mut Vec<PathBuf>) -> Result<()> {
+        for entry in fs::read_dir(dir)? {
+            let path = entry?.path();
+            if path.is_dir() {
+                visit(&path, files)?;
+            } else {
+                files.push(path);
+            }
+        }
+        Ok(())
+    }
+
+    let mut paths = Vec::new();
+    visit(activity_dir, &mut paths)?;
+    paths.sort();
+
+    paths
+        .into_iter()
+        .map(|path| Ok((relative_name(activity_dir, &path)?, path)))
+        .collect()
+}
+
+fn relative_name(activity_dir: &Path, path: &Path) -> Result<String> {
+    Ok(path
+        .strip_prefix(activity_dir)?
+        .to_string_lossy()
+        .replace('\\', "/"))
+}
+
+// Writes `.gz` and `.br` files next to the file, unless it doesn't get smaller.
+fn precompress(path: &Path) -> Result<()> {
+    let extension = path
+        .extension()
+        .and_then(|e| e.to_str())
+        .unwrap_or_default();
+    if !COMPRESSED_EXTENSIONS.contains(&extension) {
+        return Ok(());
+    }
+
+    let data = fs::read(path)?;
+    for (suffix, compressed) in [("gz", size::gzip(&data)?), ("br", size::brotli(&data)?)] {
+        if compressed.len() < data.len() {
+            let mut name = path.as_os_str().to_owned();
+            name.push(".");
+            name.push(suffix);
+            fs::write(name, compressed)?;
+        }
+    }
+
+    Ok(())
+}
+
+// Hashed assets can be cached forever, everything else has to be revalidated so a new deploy is
+// picked up. Some hosts don't know the content type of .wasm files, which breaks streaming
+// compilation.
+fn file_headers(assets: &Assets, name: &str, path: &Path) -> Vec<(&'static str, &'static str)> {
+    let mut headers = vec![(
+        "Cache-Control",
+        if is_hashed(assets, name) {
+            IMMUTABLE
+        } else {
+            REVALIDATE
+        },
+    )];
+
+    if path.extension().is_some_and(|e| e == "wasm") {
+        headers.push(("Content-Type", content_type(path)));
+    }
+
+    headers
+}
+
+// The `_headers` format of Netlify and Cloudflare Pages.
+fn headers_file(headers: &[(String, Vec<(&str, &str)>)]) -> Vec<u8> {
+    let mut contents = String::new();
+    for (url, headers) in headers {
+        contents.push_str(url);
+        contents.push('\n');
+        for (name, value) in headers {
+            contents.push_str(&format!("  {name}: {value}\n"));
+        }
+    }
+    contents.into_bytes()
+}
+
+// https://vercel.com/docs/projects/project-configuration#headers
+fn vercel_json(headers: &[(String, Vec<(&str, &str)>)]) -> Result<Vec<u8>> {
+    #[derive(Serialize)]
+    struct Config<'a> {
+        headers: Vec<Rule<'a>>,
+    }
+
+    #[derive(Serialize)]
+    struct Rule<'a> {
+        source: &'a str,
+        headers: Vec<Header<'a>>,
+    }
+
+    #[derive(Serialize)]
+    struct Header<'a> {
+        key: &'a str,
+        value: &'a str,
+    }
+
+    let rules: Vec<Rule> = headers
+        .iter()
+        .map(|(url, headers)| Rule {
+            source: url,
+            headers: headers
+                .iter()
+                .map(|&(key, value)| Header { key, value })
+                .collect(),
+        })
+        .collect();
+
+    Ok(serde_json::to_vec_pretty(&Config { headers: rules })?)
+}
+
+fn is_hashed(assets: &Assets, name: &str) -> bool {
+    assets
+        .hashed
+        .iter()
+        .any(|url| url.strip_prefix('/') == Some(name))
+}
+
+fn write_manifest(activity_dir: &Path, assets: &Assets, sdk: &Sdk) -> Result<()> {
+    let all_files = list_files(activity_dir)?;
+    let is_file = |name: &str| all_files.iter().any(|(n, _)| n == name);
+
+    let mut files = BTreeMap::new();
+    for (name, path) in &all_files {
+        if name == MANIFEST {
+            continue;
+        }
+
+        // Precompressed copies are described by the file they are a copy of.
+        let (original, content_encoding) = match name.rsplit_once('.') {
+            Some((original, "gz")) if is_file(original) => (original, Some("gzip")),
+            Some((original, "br")) if is_file(original) => (original, Some("br")),
+            _ => (name.as_str(), None),
+        };
+
+        let data = fs::read(path)?;
+        let entry = ManifestEntry {
+            size: data.len() as u64,
+            sha256: Sha256::digest(&data)
+                .iter()
+                .map(|b| format!("{b:02x}"))
+                .collect(),
+            content_type: content_type(Path::new(original)),
+            content_encoding,
+            immutable: is_hashed(assets, original),
+        };
+        files.insert(name.clone(), entry);
+    }
+
+    fs::write(
+        activity_dir.join(MANIFEST),
+        serde_json::to_vec_pretty(&Manifest {
+            sdk_version: sdk.version.clone(),
+            files,
+        })?,
+    )?;
+
+    Ok(())
+}
@@ -0,0 +1,236 @@
+// Checks that the tools needed to build an activity are installed and that the crate is set up
+// the way the build expects.
+
+use std::{env, fs, path::Path, process::Command};
+
+use anyhow::Result;
+
+use crate::{
+    cli::{BuildArgs, Bundler},
+    compile::{self, WASM_TARGET},
+    config::{self, BuildSettings},
+    dotenv, install, sdk,
+};
+
+/// The variable the templates and examples read the client ID from.
+const CLIENT_ID: &str = "CLIENT_ID";
+
+/// A check that passed, possibly with something that should be looked at.
+enum Outcome {
+    Ok(String),
+    Warn(String),
+}
+
+pub fn doctor() -> Result<()> {
+    let settings = config::load().map(|config| BuildSettings::new(&BuildArgs::default(), config));
+    let checks = [
+        ("wasm32 target", check_wasm_target()),
+        ("wasm-bindgen", check_wasm_bindgen()),
+        ("activity", check_activity_version(&settings)),
+        (CLIENT_ID, check_client_id()),
+        ("esbuild", check_esbuild(&settings)),
+    ];
+
+    let mut failed = false;
+    for (name, result) in checks {
+        match result {
+            Ok(Outcome::Ok(info)) => println!("[ok]   {name}: {info}"),
+            Ok(Outcome::Warn(warning)) => println!("[warn] {name}: {warning}"),
+            Err(err) => {
+                failed = true;
+                println!("[fail] {name}: {err:#}");
+            }
+        }
+    }
+
+    if failed {
+        anyhow::bail!("some checks failed");
+    }
+
+    Ok(())
+}
+
+// The settings of a release build, every check that needs them fails when the configuration can't
+// be loaded.
+fn settings_or_err(settings: &Result<BuildSettings>) -> Result<&BuildSettings> {
+    settings.as_ref().map_err(|err| anyhow::anyhow!("{err:#}"))
+}
+
+fn check_wasm_target() -> Result<Outcome> {
+    let output = Command::new("rustup")
+        .args(["target", "list", "--installed"])
+        .output()
+        .map_err(|err| anyhow::anyhow!("failed to run rustup: {err}"))?;
+
+    let installed = String::from_utf8_lossy(&output.stdout);
+    if installed.lines().any(|line| line.trim() == WASM_TARGET) {
+        Ok(Outcome::Ok(format!("{WASM_TARGET} is installed")))
+    } else {
+        anyhow::bail!("{WASM_TARGET} is not installed, run `rustup target add {WASM_TARGET}`")
+    }
+}
+
+fn check_wasm_bindgen() -> Result<Outcome> {
+    compile::ensure_wasm_bindgen()?;
+    Ok(Outcome::Ok(format!(
+        "{} is installed",
+        compile::locked_wasm_bindgen_version()?
+    )))
+}
+
+// The bindings of the activity crate are written against one version of the SDK, the one that is
+// built into the activity-build release of the same version. The build bundles the SDK that the
+// configuration resolves to, which has to be compatible with them.
+fn check_activity_version(settings: &Result<BuildSettings>) -> Result<Outcome> {
+    let sdk = sdk::resolve(settings_or_err(settings)?)?;
+    let versions = compile::locked_versions("activity")?;
+
+    if versions.is_empty() {
+        return Ok(Outcome::Warn(
+            "the crate doesn't depend on the activity crate, add it with `cargo add activity`"
+                .into(),
+        ));
+    }
+
+    for version in &versions {
+        let Some(bindings) = bindings_sdk_version(version)? else {
+            return Ok(Outcome::Warn(format!(
+                "activity {version} doesn't say which Discord SDK its bindings are written for, \
+                 update it with `cargo update -p activity`"
+            )));
+        };
+
+        if !sdk::is_compatible(&sdk.version, &bindings) {
+            return Ok(Outcome::Warn(format!(
+                "the bindings of activity {version} are written for the Discord SDK {bindings}, \
+                 but the build bundles {}. Pin a compatible SDK with `sdk.version` in the \
+                 configuration or update the crate to the activity version that matches it",
+                sdk.version
+            )));
+        }
+    }
+
+    Ok(Outcome::Ok(format!(
+        "{} is compatible with the bundled Discord SDK {}",
+        versions.join(", "),
+        sdk.version
+    )))
+}
+
+// The version of this tool has the built-in SDK in its bindings, other versions have it in the
+// `BINDINGS_SDK_VERSION` of their sources, which cargo has downloaded for the lockfile.
+fn bindings_sdk_version(version: &str) -> Result<Option<String>> {
+    if version == env!("CARGO_PKG_VERSION") {
+        return Ok(Some(sdk::BUILT_IN_VERSION.into()));
+    }
+
+    let output = Command::new("cargo")
+        .args(["metadata", "--format-version=1", "--offline"])
+        .output()
+        .map_err(|err| anyhow::anyhow!("failed to run cargo: {err}"))?;
+    if !output.status.success() {
+        anyhow::bail!(
+            "cargo metadata failed: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+
+    let metadata: serde_json::Value = serde_json::from_slice(&output.stdout)?;
+    let Some(manifest_path) = metadata["packages"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .find(|p| p["name"] == "activity" && p["version"] == version)
+        .and_then(|p| p["manifest_path"].as_str())
+    else {
+        return Ok(None);
+    };
+
+    let schema = Path::new(manifest_path).with_file_name("src/schema.rs");
+    let Ok(schema) = fs::read_to_string(schema) else {
+        return Ok(None);
+    };
+    Ok(schema
+        .lines()
+        .find_map(|line| line.strip_prefix("pub const BINDINGS_SDK_VERSION: &str = \""))
+        .and_then(|rest| rest.strip_suffix("\";"))
+        .map(String::from))
+}
+
+// `option_env!` compiles without the variable and only panics when the activity starts.
+fn check_client_id() -> Result<Outcome> {
+    if env::var(CLIENT_ID).is_ok_and(|id| !id.is_empty()) {
+        return Ok(Outcome::Ok("is exported".into()));
+    }
+
+    let config = config::load()?;
+    let mut missing = Vec::new();
+    for profile in ["release", "dev"] {
+        let in_config = config.env.contains_key(CLIENT_ID)
+            || config
+                .profile
+                .get(profile)
+                .is_some_and(|p| p.env.contains_key(CLIENT_ID));
+        let in_files = dotenv::load(profile)?
+            .get(CLIENT_ID)
+            .is_some_and(|id| !id.is_empty());
+        if !in_config && !in_files {
+            missing.push(profile);
+        }
+    }
+
+    if missing.is_empty() {
+        return Ok(Outcome::Ok("is set in `.env` or the configuration".into()));
+    }
+
+    if !sources_mention(CLIENT_ID)? {
+        return Ok(Outcome::Ok("not set, but the crate doesn't read it".into()));
+    }
+
+    if missing.len() == 1 {
+        return Ok(Outcome::Warn(format!(
+            "not set for {} builds, add it to `.env.{}` or `.env`",
+            missing[0], missing[0]
+        )));
+    }
+
+    anyhow::bail!(
+        "not set, but the crate reads it at compile time. Add the client ID of your application \
+         from the Discord developer portal to `.env` as `{CLIENT_ID}=<client id>` or export it"
+    )
+}
+
+fn sources_mention(text: &str) -> Result<bool> {
+    for path in glob::glob("src/**/*.rs")? {
+        if fs::read_to_string(path?)?.contains(text) {
+            return Ok(true);
+        }
+    }
+    Ok(false)
+}
+
+fn check_esbuild(settings: &Result<BuildSettings>) -> Result<Outcome> {
+    let settings = settings_or_err(settings)?;
+    let sdk = sdk::resolve(settings)?;
+    if settings.bundler == Bundler::Builtin && sdk.is_built_in() {
+        return Ok(Outcome::Ok(
+            "not needed, the built-in bundler is configured".into(),
+        ));
+    }
+
+    match install::find_esbuild(&settings.esbuild)? {
+        Some(path) => Ok(Outcome::Ok(path.display().to_string())),
+        None if !sdk.is_built_in() => Ok(Outcome::Warn(format!(
+            "not cached, the first build downloads it to bundle the Discord SDK {}, also with \
+             `--bundler builtin`. Run a build while online before building offline or vendor it \
+             with `esbuild.path` in the configuration",
+            sdk.version
+        ))),
+        None => Ok(Outcome::Warn(
+            "not cached, the first build downloads it. Run a build while online before \
+             building offline, vendor it with `esbuild.path` in the configuration or use \
+             `--bundler builtin`"
+                .into(),
+        )),
+    }
+}
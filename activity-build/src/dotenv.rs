@@ -0,0 +1,167 @@
+// Environment variables for cargo from `.env` files, so client IDs and the like don't have to be
+// exported in the shell and can differ between profiles.
+
+use std::{collections::BTreeMap, env, fs, io, path::Path};
+
+use anyhow::{Context, Result};
+
+use crate::config::BuildSettings;
+
+/// Reads `.env` and then `.env.<profile>`, whose variables take precedence.
+pub fn load(profile: &str) -> Result<BTreeMap<String, String>> {
+    let mut vars = BTreeMap::new();
+
+    for name in [".env".to_string(), format!(".env.{profile}")] {
+        let path = Path::new(&name);
+        let contents = match fs::read_to_string(path) {
+            Ok(contents) => contents,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => continue,
+            Err(err) => return Err(err).with_context(|| format!("failed to read {name}")),
+        };
+        vars.extend(parse(&contents).with_context(|| format!("failed to parse {name}"))?);
+    }
+
+    Ok(vars)
+}
+
+/// Fails when a required variable is empty or neither in the environment nor passed to cargo.
+pub fn check_required(settings: &BuildSettings) -> Result<()> {
+    let missing: Vec<&str> = settings
+        .required_env
+        .iter()
+        .filter(|name| {
+            // `NAME=` in `.env.example` doesn't count as set.
+            let is_set = match settings.env.get(*name) {
+                Some(value) => !value.is_empty(),
+                None => env::var_os(name).is_some_and(|v| !v.is_empty()),
+            };
+            !is_set
+        })
+        .map(|name| name.as_str())
+        .collect();
+
+    if !missing.is_empty() {
+        anyhow::bail!(
+            "required environment variables aren't set: {}. Add them to `.env` or `.env.{}` \
+             (see `.env.example`), or export them",
+            missing.join(", "),
+            settings.profile_name()
+        );
+    }
+
+    Ok(())
+}
+
+// Lines are `NAME=value`, optionally prefixed with `export`. Values can be in double quotes with
+// escapes or in single quotes without, unquoted values end at a ` #` comment. There is no
+// variable expansion.
+fn parse(contents: &str) -> Result<Vec<(String, String)>> {
+    let mut vars = Vec::new();
+
+    for (i, line) in contents.lines().enumerate() {
+        let line_number = i + 1;
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+
+        let line = line.strip_prefix("export ").unwrap_or(line);
+        let (name, value) = line
+            .split_once('=')
+            .ok_or_else(|| anyhow::anyhow!("expected `NAME=value` on line {line_number}"))?;
+
+        let name = name.trim();
+        let is_valid_name = name.starts_with(|c: char| c.is_ascii_alphabetic() || c == '_')
+            && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_');
+        if !is_valid_name {
+            anyhow::bail!("invalid variable name `{name}` on line {line_number}");
+        }
+
+        let value = parse_value(value.trim_start())
+            .ok_or_else(|| anyhow::anyhow!("unterminated quote on line {line_number}"))?;
+        vars.push((name.to_string(), value));
+    }
+
+    Ok(vars)
+}
+
+fn parse_value(value: &str) -> Option<String> {
+    if let Some(rest) = value.strip_prefix('\'') {
+        return rest.find('\'').map(|end| rest[..end].to_string());
+    }
+
+    if let Some(rest) = value.strip_prefix('"') {
+        let mut parsed = String::new();
+        let mut chars = rest.chars();
+        while let Some(c) = chars.next() {
+            match c {
+                '"' => return Some(parsed),
+                '\\' => match chars.next()? {
+                    'n' => parsed.push('\n'),
+                    'r' => parsed.push('\r'),
+                    't' => parsed.push('\t'),
+                    c => parsed.push(c),
+                },
+                c => parsed.push(c),
+            }
+        }
+        return None;
+    }
+
+    let end = value.find(" #").unwrap_or(value.len());
+    Some(value[..end].trim_end().to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::parse;
+
+    fn vars(contents: &str) -> Vec<(String, String)> {
+        parse(contents).unwrap()
+    }
+
+    #[test]
+    fn parses_names_and_values() {
+        let contents =
+            "# comment\n\nexport CLIENT_ID=123\n  NAME = value # comment\nHASH=a#b\nEMPTY=\n";
+        assert_eq!(
+            vars(contents),
+            [
+                ("CLIENT_ID".into(), "123".into()),
+                ("NAME".into(), "value".into()),
+                ("HASH".into(), "a#b".into()),
+                ("EMPTY".into(), "".into()),
+            ]
+        );
+    }
+
+    #[test]
+    fn parses_quoted_values() {
+        let contents = r#"DOUBLE="a \"b\"\n\tc\\" # comment
+SINGLE='a \n # b'
+SPACES="  padded  "
+"#;
+        assert_eq!(
+            vars(contents),
+            [
+                ("DOUBLE".into(), "a \"b\"\n\tc\\".into()),
+                ("SINGLE".into(), "a \\n # b".into()),
+                ("SPACES".into(), "  padded  ".into()),
+            ]
+        );
+    }
+
+    #[test]
+    fn rejects_invalid_lines() {
+        for contents in [
+            "NAME",
+            "1NAME=x",
+            "MY-NAME=x",
+            "NAME=\"x",
+            "NAME='x",
+            "NAME=\"x\\\"",
+        ] {
+            assert!(parse(contents).is_err(), "{contents}");
+        }
+    }
+}
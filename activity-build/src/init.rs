@@ -0,0 +1,153 @@
+// Scaffolds a new activity crate from one of the templates, which are part of the binary so this
+// works offline.
+
+use std::{fs, path::Path};
+
+use anyhow::{bail, Result};
+
+use crate::{
+    cli::{InitArgs, Template},
+    write_string_to_file,
+};
+
+const COMMON: &[(&str, &str)] = &[(".gitignore", include_str!("./templates/common/gitignore"))];
+
+const ENV_EXAMPLE: &str = include_str!("./templates/common/env.example");
+
+const MINIMAL: &[(&str, &str)] = &[
+    (
+        "Cargo.toml",
+        include_str!("./templates/minimal/Cargo.toml.tmpl"),
+    ),
+    ("src/lib.rs", include_str!("./templates/minimal/lib.rs")),
+    (".env.example", ENV_EXAMPLE),
+];
+
+const AUTH: &[(&str, &str)] = &[
+    (
+        "Cargo.toml",
+        include_str!("./templates/auth/Cargo.toml.tmpl"),
+    ),
+    ("src/lib.rs", include_str!("./templates/auth/lib.rs")),
+    (".env.example", include_str!("./templates/auth/env.example")),
+    (
+        "server/Cargo.toml",
+        include_str!("./templates/auth/server/Cargo.toml.tmpl"),
+    ),
+    (
+        "server/src/main.rs",
+        include_str!("./templates/auth/server/main.rs"),
+    ),
+];
+
+const YEW: &[(&str, &str)] = &[
+    (
+        "Cargo.toml",
+        include_str!("./templates/yew/Cargo.toml.tmpl"),
+    ),
+    ("src/lib.rs", include_str!("./templates/yew/lib.rs")),
+    (".env.example", ENV_EXAMPLE),
+];
+
+const CANVAS: &[(&str, &str)] = &[
+    (
+        "Cargo.toml",
+        include_str!("./templates/canvas/Cargo.toml.tmpl"),
+    ),
+    ("src/lib.rs", include_str!("./templates/canvas/lib.rs")),
+    (".env.example", ENV_EXAMPLE),
+];
+
+fn template_files(template: Template) -> &'static [(&'static str, &'static str)] {
+    match template {
+        Template::Minimal => MINIMAL,
+        Template::Auth => AUTH,
+        Template::Yew => YEW,
+        Template::Canvas => CANVAS,
+    }
+}
+
+// Names that cargo refuses for packages, besides the keywords.
+const RESERVED: &[&str] = &["test", "core", "std", "alloc", "proc_macro", "proc-macro"];
+
+const KEYWORDS: &[&str] = &[
+    "abstract", "as", "async", "await", "become", "box", "break", "const", "continue", "crate",
+    "do", "dyn", "else", "enum", "extern", "false", "final", "fn", "for", "gen", "if", "impl",
+    "in", "let", "loop", "macro", "match", "mod", "move", "mut", "override", "priv", "pub", "ref",
+    "return", "self", "Self", "static", "struct", "super", "trait", "true", "try", "type",
+    "typeof", "unsafe", "unsized", "use", "virtual", "where", "while", "yield",
+];
+
+// The name is used as the package name and the directory, so it has to be a valid crate name.
+fn validate_name(name: &str) -> Result<()> {
+    if name.is_empty() {
+        bail!("the name of the activity can't be empty");
+    }
+    if let Some(c) = name
+        .chars()
+        .find(|c| !c.is_ascii_alphanumeric() && *c != '-' && *c != '_')
+    {
+        bail!("`{name}` isn't a valid crate name, it can only have letters, digits, `-` and `_`, not `{c}`");
+    }
+    if name.starts_with(|c: char| c.is_ascii_digit()) {
+        bail!("`{name}` isn't a valid crate name, it can't start with a digit");
+    }
+    if KEYWORDS.contains(&name) || RESERVED.contains(&name) {
+        bail!("`{name}` isn't a valid crate name, it's reserved by Rust");
+    }
+    Ok(())
+}
+
+pub fn init(args: &InitArgs) -> Result<()> {
+    validate_name(&args.name)?;
+
+    let dir = Path::new(&args.name);
+    if dir.exists() {
+        bail!("{} already exists", dir.display());
+    }
+
+    // The activity crate is released together with this tool.
+    let activity_version = env!("CARGO_PKG_VERSION");
+
+    for (path, contents) in COMMON.iter().chain(template_files(args.template)) {
+        let path = dir.join(path);
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+
+        let contents = contents
+            .replace("{{name}}", &args.name)
+            .replace("{{activity_version}}", activity_version);
+        write_string_to_file(path, contents)?;
+    }
+
+    eprintln!("Created activity `{}`", args.name);
+    eprintln!(
+        "Copy `.env.example` to `.env`, fill in your client ID and build it with \
+         `activity-build` in its directory."
+    );
+    if let Template::Auth = args.template {
+        eprintln!(
+            "Run the server that exchanges the OAuth2 codes with `cargo run` in `{}/server`, \
+             it needs CLIENT_ID and CLIENT_SECRET.",
+            args.name
+        );
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::validate_name;
+
+    #[test]
+    fn crate_names() {
+        for name in ["snake", "my-activity", "my_activity2", "A"] {
+            assert!(validate_name(name).is_ok(), "{name}");
+        }
+        for name in ["", "2d", "my activity", "../x", "a/b", "fn", "test", "é"] {
+            assert!(validate_name(name).is_err(), "{name}");
+        }
+    }
+}
@@ -1,10 +1,19 @@
 // Taken from https://github.com/cloudflare/workers-rs/blob/main/worker-build/src/install.rs
 // Credits to the original authors.
 
-use std::{fs::OpenOptions, io::Write, path::PathBuf, process::Command};
+use std::{
+    env,
+    fs::{self, OpenOptions},
+    io::{Read, Write},
+    path::{Path, PathBuf},
+};
 
-use anyhow::Result;
+use anyhow::{Context, Result};
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine};
 use flate2::read::GzDecoder;
+use sha2::{Digest, Sha256, Sha512};
+
+use crate::config::EsbuildConfig;
 
 /// Checks if a binary with the specified name is on the user's path.
 pub fn is_installed(name: &str) -> Result<Option<PathBuf>> {
@@ -34,71 +43,211 @@ pub fn is_installed(name: &str) -> Result<Option<PathBuf>> {
     Ok(None)
 }
 
-pub fn ensure_wasm_pack() -> Result<()> {
-    if is_installed("wasm-pack")?.is_none() {
-        println!("Installing wasm-pack...");
-        let exit_status = Command::new("cargo")
-            .args(["install", "wasm-pack"])
-            .spawn()?
-            .wait()?;
-
-        match exit_status.success() {
-            true => Ok(()),
-            false => anyhow::bail!(
-                "installation of wasm-pack exited with status {}",
-                exit_status
-            ),
+const ESBUILD_VERSION: &str = "0.14.47";
+const BINARY_EXTENSION: &str = if cfg!(windows) { ".exe" } else { "" };
+
+/// Overrides every other way of finding esbuild.
+const ESBUILD_BINARY_ENV: &str = "ESBUILD_BINARY";
+
+/// Returns an esbuild binary that can be used without downloading anything.
+///
+/// In order of precedence that's `ESBUILD_BINARY`, the configured vendored binary or tarball,
+/// one installed in a local `node_modules`, one on the user's path and the one we downloaded
+/// before.
+pub fn find_esbuild(config: &EsbuildConfig) -> Result<Option<PathBuf>> {
+    if let Some(path) = env::var_os(ESBUILD_BINARY_ENV) {
+        let path = PathBuf::from(path);
+        if !path.is_file() {
+            anyhow::bail!(
+                "{ESBUILD_BINARY_ENV} is set to {}, which doesn't exist",
+                path.display()
+            );
         }
-    } else {
-        Ok(())
+        return Ok(Some(path));
     }
-}
 
-const ESBUILD_VERSION: &str = "0.14.47";
-const BINARY_EXTENSION: &str = if cfg!(windows) { ".exe" } else { "" };
+    if let Some(path) = &config.path {
+        return vendored_esbuild(config, path).map(Some);
+    }
+
+    if let Some(path) = node_modules_esbuild()? {
+        return Ok(Some(path));
+    }
 
-pub fn ensure_esbuild() -> Result<PathBuf> {
-    // If we already have it we can skip the download.
     if let Some(path) = is_installed("esbuild")? {
+        return Ok(Some(path));
+    };
+
+    let esbuild_bin_path = esbuild_cache_path()?;
+    Ok(esbuild_bin_path.exists().then_some(esbuild_bin_path))
+}
+
+pub fn ensure_esbuild(config: &EsbuildConfig, offline: bool) -> Result<PathBuf> {
+    // If we already have it we can skip the download.
+    if let Some(path) = find_esbuild(config)? {
         return Ok(path);
     };
 
-    let esbuild_binary = format!("esbuild-{}{BINARY_EXTENSION}", platform());
-    let esbuild_bin_path = dirs_next::cache_dir()
-        .unwrap_or_else(std::env::temp_dir)
-        .join(esbuild_binary);
+    if offline {
+        anyhow::bail!(
+            "esbuild isn't available and can't be downloaded in offline mode. \
+             Set {ESBUILD_BINARY_ENV} to an esbuild binary, vendor one with `esbuild.path` in the configuration \
+             install it with `npm install esbuild@{ESBUILD_VERSION}` or use `--bundler builtin`"
+        );
+    }
+
+    eprintln!("Installing esbuild...");
+
+    let tarball = download_esbuild()?;
+    verify_esbuild(config, &tarball)?;
+    install_esbuild(&tarball, &esbuild_cache_path()?)
+}
+
+// Vendored binaries and tarballs are always checked against the pinned checksum. Tarballs are
+// extracted into a path keyed by their checksum, so replacing the tarball never runs a binary
+// extracted from another one.
+fn vendored_esbuild(config: &EsbuildConfig, path: &Path) -> Result<PathBuf> {
+    let contents = fs::read(path)
+        .with_context(|| format!("failed to read the vendored esbuild {}", path.display()))?;
+    let sha256 = hex(&Sha256::digest(&contents));
+    match expected_sha256(config)? {
+        Some(expected) => verify_sha256(&sha256, expected)?,
+        None => anyhow::bail!(
+            "the vendored esbuild has no pinned checksum for {0}, pin it with \
+             `sha256.{0} = \"{sha256}\"` in the `esbuild` configuration",
+            platform()?
+        ),
+    }
+
+    let is_tarball = path
+        .extension()
+        .is_some_and(|ext| ext == "tgz" || ext == "gz");
+    if !is_tarball {
+        return Ok(path.to_path_buf());
+    }
+    let tarball = contents;
 
+    let esbuild_bin_path = esbuild_cache_path()?
+        .with_file_name(format!("esbuild-{}{BINARY_EXTENSION}", &sha256[..16]));
     if esbuild_bin_path.exists() {
         return Ok(esbuild_bin_path);
     }
 
-    let mut options = &mut std::fs::OpenOptions::new();
+    install_esbuild(&tarball, &esbuild_bin_path)
+}
 
-    options = fix_permissions(options);
+// Looks for `node_modules/.bin/esbuild` in the current and all parent directories, like npm does.
+fn node_modules_esbuild() -> Result<Option<PathBuf>> {
+    let name = if cfg!(windows) {
+        "esbuild.cmd"
+    } else {
+        "esbuild"
+    };
 
-    let mut file = options.create(true).write(true).open(&esbuild_bin_path)?;
+    Ok(env::current_dir()?
+        .ancestors()
+        .map(|dir| dir.join("node_modules").join(".bin").join(name))
+        .find(|path| path.is_file()))
+}
 
-    println!("Installing esbuild...");
+fn esbuild_cache_path() -> Result<PathBuf> {
+    let esbuild_binary = format!(
+        "esbuild-{}-{ESBUILD_VERSION}{BINARY_EXTENSION}",
+        platform()?
+    );
+    Ok(dirs_next::cache_dir()
+        .unwrap_or_else(std::env::temp_dir)
+        .join(esbuild_binary))
+}
+
+fn download_esbuild() -> Result<Vec<u8>> {
+    let esbuild_url = format!(
+        "https://registry.npmjs.org/esbuild-{0}/-/esbuild-{0}-{ESBUILD_VERSION}.tgz",
+        platform()?
+    );
+
+    let mut tarball = Vec::new();
+    ureq::get(&esbuild_url)
+        .call()?
+        .into_reader()
+        .read_to_end(&mut tarball)?;
+
+    Ok(tarball)
+}
+
+// Checks the tarball against the configured checksum of the platform, or the integrity hash of
+// the npm registry without one.
+fn verify_esbuild(config: &EsbuildConfig, tarball: &[u8]) -> Result<()> {
+    if let Some(expected) = expected_sha256(config)? {
+        return verify_sha256(&hex(&Sha256::digest(tarball)), expected);
+    }
+
+    let platform = platform()?;
+    let metadata_url = format!("https://registry.npmjs.org/esbuild-{platform}/{ESBUILD_VERSION}");
+    let metadata: serde_json::Value =
+        serde_json::from_reader(ureq::get(&metadata_url).call()?.into_reader())?;
+    let integrity = metadata["dist"]["integrity"]
+        .as_str()
+        .and_then(|i| i.strip_prefix("sha512-"))
+        .ok_or_else(|| anyhow::anyhow!("the npm registry has no integrity hash for esbuild"))?;
+
+    if BASE64.encode(Sha512::digest(tarball)) != integrity {
+        anyhow::bail!(
+            "the downloaded esbuild doesn't match the integrity hash of the npm registry"
+        );
+    }
+
+    Ok(())
+}
+
+fn expected_sha256(config: &EsbuildConfig) -> Result<Option<&str>> {
+    Ok(config.sha256.get(platform()?).map(String::as_str))
+}
 
-    if let Err(e) = download_esbuild(&mut file) {
+fn verify_sha256(actual: &str, expected: &str) -> Result<()> {
+    if !actual.eq_ignore_ascii_case(expected) {
+        anyhow::bail!("esbuild checksum mismatch, expected SHA-256 {expected} but got {actual}");
+    }
+
+    Ok(())
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{b:02x}")).collect()
+}
+
+// Extracts the binary to the path. It's written to a temporary file first, so an interrupted
+// build doesn't leave a broken binary behind.
+fn install_esbuild(tarball: &[u8], esbuild_bin_path: &Path) -> Result<PathBuf> {
+    let tmp_path = esbuild_bin_path.with_extension("tmp");
+    if let Some(parent) = esbuild_bin_path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+
+    let mut options = &mut std::fs::OpenOptions::new();
+    options = fix_permissions(options);
+    let mut file = options
+        .create(true)
+        .write(true)
+        .truncate(true)
+        .open(&tmp_path)?;
+
+    if let Err(e) = extract_esbuild(tarball, &mut file) {
         // Make sure we close the file before we remove it.
         drop(file);
 
-        std::fs::remove_file(&esbuild_bin_path)?;
+        std::fs::remove_file(&tmp_path)?;
         return Err(e);
     }
 
-    Ok(esbuild_bin_path)
-}
+    drop(file);
+    fs::rename(&tmp_path, esbuild_bin_path)?;
 
-fn download_esbuild(writer: &mut impl Write) -> Result<()> {
-    let esbuild_url = format!(
-        "https://registry.npmjs.org/esbuild-{0}/-/esbuild-{0}-{ESBUILD_VERSION}.tgz",
-        platform()
-    );
+    Ok(esbuild_bin_path.to_path_buf())
+}
 
-    let body = ureq::get(&esbuild_url).call()?.into_reader();
-    let deflater = GzDecoder::new(body);
+fn extract_esbuild(tarball: &[u8], writer: &mut impl Write) -> Result<()> {
+    let deflater = GzDecoder::new(tarball);
     let mut archive = tar::Archive::new(deflater);
 
     for entry in archive.entries()? {
@@ -131,8 +280,8 @@ fn fix_permissions(options: &mut OpenOptions) -> &mut OpenOptions {
 
 /// Converts the user's platform from their Rust representation to their esbuild representation.
 /// https://esbuild.github.io/getting-started/#download-a-build
-pub fn platform() -> &'static str {
-    match (std::env::consts::OS, std::env::consts::ARCH) {
+pub fn platform() -> Result<&'static str> {
+    Ok(match (std::env::consts::OS, std::env::consts::ARCH) {
         ("macos", "x86_64") => "darwin-64",
         ("macos", "aarch64") => "darwin-arm64",
         ("linux", "x86") => "linux-32",
@@ -142,6 +291,8 @@ pub fn platform() -> &'static str {
         ("windows", "x86") => "windows-32",
         ("windows", "x86_64") => "windows-64",
         ("windows", "aarch64") => "windows-arm64",
-        _ => panic!("Platform unsupported by esbuild."),
-    }
+        (os, arch) => anyhow::bail!(
+            "esbuild has no binary for {os}-{arch}, set {ESBUILD_BINARY_ENV} to an esbuild you built yourself"
+        ),
+    })
 }
@@ -0,0 +1,380 @@
+// A JavaScript tokenizer that is just good enough to find the top level import and export
+// statements of a module and to strip whitespace and comments from it.
+
+use anyhow::Result;
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum TokenKind {
+    /// Whitespace, `true` if it contains a line break.
+    Whitespace(bool),
+    /// A comment, `true` for legal comments (`/*!` or `//!`) which are kept when minifying.
+    Comment(bool),
+    /// Identifiers, keywords and numbers.
+    Word,
+    String,
+    /// A complete template literal, or the part of one up to `${` or after `}`.
+    Template,
+    Regex,
+    Punct(char),
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct Token {
+    pub kind: TokenKind,
+    pub start: usize,
+    pub end: usize,
+    /// Nesting depth of brackets at the start of the token, template substitutions count too.
+    pub depth: usize,
+}
+
+impl Token {
+    pub fn text<'a>(&self, src: &'a str) -> &'a str {
+        &src[self.start..self.end]
+    }
+
+    pub fn is_trivia(&self) -> bool {
+        matches!(self.kind, TokenKind::Whitespace(_) | TokenKind::Comment(_))
+    }
+
+    pub fn is_word(&self, src: &str, word: &str) -> bool {
+        self.kind == TokenKind::Word && self.text(src) == word
+    }
+}
+
+// Keywords after which a `/` starts a regular expression rather than a division.
+const REGEX_KEYWORDS: &[&str] = &[
+    "return",
+    "typeof",
+    "instanceof",
+    "in",
+    "of",
+    "new",
+    "delete",
+    "void",
+    "throw",
+    "case",
+    "do",
+    "else",
+    "yield",
+    "await",
+];
+
+// Keywords whose parenthesized condition can be followed by a regular expression, as in
+// `if (x) /a/.test(y)`.
+const CONDITION_KEYWORDS: &[&str] = &["if", "while", "for", "with"];
+
+/// An open bracket.
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Open {
+    Bracket,
+    /// The parenthesis after one of `CONDITION_KEYWORDS`.
+    Condition,
+    /// `${` of a template literal.
+    Substitution,
+}
+
+fn is_word_char(c: char) -> bool {
+    c.is_alphanumeric() || c == '_' || c == '$' || !c.is_ascii()
+}
+
+pub fn tokenize(src: &str) -> Result<Vec<Token>> {
+    let bytes = src.as_bytes();
+    let mut tokens: Vec<Token> = Vec::new();
+    let mut pos = 0;
+
+    let mut stack: Vec<Open> = Vec::new();
+    // The last token that isn't trivia, decides whether `/` starts a regex.
+    let mut last: Option<Token> = None;
+    // Whether `last` closes the condition of an `if`, `while`, `for` or `with`.
+    let mut after_condition = false;
+
+    while pos < src.len() {
+        let start = pos;
+        let depth = stack.len();
+        let c = src[pos..].chars().next().unwrap();
+
+        let kind = if c.is_whitespace() {
+            let mut newline = false;
+            while let Some(c) = src[pos..].chars().next().filter(|c| c.is_whitespace()) {
+                newline |= c == '\n' || c == '\r' || c == '\u{2028}' || c == '\u{2029}';
+                pos += c.len_utf8();
+            }
+            TokenKind::Whitespace(newline)
+        } else if src[pos..].starts_with("//") {
+            pos = src[pos..].find('\n').map_or(src.len(), |i| pos + i);
+            TokenKind::Comment(src[start..].starts_with("//!"))
+        } else if src[pos..].starts_with("/*") {
+            pos = src[pos + 2..]
+                .find("*/")
+                .map(|i| pos + 2 + i + 2)
+                .ok_or_else(|| anyhow::anyhow!("unterminated comment at byte {start}"))?;
+            TokenKind::Comment(src[start..].starts_with("/*!"))
+        } else if c == '"' || c == '\'' {
+            pos = skip_string(bytes, pos, c as u8)?;
+            TokenKind::String
+        } else if c == '`' {
+            pos = skip_template(bytes, pos + 1, &mut stack)?;
+            TokenKind::Template
+        } else if c == '}' && stack.last() == Some(&Open::Substitution) {
+            // The end of a template substitution, the literal continues.
+            stack.pop();
+            pos = skip_template(bytes, pos + 1, &mut stack)?;
+            TokenKind::Template
+        } else if c == '/' && (after_condition || starts_regex(src, last)) {
+            pos = skip_regex(bytes, pos)?;
+            TokenKind::Regex
+        } else if is_word_char(c) {
+            while let Some(c) = src[pos..].chars().next().filter(|c| is_word_char(*c)) {
+                pos += c.len_utf8();
+            }
+            TokenKind::Word
+        } else {
+            pos += c.len_utf8();
+            TokenKind::Punct(c)
+        };
+
+        let token = Token {
+            kind,
+            start,
+            end: pos,
+            depth,
+        };
+        if !token.is_trivia() {
+            after_condition = false;
+            match kind {
+                TokenKind::Punct('(')
+                    if last.is_some_and(|last| {
+                        CONDITION_KEYWORDS.iter().any(|w| last.is_word(src, w))
+                    }) =>
+                {
+                    stack.push(Open::Condition)
+                }
+                TokenKind::Punct('(' | '[' | '{') => stack.push(Open::Bracket),
+                TokenKind::Punct(')' | ']' | '}') => {
+                    after_condition = stack.pop() == Some(Open::Condition);
+                }
+                _ => {}
+            }
+            last = Some(token);
+        }
+        tokens.push(token);
+    }
+
+    Ok(tokens)
+}
+
+fn starts_regex(src: &str, last: Option<Token>) -> bool {
+    match last {
+        None => true,
+        Some(token) => match token.kind {
+            TokenKind::Word => REGEX_KEYWORDS.contains(&token.text(src)),
+            // `i++ / 2`
+            TokenKind::Punct(c @ ('+' | '-')) => !src[..token.start].ends_with(c),
+            TokenKind::Punct(c) => !matches!(c, ')' | ']'),
+            _ => false,
+        },
+    }
+}
+
+fn skip_string(bytes: &[u8], mut pos: usize, quote: u8) -> Result<usize> {
+    let start = pos;
+    pos += 1;
+    while pos < bytes.len() {
+        match bytes[pos] {
+            b'\\' => pos += 2,
+            b if b == quote => return Ok(pos + 1),
+            b'\n' => break,
+            _ => pos += 1,
+        }
+    }
+
+    anyhow::bail!("unterminated string at byte {start}")
+}
+
+// Skips to the end of the template literal or the start of a substitution in it.
+fn skip_template(bytes: &[u8], mut pos: usize, stack: &mut Vec<Open>) -> Result<usize> {
+    let start = pos;
+    while pos < bytes.len() {
+        match bytes[pos] {
+            b'\\' => pos += 2,
+            b'`' => return Ok(pos + 1),
+            b'$' if bytes.get(pos + 1) == Some(&b'{') => {
+                stack.push(Open::Substitution);
+                return Ok(pos + 2);
+            }
+            _ => pos += 1,
+        }
+    }
+
+    anyhow::bail!("unterminated template literal at byte {start}")
+}
+
+fn skip_regex(bytes: &[u8], mut pos: usize) -> Result<usize> {
+    let start = pos;
+    let mut in_class = false;
+    pos += 1;
+    while pos < bytes.len() {
+        match bytes[pos] {
+            b'\\' => pos += 2,
+            b'[' => {
+                in_class = true;
+                pos += 1;
+            }
+            b']' => {
+                in_class = false;
+                pos += 1;
+            }
+            b'/' if !in_class => {
+                pos += 1;
+                // flags
+                while pos < bytes.len() && (bytes[pos].is_ascii_alphanumeric()) {
+                    pos += 1;
+                }
+                return Ok(pos);
+            }
+            b'\n' => break,
+            _ => pos += 1,
+        }
+    }
+
+    anyhow::bail!("unterminated regular expression at byte {start}")
+}
+
+/// Removes comments and unnecessary whitespace.
+///
+/// Line breaks are only removed where automatic semicolon insertion can't depend on them, and
+/// identifiers are kept as they are, so this is safe but not as small as a real minifier.
+pub fn minify(src: &str) -> Result<String> {
+    let tokens = tokenize(src)?;
+    let mut out = String::with_capacity(src.len() / 2);
+    let mut pending_newline = false;
+    let mut pending_space = false;
+
+    for token in &tokens {
+        match token.kind {
+            TokenKind::Whitespace(newline) => {
+                pending_newline |= newline;
+                pending_space = true;
+            }
+            TokenKind::Comment(false) => {
+                pending_newline |=
+                    token.text(src).contains('\n') || token.text(src).starts_with("//");
+                pending_space = true;
+            }
+            _ => {
+                let text = token.text(src);
+                let prev = out.chars().last();
+                let next = text.chars().next();
+
+                if pending_newline && !newline_is_redundant(prev, next) {
+                    out.push('\n');
+                } else if pending_space && needs_space(&out, text) {
+                    out.push(' ');
+                }
+
+                out.push_str(text);
+                // Line comments have to end with a line break.
+                if matches!(token.kind, TokenKind::Comment(true)) && text.starts_with("//") {
+                    out.push('\n');
+                }
+
+                pending_newline = false;
+                pending_space = false;
+            }
+        }
+    }
+
+    Ok(out)
+}
+
+fn newline_is_redundant(prev: Option<char>, next: Option<char>) -> bool {
+    matches!(
+        (prev, next),
+        (None | Some('\n' | '{' | '(' | '[' | ',' | ';'), _)
+            | (_, Some('}' | ')' | ']' | ',' | ';'))
+    )
+}
+
+// Whether two tokens that were separated by whitespace would merge into something else without
+// it.
+fn needs_space(out: &str, next: &str) -> bool {
+    let (Some(prev), Some(first)) = (out.chars().last(), next.chars().next()) else {
+        return false;
+    };
+
+    if is_word_char(prev) && is_word_char(first) {
+        return true;
+    }
+
+    // `1 .toString()`
+    if first == '.' {
+        let word = out
+            .rsplit(|c: char| !is_word_char(c))
+            .next()
+            .unwrap_or_default();
+        if !word.is_empty() && word.chars().all(|c| c.is_ascii_digit()) {
+            return true;
+        }
+    }
+
+    matches!(
+        (prev, first),
+        ('+', '+') | ('-', '-') | ('/', '/') | ('/', '*') | ('<', '!') | ('-', '>')
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn regexes(src: &str) -> Vec<&str> {
+        tokenize(src)
+            .unwrap()
+            .into_iter()
+            .filter(|t| t.kind == TokenKind::Regex)
+            .map(|t| t.text(src))
+            .collect()
+    }
+
+    #[test]
+    fn division_after_closing_parens_and_brackets() {
+        assert!(regexes("a = (b + c) / d / e").is_empty());
+        assert!(regexes("a = b[0] / c[1] / 2").is_empty());
+        assert!(regexes("i++ / 2 / j").is_empty());
+    }
+
+    #[test]
+    fn regex_after_conditions() {
+        assert_eq!(regexes("if (a) /b/.test(c)"), ["/b/"]);
+        assert_eq!(regexes("while (f(a)) /[)]/g.exec(b)"), ["/[)]/g"]);
+        assert_eq!(regexes("if (a) b = (c) / d"), Vec::<&str>::new());
+    }
+
+    #[test]
+    fn regex_after_blocks_and_keywords() {
+        assert_eq!(regexes("function f() {}\n/a/.test(b)"), ["/a/"]);
+        assert_eq!(regexes("return /a\\/b/i"), ["/a\\/b/i"]);
+        assert_eq!(regexes("x = [/a/, /b/]"), ["/a/", "/b/"]);
+    }
+
+    #[test]
+    fn brackets_in_regexes_and_templates_keep_the_depth() {
+        let src = "if (a) /[{(]/.test(b); `${ {c: 1}.c }`; d";
+        let tokens = tokenize(src).unwrap();
+        let last = tokens.last().unwrap();
+        assert_eq!(last.text(src), "d");
+        assert_eq!(last.depth, 0);
+    }
+
+    #[test]
+    fn minify_keeps_line_breaks_that_matter() {
+        assert_eq!(
+            minify("let a = 1 // one\nlet b = a\n++b\n/* c */ f( a , b )").unwrap(),
+            "let a=1\nlet b=a\n++b\nf(a,b)"
+        );
+        assert_eq!(
+            minify("/*! legal */\nx = \"a  b\"").unwrap(),
+            "/*! legal */\nx=\"a  b\""
+        );
+    }
+}
@@ -2,15 +2,19 @@
 // Credits to the original authors.
 
 use std::{
-    env::{self, VarError},
-    ffi::OsStr,
     fs::{self, File},
-    io::{Read, Write},
+    io::{self, Read, Write},
     path::{Path, PathBuf},
     process::{Command, Stdio},
 };
 
 use anyhow::Result;
+use assets::Assets;
+use clap::Parser;
+use cli::{BuildArgs, Bundler, Cli, Commands, CoredumpCommand, DebugInfo, MessageFormat};
+use config::BuildSettings;
+use serde::Serialize;
+use size::WasmBreakdown;
 
 const OUT_DIR: &str = "build";
 const OUT_NAME: &str = "index";
@@ -20,50 +24,271 @@ const SDK_IMPORT: &str = "@discord/embedded-app-sdk";
 
 const SDK_IMPORT_REPLACEMENT: &str = "./sdk";
 
+mod assets;
+mod bundler;
+mod cli;
+mod compile;
+mod config;
+mod coredump;
+mod debug;
+mod deploy;
+mod doctor;
+mod dotenv;
+mod init;
 mod install;
+mod js;
+mod proxy;
+mod sdk;
+mod serve;
+mod size;
+mod wasm;
 
 pub fn main() -> Result<()> {
+    let cli = Cli::parse();
+
+    match cli.command {
+        None => run_build(&cli.build),
+        Some(Commands::Build(args)) => run_build(&args),
+        Some(Commands::Serve(args)) => serve::serve(args),
+        Some(Commands::Clean(args)) => clean(&config::load()?.out_dir(args.out_dir.as_deref())),
+        Some(Commands::Init(args)) => init::init(&args),
+        Some(Commands::Doctor) => doctor::doctor(),
+        Some(Commands::Symbolicate(args)) => debug::symbolicate(&args),
+        Some(Commands::Coredump(CoredumpCommand::Inspect(args))) => coredump::inspect(&args),
+    }
+}
+
+/// A file of the finished bundle, as reported with `--message-format=json`.
+#[derive(Serialize)]
+struct Artifact {
+    path: PathBuf,
+    size: u64,
+    /// Compressed sizes are only measured for release builds, it's slow for large files.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    gzip_size: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    brotli_size: Option<u64>,
+}
+
+#[derive(Serialize)]
+#[serde(tag = "reason", rename = "build-finished")]
+struct BuildFinished<'a> {
+    success: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    out_dir: Option<PathBuf>,
+    #[serde(skip_serializing_if = "<[_]>::is_empty")]
+    artifacts: &'a [Artifact],
+    #[serde(skip_serializing_if = "Option::is_none")]
+    wasm: Option<&'a WasmBreakdown>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+}
+
+/// What a successful build produced.
+struct BuildOutput {
+    out_dir: PathBuf,
+    artifacts: Vec<Artifact>,
+    wasm: PathBuf,
+    breakdown: Option<WasmBreakdown>,
+}
+
+// Builds the activity and reports the result in the requested format.
+pub fn run_build(args: &BuildArgs) -> Result<()> {
+    let result = BuildSettings::load(args).and_then(|settings| {
+        build(&settings)?;
+
+        let wasm = activity_path(&settings.out_dir, format!("{}_bg.wasm", settings.out_name));
+        let breakdown = match args.top {
+            0 => None,
+            top => Some(size::wasm_breakdown(&fs::read(&wasm)?, top)?),
+        };
+        let output = BuildOutput {
+            out_dir: settings.out_dir.join(ACTIVITY_SUBDIR),
+            artifacts: artifacts(&settings.out_dir, !settings.dev)?,
+            wasm,
+            breakdown,
+        };
+
+        // The sizes are reported even if they exceed the budgets.
+        let budgets = match settings.dev {
+            true => Ok(()),
+            false => size::check_budgets(&output.out_dir, &output.artifacts, &settings.budgets),
+        };
+        Ok((output, budgets.err()))
+    });
+    let (output, error) = match result {
+        Ok((output, error)) => (Some(output), error),
+        Err(err) => (None, Some(err)),
+    };
+
+    match args.message_format {
+        MessageFormat::Human => {
+            if let Some(output) = &output {
+                print_sizes(&output.artifacts);
+                if let Some(breakdown) = &output.breakdown {
+                    size::print_breakdown(&output.wasm, breakdown);
+                }
+            }
+        }
+        MessageFormat::Json => {
+            let message = BuildFinished {
+                success: error.is_none(),
+                out_dir: output.as_ref().map(|o| o.out_dir.clone()),
+                artifacts: output.as_ref().map_or(&[], |o| &o.artifacts),
+                wasm: output.as_ref().and_then(|o| o.breakdown.as_ref()),
+                error: error.as_ref().map(|err| format!("{err:#}")),
+            };
+            println!("{}", serde_json::to_string(&message)?);
+        }
+    }
+
+    match error {
+        Some(err) => Err(err),
+        None => Ok(()),
+    }
+}
+
+fn print_sizes(artifacts: &[Artifact]) {
+    let compressed = |size: Option<u64>| size.map_or("-".into(), format_size);
+
+    eprintln!("{:>10}  {:>10}  {:>10}", "raw", "gzip", "brotli");
+    for artifact in artifacts {
+        eprintln!(
+            "{:>10}  {:>10}  {:>10}  {}",
+            format_size(artifact.size),
+            compressed(artifact.gzip_size),
+            compressed(artifact.brotli_size),
+            artifact.path.display()
+        );
+    }
+}
+
+pub fn build(settings: &BuildSettings) -> Result<()> {
+    let out_dir = settings.out_dir.as_path();
+
+    let sdk = sdk::resolve(settings)?;
+
     // Our tests build the bundle ourselves.
     if !cfg!(test) {
-        install::ensure_wasm_pack()?;
-        wasm_pack_build(env::args_os().skip(1))?;
+        let wasm = compile::cargo_build(settings, &sdk)?;
+        compile::wasm_bindgen(settings, &wasm)?;
+        compile::wasm_opt(settings)?;
     }
 
-    let with_coredump = env::var("COREDUMP").is_ok();
-    if with_coredump {
-        println!("Adding wasm coredump");
-        wasm_coredump()?;
+    if settings.coredump {
+        eprintln!("Adding wasm coredump");
+        wasm_coredump(settings)?;
     }
 
-    let esbuild_path = install::ensure_esbuild()?;
+    if settings.debug_info == DebugInfo::Separate {
+        debug::split_debug_info(settings)?;
+    }
 
-    create_activity_dir()?;
-    copy_generated_code_to_activity_dir()?;
-    replace_sdk_import()?;
+    create_activity_dir(out_dir)?;
+    let assets = assets::copy_assets(settings)?;
+    copy_generated_code_to_activity_dir(settings)?;
+    replace_sdk_import(settings)?;
 
-    write_string_to_file(activity_path("shim.js"), include_str!("./static/shim.js"))?;
-    write_string_to_file(activity_path("sdk.js"), include_str!("./static/sdk.js"))?;
     write_string_to_file(
-        activity_path("index.html"),
-        include_str!("./static/index.html"),
+        activity_path(out_dir, "shim.js"),
+        include_str!("./static/shim.js")
+            .replace("{{out_name}}", &settings.out_name)
+            .replace("{{export}}", &settings.export),
     )?;
+    sdk::write(settings, &sdk)?;
+    write_index_html(settings, &assets)?;
+
+    match settings.bundler {
+        Bundler::Esbuild => {
+            let esbuild_path = install::ensure_esbuild(&settings.esbuild, settings.offline)?;
+            bundle(settings, &esbuild_path)?;
+        }
+        Bundler::Builtin => {
+            if settings.source_map {
+                eprintln!(
+                    "warning: the built-in bundler doesn't write source maps, use `--bundler esbuild` for `source-map = true`"
+                );
+            }
+            let code = bundler::bundle(&activity_path(out_dir, "shim.js"), settings.minify)?;
+            write_string_to_file(
+                activity_path(out_dir, format!("{}.js", settings.out_name)),
+                code,
+            )?;
+        }
+    }
 
-    bundle(&esbuild_path)?;
+    remove_unused_js(settings)?;
+    deploy::write_deploy_files(settings, &assets, &sdk)?;
+
+    Ok(())
+}
 
-    remove_unused_js()?;
+fn clean(out_dir: &Path) -> Result<()> {
+    if out_dir.is_dir() {
+        fs::remove_dir_all(out_dir)?;
+        eprintln!("Removed {}", out_dir.display());
+    }
 
     Ok(())
 }
 
+// Lists the files of the finished bundle with their sizes.
+fn artifacts(out_dir: &Path, compressed_sizes: bool) -> Result<Vec<Artifact>> {
+    fn visit(dir: &Path, compressed_sizes: bool, artifacts: &mut Vec<Artifact>) -> Result<()> {
+        for entry in fs::read_dir(dir)? {
+            let path = entry?.path();
+            if path.is_dir() {
+                visit(&path, compressed_sizes, artifacts)?;
+            } else {
+                let data = fs::read(&path)?;
+                // Precompressed copies don't get any smaller.
+                let precompressed = path.extension().is_some_and(|e| e == "gz" || e == "br");
+                let (gzip_size, brotli_size) = match compressed_sizes && !precompressed {
+                    true => (
+                        Some(size::gzip(&data)?.len() as u64),
+                        Some(size::brotli(&data)?.len() as u64),
+                    ),
+                    false => (None, None),
+                };
+                artifacts.push(Artifact {
+                    path,
+                    size: data.len() as u64,
+                    gzip_size,
+                    brotli_size,
+                });
+            }
+        }
+
+        Ok(())
+    }
+
+    let mut artifacts = Vec::new();
+    visit(
+        &out_dir.join(ACTIVITY_SUBDIR),
+        compressed_sizes,
+        &mut artifacts,
+    )?;
+    artifacts.sort_by(|a, b| a.path.cmp(&b.path));
+
+    Ok(artifacts)
+}
+
+fn format_size(size: u64) -> String {
+    match size {
+        0..=1023 => format!("{size} B"),
+        1024..=1048575 => format!("{:.1} KiB", size as f64 / 1024.0),
+        _ => format!("{:.1} MiB", size as f64 / 1048576.0),
+    }
+}
+
 const INSTALL_HELP: &str = "In case you are missing the binary, you can install it using: `cargo install wasm-coredump-rewriter`";
 
-fn wasm_coredump() -> Result<()> {
-    let coredump_flags = env::var("COREDUMP_FLAGS");
-    let coredump_flags: Vec<&str> = if let Ok(flags) = &coredump_flags {
-        flags.split(' ').collect()
-    } else {
-        vec![]
-    };
+fn wasm_coredump(settings: &BuildSettings) -> Result<()> {
+    let coredump_flags: Vec<&str> = settings
+        .coredump_flags
+        .as_deref()
+        .map(|flags| flags.split_whitespace().collect())
+        .unwrap_or_default();
 
     let mut child = Command::new("wasm-coredump-rewriter")
         .args(coredump_flags)
@@ -74,7 +299,7 @@ fn wasm_coredump() -> Result<()> {
             anyhow::anyhow!("failed to spawn wasm-coredump-rewriter: {err}\n\n{INSTALL_HELP}.")
         })?;
 
-    let input_filename = output_path("index_bg.wasm");
+    let input_filename = output_path(&settings.out_dir, format!("{}_bg.wasm", settings.out_name));
 
     let input_bytes = {
         let mut input = File::open(input_filename.clone())
@@ -120,30 +345,9 @@ fn wasm_coredump() -> Result<()> {
     }
 }
 
-fn wasm_pack_build<I, S>(args: I) -> Result<()>
-where
-    I: IntoIterator<Item = S>,
-    S: AsRef<OsStr>,
-{
-    let exit_status = Command::new("wasm-pack")
-        .arg("build")
-        .arg("--no-typescript")
-        .args(["--target", "web"])
-        .args(["--out-dir", OUT_DIR])
-        .args(["--out-name", OUT_NAME])
-        .args(args)
-        .spawn()?
-        .wait()?;
-
-    match exit_status.success() {
-        true => Ok(()),
-        false => anyhow::bail!("wasm-pack exited with status {}", exit_status),
-    }
-}
-
-fn create_activity_dir() -> Result<()> {
+fn create_activity_dir(out_dir: &Path) -> Result<()> {
     // create a directory for our activity to live in
-    let activity_dir = PathBuf::from(OUT_DIR).join(ACTIVITY_SUBDIR);
+    let activity_dir = out_dir.join(ACTIVITY_SUBDIR);
 
     // remove anything that already exists
     if activity_dir.is_dir() {
@@ -158,17 +362,20 @@ fn create_activity_dir() -> Result<()> {
     Ok(())
 }
 
-fn copy_generated_code_to_activity_dir() -> Result<()> {
-    let glue_src = output_path(format!("{OUT_NAME}.js"));
-    let glue_dest = activity_path(format!("{OUT_NAME}_bg.js"));
+fn copy_generated_code_to_activity_dir(settings: &BuildSettings) -> Result<()> {
+    let out_dir = settings.out_dir.as_path();
+    let out_name = &settings.out_name;
+
+    let glue_src = output_path(out_dir, format!("{out_name}.js"));
+    let glue_dest = activity_path(out_dir, format!("{out_name}_bg.js"));
 
-    let wasm_src = output_path(format!("{OUT_NAME}_bg.wasm"));
-    let wasm_dest = activity_path(format!("{OUT_NAME}_bg.wasm"));
+    let wasm_src = output_path(out_dir, format!("{out_name}_bg.wasm"));
+    let wasm_dest = activity_path(out_dir, format!("{out_name}_bg.wasm"));
 
     // wasm-bindgen supports adding arbitrary JavaScript for a library, so we need to move that as well.
     // https://rustwasm.github.io/wasm-bindgen/reference/js-snippets.html
-    let snippets_src = output_path("snippets");
-    let snippets_dest = activity_path("snippets");
+    let snippets_src = output_path(out_dir, "snippets");
+    let snippets_dest = activity_path(out_dir, "snippets");
 
     for (src, dest) in [
         (glue_src, glue_dest),
@@ -186,34 +393,113 @@ fn copy_generated_code_to_activity_dir() -> Result<()> {
 }
 
 // Replaces the import of the official SDK with the local one.
-fn replace_sdk_import() -> Result<()> {
-    let bindgen_glue_path = activity_path(format!("{OUT_NAME}_bg.js"));
+fn replace_sdk_import(settings: &BuildSettings) -> Result<()> {
+    let bindgen_glue_path =
+        activity_path(&settings.out_dir, format!("{}_bg.js", settings.out_name));
     let old_bindgen_glue = read_file_to_string(&bindgen_glue_path)?;
     let fixed_bindgen_glue = old_bindgen_glue.replace(SDK_IMPORT, SDK_IMPORT_REPLACEMENT);
     write_string_to_file(bindgen_glue_path, fixed_bindgen_glue)?;
     Ok(())
 }
 
+// Writes the index.html from the template, or the default one.
+fn write_index_html(settings: &BuildSettings, assets: &Assets) -> Result<()> {
+    let template = match &settings.template {
+        Some(path) => read_file_to_string(path)
+            .map_err(|err| anyhow::anyhow!("failed to read {}: {err}", path.display()))?,
+        None => include_str!("./static/index.html").into(),
+    };
+
+    let script = format!(r#"<script defer src="/{}.js"></script>"#, settings.out_name);
+    let styles: Vec<String> = assets
+        .styles
+        .iter()
+        .map(|url| format!(r#"<link rel="stylesheet" href="{url}" />"#))
+        .collect();
+    let favicon = assets
+        .favicon
+        .as_ref()
+        .map(|url| format!(r#"<link rel="icon" href="{url}" />"#));
+    let head: Vec<&str> = favicon
+        .iter()
+        .chain(&styles)
+        .chain([&script])
+        .map(String::as_str)
+        .collect();
+
+    if !["{{head}}", "{{script}}", "{{out_name}}"]
+        .iter()
+        .any(|placeholder| template.contains(placeholder))
+    {
+        eprintln!("warning: the index.html template doesn't contain {{{{head}}}} or {{{{script}}}}, the activity won't be loaded");
+    }
+
+    let html = replace_asset_placeholders(&template, assets)?
+        .replace("{{title}}", &escape_html(&settings.title))
+        .replace("{{head}}", &head.join("\n    "))
+        .replace("{{styles}}", &styles.join("\n    "))
+        .replace("{{script}}", &script)
+        .replace("{{out_name}}", &settings.out_name);
+
+    write_string_to_file(activity_path(&settings.out_dir, "index.html"), html)
+}
+
+// Replaces `{{asset:path}}` with the URL the asset is served from.
+fn replace_asset_placeholders(template: &str, assets: &Assets) -> Result<String> {
+    const PREFIX: &str = "{{asset:";
+
+    let mut result = String::with_capacity(template.len());
+    let mut rest = template;
+
+    while let Some(start) = rest.find(PREFIX) {
+        result.push_str(&rest[..start]);
+        rest = &rest[start + PREFIX.len()..];
+
+        let end = rest
+            .find("}}")
+            .ok_or_else(|| anyhow::anyhow!("unterminated {PREFIX} placeholder in template"))?;
+        let name = rest[..end].trim().trim_start_matches('/');
+        let url = assets
+            .urls
+            .get(name)
+            .ok_or_else(|| anyhow::anyhow!("template references unknown asset `{name}`"))?;
+
+        result.push_str(url);
+        rest = &rest[end + 2..];
+    }
+
+    result.push_str(rest);
+    Ok(result)
+}
+
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
 // Bundles the snippets and activity-related code into a single file.
-fn bundle(esbuild_path: &Path) -> Result<()> {
-    let no_minify = !matches!(env::var("NO_MINIFY"), Err(VarError::NotPresent));
-    let path = PathBuf::from(OUT_DIR)
-        .join(ACTIVITY_SUBDIR)
-        .canonicalize()?;
+fn bundle(settings: &BuildSettings, esbuild_path: &Path) -> Result<()> {
+    let path = settings.out_dir.join(ACTIVITY_SUBDIR).canonicalize()?;
     let esbuild_path = esbuild_path.canonicalize()?;
     let mut command = Command::new(esbuild_path);
-    command.args([
-        "--format=iife",
-        "--bundle",
-        "./shim.js",
-        "--outfile=index.js",
-    ]);
-
-    if !no_minify {
+    command
+        .args(["--format=iife", "--bundle", "./shim.js"])
+        .arg(format!("--outfile={}.js", settings.out_name));
+
+    if settings.minify {
         command.arg("--minify");
     }
+    if settings.source_map {
+        command.arg("--sourcemap");
+    }
 
-    let exit_status = command.current_dir(path).spawn()?.wait()?;
+    let exit_status = command
+        .current_dir(path)
+        .stdout(io::stderr())
+        .spawn()?
+        .wait()?;
 
     match exit_status.success() {
         true => Ok(()),
@@ -223,19 +509,20 @@ fn bundle(esbuild_path: &Path) -> Result<()> {
 
 // After bundling there's no reason why we'd want to upload our now un-used JavaScript so we'll
 // delete it.
-fn remove_unused_js() -> Result<()> {
-    let snippets_dir = activity_path("snippets");
+fn remove_unused_js(settings: &BuildSettings) -> Result<()> {
+    let out_dir = settings.out_dir.as_path();
+    let snippets_dir = activity_path(out_dir, "snippets");
 
     if snippets_dir.exists() {
         std::fs::remove_dir_all(&snippets_dir)?;
     }
 
     for to_remove in [
-        format!("{OUT_NAME}_bg.js"),
+        format!("{}_bg.js", settings.out_name),
         "shim.js".into(),
         "sdk.js".into(),
     ] {
-        std::fs::remove_file(activity_path(to_remove))?;
+        std::fs::remove_file(activity_path(out_dir, to_remove))?;
     }
 
     Ok(())
@@ -256,12 +543,42 @@ fn write_string_to_file<P: AsRef<Path>>(path: P, contents: impl AsRef<str>) -> R
     Ok(())
 }
 
-pub fn activity_path(name: impl AsRef<str>) -> PathBuf {
-    PathBuf::from(OUT_DIR)
-        .join(ACTIVITY_SUBDIR)
-        .join(name.as_ref())
+pub fn activity_path(out_dir: &Path, name: impl AsRef<str>) -> PathBuf {
+    out_dir.join(ACTIVITY_SUBDIR).join(name.as_ref())
 }
 
-pub fn output_path(name: impl AsRef<str>) -> PathBuf {
-    PathBuf::from(OUT_DIR).join(name.as_ref())
+pub fn output_path(out_dir: &Path, name: impl AsRef<str>) -> PathBuf {
+    out_dir.join(name.as_ref())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{assets::Assets, replace_asset_placeholders};
+
+    #[test]
+    fn asset_placeholders() {
+        let assets = Assets {
+            urls: [(
+                "images/logo.png".to_string(),
+                "/images/logo.1a2b3c4d.png".to_string(),
+            )]
+            .into(),
+            ..Default::default()
+        };
+
+        assert_eq!(
+            replace_asset_placeholders(
+                r#"<img src="{{asset:images/logo.png}}"><img src="{{asset: /images/logo.png }}">"#,
+                &assets
+            )
+            .unwrap(),
+            r#"<img src="/images/logo.1a2b3c4d.png"><img src="/images/logo.1a2b3c4d.png">"#
+        );
+        assert_eq!(
+            replace_asset_placeholders("{{title}}", &assets).unwrap(),
+            "{{title}}"
+        );
+        assert!(replace_asset_placeholders("{{asset:missing.png}}", &assets).is_err());
+        assert!(replace_asset_placeholders("{{asset:images/logo.png", &assets).is_err());
+    }
 }
@@ -0,0 +1,200 @@
+// Emulates the URL mappings Discord applies to requests of an activity, so requests behave
+// the same in development as they do in production.
+// https://discord.com/developers/docs/activities/development-guides#using-external-resources
+
+use std::{collections::BTreeMap, io::Read, time::Duration};
+
+use anyhow::Result;
+use tiny_http::{Header, Request, Response};
+
+const PROXY_PREFIX: &str = "/.proxy";
+
+// Headers that only apply to a single connection and must not be forwarded.
+const HOP_BY_HOP_HEADERS: &[&str] = &[
+    "connection",
+    "keep-alive",
+    "proxy-connection",
+    "transfer-encoding",
+    "upgrade",
+    "te",
+    "trailer",
+    "host",
+    "content-length",
+];
+
+pub struct UrlMappings {
+    // Sorted by prefix length, so the most specific mapping wins.
+    mappings: Vec<(String, String)>,
+    agent: ureq::Agent,
+}
+
+impl UrlMappings {
+    pub fn new(mappings: &BTreeMap<String, String>) -> Self {
+        let mut mappings: Vec<_> = mappings
+            .iter()
+            .map(|(prefix, target)| (normalize_prefix(prefix), normalize_target(target)))
+            .collect();
+        mappings.sort_by_key(|(prefix, _)| std::cmp::Reverse(prefix.len()));
+
+        let agent = ureq::AgentBuilder::new()
+            .redirects(0)
+            .timeout(Duration::from_secs(60))
+            .build();
+
+        Self { mappings, agent }
+    }
+
+    pub fn print(&self) {
+        for (prefix, target) in &self.mappings {
+            eprintln!("  {prefix} -> {target}");
+        }
+    }
+
+    /// Returns the URL the request should be proxied to, `None` if it's for the activity itself.
+    ///
+    /// Like in production, the matched prefix is stripped from the path.
+    pub fn resolve(&self, path: &str, query: &str) -> Option<String> {
+        let path = path.strip_prefix(PROXY_PREFIX).unwrap_or(path);
+
+        self.mappings.iter().find_map(|(prefix, target)| {
+            // The root is where the activity itself is served from.
+            if prefix == "/" {
+                return None;
+            }
+
+            let rest = path.strip_prefix(prefix.as_str())?;
+            if !(rest.is_empty() || rest.starts_with('/')) {
+                return None;
+            }
+
+            let mut url = format!("{target}{rest}");
+            if !query.is_empty() {
+                url = format!("{url}?{query}");
+            }
+            Some(url)
+        })
+    }
+
+    pub fn forward(&self, mut request: Request, url: &str) -> Result<()> {
+        let mut proxied = self.agent.request(request.method().as_str(), url);
+        for header in request.headers() {
+            let name = header.field.as_str().as_str();
+            if !is_hop_by_hop(name) {
+                proxied = proxied.set(name, header.value.as_str());
+            }
+        }
+
+        let mut body = Vec::new();
+        request.as_reader().read_to_end(&mut body)?;
+
+        let response = match proxied.send_bytes(&body) {
+            Ok(response) => response,
+            // Error responses are forwarded as they are.
+            Err(ureq::Error::Status(_, response)) => response,
+            Err(err) => {
+                eprintln!("Failed to proxy request to {url}: {err}");
+                request.respond(
+                    Response::from_string(format!("Failed to proxy request: {err}"))
+                        .with_status_code(502),
+                )?;
+                return Ok(());
+            }
+        };
+
+        let status = response.status();
+        let headers: Vec<Header> = response
+            .headers_names()
+            .into_iter()
+            .filter(|name| !is_hop_by_hop(name))
+            .flat_map(|name| {
+                response
+                    .all(&name)
+                    .into_iter()
+                    .filter_map(|value| Header::from_bytes(name.as_bytes(), value.as_bytes()).ok())
+                    .collect::<Vec<_>>()
+            })
+            .collect();
+
+        let mut data = Vec::new();
+        response.into_reader().read_to_end(&mut data)?;
+
+        let mut response = Response::from_data(data).with_status_code(status);
+        for header in headers {
+            response.add_header(header);
+        }
+        request.respond(response)?;
+
+        Ok(())
+    }
+}
+
+fn is_hop_by_hop(name: &str) -> bool {
+    HOP_BY_HOP_HEADERS
+        .iter()
+        .any(|h| h.eq_ignore_ascii_case(name))
+}
+
+fn normalize_prefix(prefix: &str) -> String {
+    format!("/{}", prefix.trim_matches('/'))
+}
+
+// Targets in the developer portal don't have a scheme, but local ones usually need http.
+fn normalize_target(target: &str) -> String {
+    let target = target.trim_end_matches('/');
+    if target.contains("://") {
+        target.to_string()
+    } else {
+        format!("https://{target}")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::UrlMappings;
+
+    fn mappings(mappings: &[(&str, &str)]) -> UrlMappings {
+        UrlMappings::new(
+            &mappings
+                .iter()
+                .map(|(prefix, target)| (prefix.to_string(), target.to_string()))
+                .collect(),
+        )
+    }
+
+    #[test]
+    fn resolves_the_most_specific_mapping() {
+        let mappings = mappings(&[
+            ("/", "localhost:3000"),
+            ("/api", "api.example.com"),
+            ("api/v2/", "http://localhost:8080/v2/"),
+        ]);
+
+        let resolve = |path| mappings.resolve(path, "");
+        assert_eq!(
+            resolve("/api/users").as_deref(),
+            Some("https://api.example.com/users")
+        );
+        assert_eq!(resolve("/api").as_deref(), Some("https://api.example.com"));
+        assert_eq!(
+            resolve("/api/v2/users").as_deref(),
+            Some("http://localhost:8080/v2/users")
+        );
+        assert_eq!(
+            resolve("/.proxy/api/users").as_deref(),
+            Some("https://api.example.com/users")
+        );
+        assert_eq!(
+            mappings.resolve("/api/users", "id=1&page=2").as_deref(),
+            Some("https://api.example.com/users?id=1&page=2")
+        );
+    }
+
+    #[test]
+    fn leaves_the_activity_alone() {
+        let mappings = mappings(&[("/", "localhost:3000"), ("/api", "api.example.com")]);
+
+        assert_eq!(mappings.resolve("/", ""), None);
+        assert_eq!(mappings.resolve("/index.js", ""), None);
+        assert_eq!(mappings.resolve("/apis", ""), None);
+    }
+}
@@ -0,0 +1,232 @@
+// The Discord embedded app SDK that the bundle imports: the copy built into this tool, or the one
+// from `node_modules` or a vendored npm tarball when the configuration pins another version.
+
+use std::{
+    env, fs,
+    path::{Path, PathBuf},
+    process::Command,
+};
+
+use anyhow::{Context, Result};
+use flate2::read::GzDecoder;
+
+use crate::{
+    activity_path, config::BuildSettings, install, output_path, write_string_to_file, SDK_IMPORT,
+};
+
+/// The version of the SDK in `static/sdk.js`.
+pub const BUILT_IN_VERSION: &str = "1.9.0";
+
+/// Tells the activity crate which version it's bundled with, see `activity::SDK_VERSION`.
+pub const VERSION_ENV: &str = "ACTIVITY_SDK_VERSION";
+
+pub struct Sdk {
+    pub version: String,
+    source: Source,
+}
+
+enum Source {
+    BuiltIn,
+    /// The directory of the npm package.
+    Package(PathBuf),
+}
+
+/// Finds the SDK to bundle, the vendored tarball or `node_modules` if a version is pinned.
+pub fn resolve(settings: &BuildSettings) -> Result<Sdk> {
+    let config = &settings.sdk;
+
+    let sdk = match (&config.path, &config.version) {
+        (Some(path), _) => package(extract_tarball(settings, path)?)?,
+        (None, Some(pinned)) => match node_modules_package()? {
+            Some(dir) => package(dir)?,
+            None if pinned == BUILT_IN_VERSION => built_in(),
+            None => anyhow::bail!(
+                "the configuration pins the Discord SDK {pinned}, but activity-build only has \
+                 {BUILT_IN_VERSION} built in. Install it with \
+                 `npm install {SDK_IMPORT}@{pinned}` or vendor its npm tarball with `sdk.path`"
+            ),
+        },
+        (None, None) => built_in(),
+    };
+
+    if let Some(pinned) = &config.version {
+        if &sdk.version != pinned {
+            anyhow::bail!(
+                "the configuration pins the Discord SDK {pinned}, but {} has {}",
+                sdk.source.describe(),
+                sdk.version
+            );
+        }
+    }
+
+    Ok(sdk)
+}
+
+/// Whether an SDK has the commands and events of bindings written for another version.
+///
+/// This is a copy of the rule in `activity/src/version.rs`, which is where it's tested. The crates
+/// are published separately, so they can't share the file, a test keeps them the same instead.
+pub fn is_compatible(bundled: &str, bindings: &str) -> bool {
+    let (Some(bundled), Some(bindings)) = (parse_version(bundled), parse_version(bindings)) else {
+        return false;
+    };
+
+    let same_major = match bindings.0 {
+        0 => bundled.0 == 0 && bundled.1 == bindings.1,
+        major => bundled.0 == major,
+    };
+    same_major && bundled >= bindings
+}
+
+fn parse_version(version: &str) -> Option<(u64, u64, u64)> {
+    let version = version.split(['-', '+']).next()?;
+    let mut parts = version.split('.').map(|part| part.parse().ok());
+    Some((parts.next()??, parts.next()??, parts.next()??))
+}
+
+/// Writes the SDK into the bundle as `sdk.js`, packages are bundled with their dependencies.
+pub fn write(settings: &BuildSettings, sdk: &Sdk) -> Result<()> {
+    let out_path = activity_path(&settings.out_dir, "sdk.js");
+
+    let dir = match &sdk.source {
+        Source::BuiltIn => {
+            return write_string_to_file(out_path, include_str!("./static/sdk.js"));
+        }
+        Source::Package(dir) => dir,
+    };
+
+    let entry = package_entry(dir)?;
+    // The built-in bundler only handles relative imports, not the dependencies of the package.
+    let esbuild_path =
+        install::ensure_esbuild(&settings.esbuild, settings.offline).with_context(|| {
+            format!(
+                "the Discord SDK {} is bundled with esbuild, also with `--bundler builtin`, only \
+                 the built-in SDK {BUILT_IN_VERSION} works without it",
+                sdk.version
+            )
+        })?;
+    let exit_status = Command::new(esbuild_path)
+        .args([
+            "--bundle",
+            "--format=esm",
+            "--platform=browser",
+            "--log-level=warning",
+        ])
+        .arg(&entry)
+        .arg(format!("--outfile={}", out_path.display()))
+        .status()
+        .context("failed to run esbuild")?;
+
+    if !exit_status.success() {
+        anyhow::bail!(
+            "esbuild failed to bundle the Discord SDK {} from {}. Its dependencies are resolved \
+             from `node_modules`, run `npm install` or vendor a tarball that bundles them",
+            sdk.version,
+            dir.display()
+        );
+    }
+
+    Ok(())
+}
+
+impl Sdk {
+    pub fn is_built_in(&self) -> bool {
+        matches!(self.source, Source::BuiltIn)
+    }
+}
+
+impl Source {
+    fn describe(&self) -> String {
+        match self {
+            Source::BuiltIn => "the SDK built into activity-build".into(),
+            Source::Package(dir) => dir.display().to_string(),
+        }
+    }
+}
+
+fn built_in() -> Sdk {
+    Sdk {
+        version: BUILT_IN_VERSION.into(),
+        source: Source::BuiltIn,
+    }
+}
+
+fn package(dir: PathBuf) -> Result<Sdk> {
+    let version = package_json(&dir)?["version"]
+        .as_str()
+        .ok_or_else(|| anyhow::anyhow!("{} has no version", dir.display()))?
+        .to_string();
+
+    Ok(Sdk {
+        version,
+        source: Source::Package(dir),
+    })
+}
+
+fn package_json(dir: &Path) -> Result<serde_json::Value> {
+    let path = dir.join("package.json");
+    let contents =
+        fs::read_to_string(&path).with_context(|| format!("failed to read {}", path.display()))?;
+    serde_json::from_str(&contents).with_context(|| format!("failed to parse {}", path.display()))
+}
+
+// The ES module of the package, from `exports` or `module`.
+fn package_entry(dir: &Path) -> Result<PathBuf> {
+    let package = package_json(dir)?;
+
+    let import = &package["exports"]["."]["import"];
+    let entry = import
+        .as_str()
+        .or_else(|| import["default"].as_str())
+        .or_else(|| package["module"].as_str())
+        .or_else(|| package["main"].as_str())
+        .ok_or_else(|| anyhow::anyhow!("{} has no entry point", dir.display()))?;
+
+    Ok(dir.join(entry.trim_start_matches("./")))
+}
+
+// Looks for the package in the current and all parent directories, like npm does.
+fn node_modules_package() -> Result<Option<PathBuf>> {
+    Ok(env::current_dir()?
+        .ancestors()
+        .map(|dir| dir.join("node_modules").join(SDK_IMPORT))
+        .find(|dir| dir.join("package.json").is_file()))
+}
+
+// npm tarballs have everything in a `package` directory. It's extracted into the output
+// directory, so esbuild finds the dependencies in the `node_modules` of the crate.
+fn extract_tarball(settings: &BuildSettings, path: &Path) -> Result<PathBuf> {
+    let tarball = fs::read(path)
+        .with_context(|| format!("failed to read the vendored SDK {}", path.display()))?;
+
+    let dir = output_path(&settings.out_dir, "sdk");
+    if dir.exists() {
+        fs::remove_dir_all(&dir)?;
+    }
+    fs::create_dir_all(&dir)?;
+
+    tar::Archive::new(GzDecoder::new(tarball.as_slice()))
+        .unpack(&dir)
+        .with_context(|| format!("failed to extract the vendored SDK {}", path.display()))?;
+
+    Ok(dir.join("package"))
+}
+
+#[cfg(test)]
+mod tests {
+    // The body of a function, from its name to its closing brace.
+    fn function<'a>(source: &'a str, name: &str) -> &'a str {
+        let start = source.find(&format!("fn {name}(")).unwrap();
+        let end = start + source[start..].find("\n}\n").unwrap();
+        &source[start..end]
+    }
+
+    #[test]
+    fn same_compatibility_rule_as_the_activity_crate() {
+        let activity = include_str!("../../activity/src/version.rs");
+        let own = include_str!("./sdk.rs");
+        for name in ["is_compatible", "parse_version"] {
+            assert_eq!(function(own, name), function(activity, name), "{name}");
+        }
+    }
+}
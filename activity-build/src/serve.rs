@@ -0,0 +1,323 @@
+// Development server that serves the activity, rebuilds it when the sources change and
+// reloads the page afterwards.
+
+use std::{
+    fs,
+    path::{Component, Path, PathBuf},
+    sync::{mpsc, Arc, Condvar, Mutex},
+    thread,
+    time::Duration,
+};
+
+use anyhow::Result;
+use notify::{EventKind, RecursiveMode, Watcher};
+use tiny_http::{Header, Request, Response, Server};
+
+use crate::{
+    activity_path,
+    cli::{BuildArgs, ServeArgs},
+    config,
+    proxy::UrlMappings,
+};
+
+const LIVE_RELOAD_PATH: &str = "/__activity/live-reload";
+const LIVE_RELOAD_TIMEOUT: Duration = Duration::from_secs(30);
+
+// The builds of the server go into numbered directories in this one, see `build`.
+const BUILDS_SUBDIR: &str = ".serve";
+
+// Changes usually come in bursts, e.g. when an editor saves multiple files.
+const DEBOUNCE: Duration = Duration::from_millis(200);
+
+// Everything that is an input to the build, the output directory is excluded from this.
+const WATCHED_PATHS: &[&str] = &[
+    "src",
+    "Cargo.toml",
+    "Activity.toml",
+    "public",
+    "assets",
+    "index.html",
+    ".env",
+    ".env.dev",
+    ".env.release",
+];
+
+/// Incremented after every successful build, the live reload client compares it to the
+/// version it was loaded with.
+struct BuildVersion {
+    version: Mutex<u64>,
+    changed: Condvar,
+    /// The output directory of the build that is served.
+    out_dir: Mutex<PathBuf>,
+}
+
+pub fn serve(args: ServeArgs) -> Result<()> {
+    let ServeArgs {
+        port,
+        build: build_args,
+    } = args;
+    let config = config::load()?;
+    let out_dir = config.out_dir(build_args.out_dir.as_deref());
+    let url_mappings = Arc::new(UrlMappings::new(&config.url_mappings));
+
+    // Configured paths can be outside of the default ones.
+    let watched_paths: Vec<PathBuf> = WATCHED_PATHS
+        .iter()
+        .map(PathBuf::from)
+        .chain(config.public_dir.clone())
+        .chain(config.template.clone())
+        .chain(config.favicon.clone())
+        .collect();
+
+    let builds_dir = out_dir.join(BUILDS_SUBDIR);
+    if builds_dir.exists() {
+        fs::remove_dir_all(&builds_dir)?;
+    }
+
+    let first_build = build(&build_args, &builds_dir, 0);
+    if let Err(err) = &first_build {
+        eprintln!("Build failed: {err:#}");
+    }
+
+    let state = Arc::new(BuildVersion {
+        version: Mutex::new(0),
+        changed: Condvar::new(),
+        out_dir: Mutex::new(first_build.unwrap_or_else(|_| builds_dir.join("0"))),
+    });
+
+    let watcher_state = state.clone();
+    thread::spawn(move || {
+        if let Err(err) = watch(build_args, &watched_paths, &out_dir, watcher_state) {
+            eprintln!("File watcher stopped: {err:#}");
+        }
+    });
+
+    let server = Server::http(("127.0.0.1", port))
+        .map_err(|err| anyhow::anyhow!("failed to start server on port {port}: {err}"))?;
+    eprintln!("Serving activity on http://127.0.0.1:{port}");
+    url_mappings.print();
+
+    for request in server.incoming_requests() {
+        let state = state.clone();
+        let url_mappings = url_mappings.clone();
+        thread::spawn(move || {
+            if let Err(err) = handle_request(request, &state, &url_mappings) {
+                eprintln!("Failed to handle request: {err}");
+            }
+        });
+    }
+
+    Ok(())
+}
+
+fn watch(
+    build_args: BuildArgs,
+    watched_paths: &[PathBuf],
+    out_dir: &Path,
+    state: Arc<BuildVersion>,
+) -> Result<()> {
+    let builds_dir = out_dir.join(BUILDS_SUBDIR);
+    let mut build_number = 0;
+
+    let (tx, rx) = mpsc::channel();
+    let mut watcher = notify::recommended_watcher(tx)?;
+
+    for path in watched_paths {
+        if path.exists() {
+            watcher.watch(path, RecursiveMode::Recursive)?;
+        }
+    }
+
+    while let Ok(event) = rx.recv() {
+        if !is_relevant(event?, out_dir) {
+            continue;
+        }
+
+        // Wait for the burst of changes to settle before rebuilding.
+        while rx.recv_timeout(DEBOUNCE).is_ok() {}
+
+        eprintln!("Change detected, rebuilding...");
+        build_number += 1;
+        match build(&build_args, &builds_dir, build_number) {
+            Ok(new_out_dir) => {
+                let old_out_dir =
+                    std::mem::replace(&mut *state.out_dir.lock().unwrap(), new_out_dir);
+                *state.version.lock().unwrap() += 1;
+                state.changed.notify_all();
+                eprintln!("Rebuilt activity");
+                remove_old_builds(&builds_dir, build_number, &old_out_dir)?;
+            }
+            Err(err) => eprintln!("Build failed: {err:#}"),
+        }
+    }
+
+    Ok(())
+}
+
+// Every build gets a directory of its own, so the page and its files keep being served from the
+// previous build while the next one runs, instead of 404ing while the output is recreated.
+fn build(build_args: &BuildArgs, builds_dir: &Path, build_number: u64) -> Result<PathBuf> {
+    let out_dir = builds_dir.join(build_number.to_string());
+    let build_args = BuildArgs {
+        out_dir: Some(out_dir.clone()),
+        ..build_args.clone()
+    };
+    crate::run_build(&build_args)?;
+    Ok(out_dir)
+}
+
+// Requests that started before the swap may still read from the previously served build, so it's
+// kept until the next one.
+fn remove_old_builds(builds_dir: &Path, current: u64, previous: &Path) -> Result<()> {
+    for entry in fs::read_dir(builds_dir)? {
+        let path = entry?.path();
+        if path != builds_dir.join(current.to_string()) && path != previous {
+            fs::remove_dir_all(path)?;
+        }
+    }
+    Ok(())
+}
+
+fn is_relevant(event: notify::Event, out_dir: &Path) -> bool {
+    let is_change = matches!(
+        event.kind,
+        EventKind::Create(_) | EventKind::Modify(_) | EventKind::Remove(_)
+    );
+    let out_dir = out_dir.canonicalize().ok();
+
+    is_change
+        && event.paths.iter().any(|path| match &out_dir {
+            Some(out_dir) => !path.starts_with(out_dir),
+            None => true,
+        })
+}
+
+fn handle_request(
+    request: Request,
+    state: &BuildVersion,
+    url_mappings: &UrlMappings,
+) -> Result<()> {
+    let url = request.url().to_string();
+    let (path, query) = url.split_once('?').unwrap_or((&url, ""));
+
+    if path == LIVE_RELOAD_PATH {
+        let client_version = query
+            .split('&')
+            .find_map(|pair| pair.strip_prefix("version="))
+            .unwrap_or_default()
+            .to_string();
+
+        let version = wait_for_change(state, &client_version);
+        request.respond(Response::from_string(version).with_header(no_store()))?;
+        return Ok(());
+    }
+
+    if let Some(target) = url_mappings.resolve(path, query) {
+        return url_mappings.forward(request, &target);
+    }
+
+    let out_dir = state.out_dir.lock().unwrap().clone();
+    let Some(file) = resolve_file(&out_dir, path) else {
+        request.respond(Response::from_string("Not Found").with_status_code(404))?;
+        return Ok(());
+    };
+
+    let content_type = content_type(&file);
+    let mut body = fs::read(&file)?;
+
+    if content_type.starts_with("text/html") {
+        let version = state.version.lock().unwrap().to_string();
+        body = inject_live_reload(&String::from_utf8_lossy(&body), &version).into_bytes();
+    }
+
+    let response = Response::from_data(body)
+        .with_header(header("Content-Type", content_type))
+        .with_header(no_store());
+    request.respond(response)?;
+
+    Ok(())
+}
+
+// Blocks until a build newer than the one the client knows about finished, or the timeout
+// elapsed. The client polls again in both cases.
+fn wait_for_change(state: &BuildVersion, client_version: &str) -> String {
+    let version = state.version.lock().unwrap();
+    let (version, _) = state
+        .changed
+        .wait_timeout_while(version, LIVE_RELOAD_TIMEOUT, |v| {
+            v.to_string() == client_version
+        })
+        .unwrap();
+
+    version.to_string()
+}
+
+fn resolve_file(out_dir: &Path, url_path: &str) -> Option<PathBuf> {
+    let relative = Path::new(url_path.trim_start_matches('/'));
+
+    // Don't serve anything outside of the activity directory.
+    if relative
+        .components()
+        .any(|c| !matches!(c, Component::Normal(_)))
+    {
+        return None;
+    }
+
+    let mut path = activity_path(out_dir, relative.to_string_lossy());
+    if path.is_dir() {
+        path = path.join("index.html");
+    }
+
+    path.is_file().then_some(path)
+}
+
+fn inject_live_reload(html: &str, version: &str) -> String {
+    let script = format!(
+        "<script>{}</script>",
+        include_str!("./static/live-reload.js")
+            .replace("__ACTIVITY_BUILD_VERSION__", version)
+            .replace("__ACTIVITY_LIVE_RELOAD_PATH__", LIVE_RELOAD_PATH)
+    );
+
+    match html.rfind("</body>") {
+        Some(index) => format!("{}{}{}", &html[..index], script, &html[index..]),
+        None => format!("{html}{script}"),
+    }
+}
+
+pub fn content_type(path: &Path) -> &'static str {
+    match path
+        .extension()
+        .and_then(|e| e.to_str())
+        .unwrap_or_default()
+    {
+        "html" => "text/html; charset=utf-8",
+        "js" | "mjs" => "text/javascript; charset=utf-8",
+        "wasm" => "application/wasm",
+        "css" => "text/css; charset=utf-8",
+        "json" | "map" => "application/json",
+        "svg" => "image/svg+xml",
+        "png" => "image/png",
+        "jpg" | "jpeg" => "image/jpeg",
+        "gif" => "image/gif",
+        "webp" => "image/webp",
+        "ico" => "image/x-icon",
+        "woff" => "font/woff",
+        "woff2" => "font/woff2",
+        "ttf" => "font/ttf",
+        "mp3" => "audio/mpeg",
+        "ogg" => "audio/ogg",
+        "wav" => "audio/wav",
+        "mp4" => "video/mp4",
+        "txt" => "text/plain; charset=utf-8",
+        _ => "application/octet-stream",
+    }
+}
+
+fn header(name: &str, value: &str) -> Header {
+    Header::from_bytes(name.as_bytes(), value.as_bytes()).expect("invalid header")
+}
+
+fn no_store() -> Header {
+    header("Cache-Control", "no-store")
+}
@@ -0,0 +1,144 @@
+// How big the bundle is: compressed sizes of its files, the largest parts of the Wasm module and
+// the size budgets that fail the build when they're exceeded.
+
+use std::{cmp::Reverse, collections::BTreeMap, io::Write, path::Path};
+
+use anyhow::{Context, Result};
+use flate2::{write::GzEncoder, Compression};
+use serde::Serialize;
+
+use crate::{
+    config::{Budget, ByteSize},
+    format_size,
+    wasm::ModuleInfo,
+    Artifact,
+};
+
+/// Compresses the data with gzip at the best level.
+pub fn gzip(data: &[u8]) -> Result<Vec<u8>> {
+    let mut encoder = GzEncoder::new(Vec::new(), Compression::best());
+    encoder.write_all(data)?;
+    Ok(encoder.finish()?)
+}
+
+/// Compresses the data with brotli at the best quality.
+pub fn brotli(data: &[u8]) -> Result<Vec<u8>> {
+    let mut writer = brotli::CompressorWriter::new(Vec::new(), 4096, 11, 22);
+    writer.write_all(data)?;
+    Ok(writer.into_inner())
+}
+
+/// A function or data segment of the Wasm module.
+#[derive(Serialize)]
+pub struct Item {
+    pub name: String,
+    pub size: u64,
+}
+
+#[derive(Serialize, Default)]
+pub struct WasmBreakdown {
+    pub functions: Vec<Item>,
+    pub data_segments: Vec<Item>,
+    /// Whether the module has a name section, wasm-opt removes it unless it's passed `-g`.
+    pub has_names: bool,
+}
+
+/// Returns the `top` largest functions and data segments of the module.
+pub fn wasm_breakdown(wasm: &[u8], top: usize) -> Result<WasmBreakdown> {
+    let info = ModuleInfo::parse(wasm)?;
+
+    let mut functions: Vec<Item> = info
+        .functions
+        .into_iter()
+        .map(|f| Item {
+            name: f.name.unwrap_or_else(|| format!("function[{}]", f.index)),
+            size: f.range.len() as u64,
+        })
+        .collect();
+    let mut data_segments: Vec<Item> = info
+        .data_segments
+        .into_iter()
+        .map(|d| Item {
+            name: d.name.unwrap_or_else(|| format!("data[{}]", d.index)),
+            size: d.size,
+        })
+        .collect();
+
+    for items in [&mut functions, &mut data_segments] {
+        items.sort_by_key(|item| Reverse(item.size));
+        items.truncate(top);
+    }
+
+    Ok(WasmBreakdown {
+        functions,
+        data_segments,
+        has_names: info.has_names,
+    })
+}
+
+pub fn print_breakdown(wasm: &Path, breakdown: &WasmBreakdown) {
+    for (title, items) in [
+        ("functions", &breakdown.functions),
+        ("data segments", &breakdown.data_segments),
+    ] {
+        eprintln!("\nLargest {title} of {}:", wasm.display());
+        for item in items {
+            eprintln!("{:>10}  {}", format_size(item.size), item.name);
+        }
+    }
+
+    if !breakdown.has_names {
+        eprintln!(
+            "\nThe module has no name section, wasm-opt removes it unless `-g` is one of its flags"
+        );
+    }
+}
+
+/// Fails if a file of the bundle is larger than its budget.
+pub fn check_budgets(
+    activity_dir: &Path,
+    artifacts: &[Artifact],
+    budgets: &BTreeMap<String, Budget>,
+) -> Result<()> {
+    let mut exceeded = Vec::new();
+
+    for (pattern, budget) in budgets {
+        let glob = glob::Pattern::new(pattern)
+            .with_context(|| format!("invalid pattern `{pattern}` in budgets"))?;
+
+        for artifact in artifacts {
+            let name = artifact
+                .path
+                .strip_prefix(activity_dir)
+                .unwrap_or(&artifact.path)
+                .to_string_lossy()
+                .replace('\\', "/");
+            if !glob.matches(&name) {
+                continue;
+            }
+
+            let sizes = [
+                ("", Some(artifact.size), budget.raw),
+                (" gzipped", artifact.gzip_size, budget.gzip),
+                (" with brotli", artifact.brotli_size, budget.brotli),
+            ];
+            for (compression, size, limit) in sizes {
+                if let (Some(size), Some(ByteSize(limit))) = (size, limit) {
+                    if size > limit {
+                        exceeded.push(format!(
+                            "{name} is {}{compression}, the budget is {}",
+                            format_size(size),
+                            format_size(limit)
+                        ));
+                    }
+                }
+            }
+        }
+    }
+
+    if !exceeded.is_empty() {
+        anyhow::bail!("size budget exceeded:\n  {}", exceeded.join("\n  "));
+    }
+
+    Ok(())
+}
@@ -0,0 +1,23 @@
+(function () {
+  var version = "__ACTIVITY_BUILD_VERSION__";
+
+  function poll() {
+    fetch("__ACTIVITY_LIVE_RELOAD_PATH__?version=" + version)
+      .then(function (res) {
+        return res.text();
+      })
+      .then(function (current) {
+        if (current !== version) {
+          location.reload();
+        } else {
+          poll();
+        }
+      })
+      .catch(function () {
+        // The server is probably restarting, try again in a bit.
+        setTimeout(poll, 1000);
+      });
+  }
+
+  poll();
+})();
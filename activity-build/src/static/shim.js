@@ -1,8 +1,8 @@
-import init, { start } from "./index_bg.js";
+import init, { {{export}} } from "./{{out_name}}_bg.js";
 
 async function main() {
-  await init("/index_bg.wasm");
-  start();
+  await init("/{{out_name}}_bg.wasm");
+  {{export}}();
 }
 
 main();
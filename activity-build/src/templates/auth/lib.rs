@@ -0,0 +1,67 @@
+use activity::*;
+use serde::{Deserialize, Serialize};
+use wasm_bindgen::{JsCast, JsValue};
+use wasm_bindgen_futures::JsFuture;
+use web_sys::{Request, RequestInit, Response};
+
+#[activity(client_id = build_env!("CLIENT_ID"), auto_ready, panic_hook)]
+pub async fn start(sdk: DiscordSDK) -> Result<(), JsValue> {
+    let res = sdk
+        .authorize(AuthorizeArgs {
+            client_id: sdk.client_id(),
+            response_type: Some("code".to_string()),
+            prompt: Some("none".to_string()),
+            scope: vec!["identify".to_string(), "guilds".to_string()],
+            ..Default::default()
+        })
+        .await?;
+
+    let access_token = exchange_code(&res.code).await?;
+    let res = sdk
+        .authenticate(AuthenticateArgs {
+            access_token: Some(access_token),
+        })
+        .await?;
+
+    console_log!("Authenticated as {}", res.user.username);
+
+    Ok(())
+}
+
+#[derive(Serialize)]
+struct TokenReq {
+    code: String,
+}
+
+#[derive(Deserialize)]
+struct TokenRes {
+    access_token: String,
+}
+
+// The client secret must not be part of the activity, so the server in `server/` exchanges the
+// code for an access token. Discord proxies `/.proxy/api` to it through the URL mapping.
+async fn exchange_code(code: &str) -> Result<String, JsValue> {
+    let body = serde_json::to_string(&TokenReq {
+        code: code.to_string(),
+    })
+    .unwrap();
+
+    let mut opts = RequestInit::new();
+    opts.method("POST").body(Some(&JsValue::from_str(&body)));
+
+    let req = Request::new_with_str_and_init("/.proxy/api/token", &opts)?;
+    req.headers().set("Content-Type", "application/json")?;
+
+    let window = web_sys::window().unwrap();
+    let res: Response = JsFuture::from(window.fetch_with_request(&req))
+        .await?
+        .dyn_into()?;
+    if !res.ok() {
+        return Err(JsValue::from_str("failed to exchange the code"));
+    }
+
+    let json = JsFuture::from(res.json()?).await?;
+    let res: TokenRes = serde_wasm_bindgen::from_value(json)?;
+
+    Ok(res.access_token)
+}
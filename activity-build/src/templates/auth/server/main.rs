@@ -0,0 +1,75 @@
+//! Exchanges the OAuth2 codes of the activity for access tokens, which needs the client secret.
+//!
+//! Run it with `cargo run` in this directory, with `CLIENT_ID` and `CLIENT_SECRET` set.
+
+use std::{env, io::Read};
+
+use serde::{Deserialize, Serialize};
+use tiny_http::{Header, Method, Request, Response, Server};
+
+const ADDRESS: &str = "127.0.0.1:8000";
+const TOKEN_URL: &str = "https://discord.com/api/oauth2/token";
+
+#[derive(Deserialize)]
+struct TokenReq {
+    code: String,
+}
+
+#[derive(Serialize, Deserialize)]
+struct TokenRes {
+    access_token: String,
+}
+
+fn main() {
+    let client_id = env::var("CLIENT_ID").expect("CLIENT_ID is not set");
+    let client_secret = env::var("CLIENT_SECRET").expect("CLIENT_SECRET is not set");
+
+    let server = Server::http(ADDRESS).expect("failed to start the server");
+    println!("Listening on http://{ADDRESS}");
+
+    for request in server.incoming_requests() {
+        // The URL mapping strips the `/api` prefix.
+        if request.method() != &Method::Post || request.url() != "/token" {
+            let _ = request.respond(Response::empty(404));
+            continue;
+        }
+        handle_token(request, &client_id, &client_secret);
+    }
+}
+
+fn handle_token(mut request: Request, client_id: &str, client_secret: &str) {
+    let mut body = String::new();
+    let req: Option<TokenReq> = request
+        .as_reader()
+        .read_to_string(&mut body)
+        .ok()
+        .and_then(|_| serde_json::from_str(&body).ok());
+    let Some(req) = req else {
+        let _ = request.respond(Response::empty(400));
+        return;
+    };
+
+    let res = ureq::post(TOKEN_URL).send_form(&[
+        ("client_id", client_id),
+        ("client_secret", client_secret),
+        ("grant_type", "authorization_code"),
+        ("code", &req.code),
+    ]);
+    let token: TokenRes = match res.map(|res| res.into_json()) {
+        Ok(Ok(token)) => token,
+        Ok(Err(err)) => {
+            eprintln!("invalid response from Discord: {err}");
+            let _ = request.respond(Response::empty(502));
+            return;
+        }
+        Err(err) => {
+            eprintln!("failed to exchange the code: {err}");
+            let _ = request.respond(Response::empty(502));
+            return;
+        }
+    };
+
+    let json = serde_json::to_string(&token).unwrap();
+    let header = Header::from_bytes("Content-Type", "application/json").unwrap();
+    let _ = request.respond(Response::from_string(json).with_header(header));
+}
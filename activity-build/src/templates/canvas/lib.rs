@@ -0,0 +1,68 @@
+use std::{cell::RefCell, f64::consts::TAU, rc::Rc};
+
+use activity::*;
+use wasm_bindgen::{closure::Closure, JsCast};
+use web_sys::{CanvasRenderingContext2d, HtmlCanvasElement, Window};
+
+#[activity(client_id = build_env!("CLIENT_ID"), auto_ready, panic_hook)]
+pub async fn start(_sdk: DiscordSDK) -> Result<()> {
+    let window = web_sys::window().unwrap();
+    let document = window.document().unwrap();
+
+    let canvas: HtmlCanvasElement = document.create_element("canvas")?.unchecked_into();
+    canvas.style().set_property("display", "block")?;
+    document
+        .body()
+        .unwrap()
+        .style()
+        .set_property("margin", "0")?;
+    document.body().unwrap().append_child(&canvas)?;
+
+    let context: CanvasRenderingContext2d = canvas.get_context("2d")?.unwrap().unchecked_into();
+    animate(window, canvas, context);
+
+    Ok(())
+}
+
+// Draws a frame whenever the browser is ready for one.
+fn animate(window: Window, canvas: HtmlCanvasElement, context: CanvasRenderingContext2d) {
+    let frame = Rc::new(RefCell::new(None::<Closure<dyn FnMut(f64)>>));
+    let next_frame = frame.clone();
+    let request_window = window.clone();
+
+    *frame.borrow_mut() = Some(Closure::new(move |time: f64| {
+        draw(&window, &canvas, &context, time);
+
+        request_frame(&window, next_frame.borrow().as_ref().unwrap());
+    }));
+
+    request_frame(&request_window, frame.borrow().as_ref().unwrap());
+}
+
+fn request_frame(window: &Window, callback: &Closure<dyn FnMut(f64)>) {
+    let _ = window.request_animation_frame(callback.as_ref().unchecked_ref());
+}
+
+fn draw(
+    window: &Window,
+    canvas: &HtmlCanvasElement,
+    context: &CanvasRenderingContext2d,
+    time: f64,
+) {
+    let width = window.inner_width().unwrap().as_f64().unwrap();
+    let height = window.inner_height().unwrap().as_f64().unwrap();
+    canvas.set_width(width as u32);
+    canvas.set_height(height as u32);
+
+    context.set_fill_style(&"#23272a".into());
+    context.fill_rect(0.0, 0.0, width, height);
+
+    let radius = width.min(height) / 4.0;
+    let x = width / 2.0 + (time / 1000.0).cos() * radius;
+    let y = height / 2.0 + (time / 1000.0).sin() * radius;
+
+    context.set_fill_style(&"#5865f2".into());
+    context.begin_path();
+    let _ = context.arc(x, y, radius / 4.0, 0.0, TAU);
+    context.fill();
+}
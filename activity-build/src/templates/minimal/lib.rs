@@ -0,0 +1,8 @@
+use activity::*;
+
+#[activity(client_id = build_env!("CLIENT_ID"), auto_ready, panic_hook)]
+pub async fn start(sdk: DiscordSDK) -> Result<()> {
+    console_log!("Activity ready in channel {:?}", sdk.channel_id());
+
+    Ok(())
+}
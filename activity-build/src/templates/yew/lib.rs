@@ -0,0 +1,27 @@
+use activity::*;
+use yew::prelude::*;
+
+#[activity(client_id = build_env!("CLIENT_ID"), panic_hook)]
+pub async fn start(sdk: DiscordSDK) -> Result<()> {
+    yew::Renderer::<App>::new().render();
+
+    sdk.ready().await?;
+
+    Ok(())
+}
+
+#[function_component]
+fn App() -> Html {
+    let clicks = use_state(|| 0);
+    let onclick = {
+        let clicks = clicks.clone();
+        move |_| clicks.set(*clicks + 1)
+    };
+
+    html! {
+        <main>
+            <h1>{ "Hello from Rust" }</h1>
+            <button {onclick}>{ format!("Clicked {} times", *clicks) }</button>
+        </main>
+    }
+}
@@ -0,0 +1,240 @@
+// Reads and rewrites the parts of Wasm modules the build tool cares about.
+// https://webassembly.github.io/spec/core/binary/modules.html
+
+use std::{collections::HashMap, ops::Range};
+
+use anyhow::Result;
+use wasmparser::{Name, NameSectionReader, Parser, Payload, TypeRef};
+
+const HEADER_LEN: usize = 8;
+const CUSTOM_SECTION: u8 = 0;
+
+pub struct Function {
+    pub index: u32,
+    /// The range of the function body in the module.
+    pub range: Range<usize>,
+    pub name: Option<String>,
+}
+
+pub struct DataSegment {
+    pub index: u32,
+    pub size: u64,
+    pub name: Option<String>,
+}
+
+/// The functions and data segments of a module.
+pub struct ModuleInfo {
+    pub functions: Vec<Function>,
+    pub data_segments: Vec<DataSegment>,
+    /// Whether the module has a name section, wasm-opt removes it unless it's passed `-g`.
+    pub has_names: bool,
+    /// Where the content of the code section starts, DWARF addresses are relative to it.
+    pub code_offset: Option<usize>,
+}
+
+impl ModuleInfo {
+    pub fn parse(wasm: &[u8]) -> Result<Self> {
+        let mut info = ModuleInfo {
+            functions: Vec::new(),
+            data_segments: Vec::new(),
+            has_names: false,
+            code_offset: None,
+        };
+        let mut imported_functions = 0;
+        let mut function_names = HashMap::new();
+        let mut data_names = HashMap::new();
+
+        for payload in Parser::new(0).parse_all(wasm) {
+            match payload? {
+                Payload::ImportSection(reader) => {
+                    for import in reader {
+                        if matches!(import?.ty, TypeRef::Func(_)) {
+                            imported_functions += 1;
+                        }
+                    }
+                }
+                Payload::CodeSectionStart { range, .. } => info.code_offset = Some(range.start),
+                Payload::CodeSectionEntry(body) => info.functions.push(Function {
+                    index: imported_functions + info.functions.len() as u32,
+                    range: body.range(),
+                    name: None,
+                }),
+                Payload::DataSection(reader) => {
+                    for (index, data) in reader.into_iter().enumerate() {
+                        info.data_segments.push(DataSegment {
+                            index: index as u32,
+                            size: data?.data.len() as u64,
+                            name: None,
+                        });
+                    }
+                }
+                Payload::CustomSection(reader) if reader.name() == "name" => {
+                    info.has_names = true;
+                    for name in NameSectionReader::new(reader.data(), reader.data_offset()) {
+                        let (names, map) = match name? {
+                            Name::Function(map) => (&mut function_names, map),
+                            Name::Data(map) => (&mut data_names, map),
+                            _ => continue,
+                        };
+                        for naming in map {
+                            let naming = naming?;
+                            names.insert(naming.index, demangle(naming.name));
+                        }
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        for function in &mut info.functions {
+            function.name = function_names.remove(&function.index);
+        }
+        for segment in &mut info.data_segments {
+            segment.name = data_names.remove(&segment.index);
+        }
+
+        Ok(info)
+    }
+
+    /// The function with the index, imported functions have none.
+    pub fn function(&self, index: u32) -> Option<&Function> {
+        self.functions.iter().find(|f| f.index == index)
+    }
+
+    /// The function whose body contains the offset in the module.
+    pub fn function_at(&self, offset: usize) -> Option<&Function> {
+        self.functions.iter().find(|f| f.range.contains(&offset))
+    }
+}
+
+/// Rust symbols without the hash at the end.
+pub fn demangle(name: &str) -> String {
+    format!("{:#}", rustc_demangle::demangle(name))
+}
+
+/// A section of a module, `range` includes the id and size in front of the content.
+pub struct Section<'a> {
+    /// The name of custom sections.
+    pub name: Option<&'a str>,
+    pub range: Range<usize>,
+    /// The content of the section, after the name for custom sections.
+    pub content: &'a [u8],
+}
+
+pub fn sections(wasm: &[u8]) -> Result<Vec<Section<'_>>> {
+    if !wasm.starts_with(b"\0asm") || wasm.len() < HEADER_LEN {
+        anyhow::bail!("not a Wasm module");
+    }
+
+    let mut sections = Vec::new();
+    let mut pos = HEADER_LEN;
+    while pos < wasm.len() {
+        let start = pos;
+        let id = wasm[pos];
+        pos += 1;
+        let size = read_u32(wasm, &mut pos)? as usize;
+        let mut content = wasm
+            .get(pos..pos + size)
+            .ok_or_else(|| anyhow::anyhow!("section at byte {start} is truncated"))?;
+        pos += size;
+
+        let mut name = None;
+        if id == CUSTOM_SECTION {
+            let mut name_pos = 0;
+            let len = read_u32(content, &mut name_pos)? as usize;
+            name = content
+                .get(name_pos..name_pos + len)
+                .and_then(|name| std::str::from_utf8(name).ok());
+            if name.is_none() {
+                anyhow::bail!("invalid custom section at byte {start}");
+            }
+            content = &content[name_pos + len..];
+        }
+
+        sections.push(Section {
+            name,
+            range: start..pos,
+            content,
+        });
+    }
+
+    Ok(sections)
+}
+
+/// The content of the custom sections by name.
+pub fn custom_sections(wasm: &[u8]) -> Result<HashMap<&str, &[u8]>> {
+    Ok(sections(wasm)?
+        .into_iter()
+        .filter_map(|section| Some((section.name?, section.content)))
+        .collect())
+}
+
+/// Removes the custom sections whose name matches.
+pub fn remove_custom_sections(wasm: &[u8], remove: impl Fn(&str) -> bool) -> Result<Vec<u8>> {
+    let mut out = wasm[..HEADER_LEN].to_vec();
+    for section in sections(wasm)? {
+        if !section.name.is_some_and(&remove) {
+            out.extend_from_slice(&wasm[section.range]);
+        }
+    }
+    Ok(out)
+}
+
+/// Encodes a custom section with the given name and content.
+pub fn custom_section(name: &str, content: &[u8]) -> Vec<u8> {
+    let mut payload = encode_u32(name.len() as u32);
+    payload.extend_from_slice(name.as_bytes());
+    payload.extend_from_slice(content);
+
+    let mut section = vec![CUSTOM_SECTION];
+    section.extend(encode_u32(payload.len() as u32));
+    section.extend(payload);
+    section
+}
+
+/// Encodes a string like the Wasm format does, prefixed with its length.
+pub fn encode_string(s: &str) -> Vec<u8> {
+    let mut out = encode_u32(s.len() as u32);
+    out.extend_from_slice(s.as_bytes());
+    out
+}
+
+/// Decodes a string prefixed with its length.
+pub fn decode_string(bytes: &[u8]) -> Result<&str> {
+    let mut pos = 0;
+    let len = read_u32(bytes, &mut pos)? as usize;
+    bytes
+        .get(pos..pos + len)
+        .and_then(|s| std::str::from_utf8(s).ok())
+        .ok_or_else(|| anyhow::anyhow!("invalid string"))
+}
+
+/// Reads an unsigned LEB128 number.
+pub fn read_u32(bytes: &[u8], pos: &mut usize) -> Result<u32> {
+    let start = *pos;
+    let mut result: u32 = 0;
+    for shift in (0..35).step_by(7) {
+        let byte = *bytes
+            .get(*pos)
+            .ok_or_else(|| anyhow::anyhow!("unexpected end of module"))?;
+        *pos += 1;
+        result |= ((byte & 0x7f) as u32) << shift;
+        if byte & 0x80 == 0 {
+            return Ok(result);
+        }
+    }
+    anyhow::bail!("invalid number at byte {start}")
+}
+
+fn encode_u32(mut value: u32) -> Vec<u8> {
+    let mut out = Vec::new();
+    loop {
+        let byte = (value & 0x7f) as u8;
+        value >>= 7;
+        if value == 0 {
+            out.push(byte);
+            return out;
+        }
+        out.push(byte | 0x80);
+    }
+}
@@ -1,10 +1,183 @@
 use proc_macro::TokenStream;
-use quote::quote;
-use syn::{parse_macro_input, Ident, ItemFn};
+use proc_macro2::{Span, TokenStream as TokenStream2};
+use quote::{quote, quote_spanned};
+use syn::{
+    parse_macro_input, spanned::Spanned, Expr, GenericArgument, Ident, ItemFn, LitStr,
+    PathArguments, ReturnType, Type,
+};
+
+/// Options of the `#[activity(...)]` attribute.
+#[derive(Default)]
+struct ActivityOptions {
+    client_id: Option<Expr>,
+    auto_ready: bool,
+    panic_hook: bool,
+    coredump_endpoint: Option<Expr>,
+    log_level: Option<Ident>,
+    export: Option<LitStr>,
+}
+
+impl ActivityOptions {
+    fn parse_meta(&mut self, meta: syn::meta::ParseNestedMeta) -> syn::Result<()> {
+        let is_set = match meta.path.get_ident().map(Ident::to_string).as_deref() {
+            Some("client_id") => self.client_id.is_some(),
+            Some("auto_ready") => self.auto_ready,
+            Some("panic_hook") => self.panic_hook,
+            Some("coredump_endpoint") => self.coredump_endpoint.is_some(),
+            Some("log_level") => self.log_level.is_some(),
+            Some("export") => self.export.is_some(),
+            _ => false,
+        };
+        if is_set {
+            return Err(meta.error("duplicate activity option"));
+        }
+
+        if meta.path.is_ident("client_id") {
+            self.client_id = Some(meta.value()?.parse()?);
+        } else if meta.path.is_ident("auto_ready") {
+            self.auto_ready = true;
+        } else if meta.path.is_ident("panic_hook") {
+            self.panic_hook = true;
+        } else if meta.path.is_ident("coredump_endpoint") {
+            self.coredump_endpoint = Some(meta.value()?.parse()?);
+        } else if meta.path.is_ident("log_level") {
+            let level: LitStr = meta.value()?.parse()?;
+            let filter = match level.value().as_str() {
+                "off" => "Off",
+                "error" => "Error",
+                "warn" => "Warn",
+                "info" => "Info",
+                "debug" => "Debug",
+                "trace" => "Trace",
+                _ => {
+                    return Err(syn::Error::new_spanned(
+                        level,
+                        "expected one of \"off\", \"error\", \"warn\", \"info\", \"debug\" or \"trace\"",
+                    ))
+                }
+            };
+            self.log_level = Some(Ident::new(filter, level.span()));
+        } else if meta.path.is_ident("export") {
+            self.export = Some(meta.value()?.parse()?);
+        } else {
+            return Err(meta.error(
+                "unsupported activity option, expected `client_id`, `auto_ready`, `panic_hook`, `coredump_endpoint`, `log_level` or `export`",
+            ));
+        }
+
+        Ok(())
+    }
+}
+
+/// What the attributed function returns.
+enum Output {
+    Unit,
+    /// Holds the span of the return type.
+    Result(Span),
+}
+
+/// Checks that the function can be called by the generated glue and emits spanned errors
+/// instead of leaving them to wasm-bindgen or the type checker.
+fn validate_signature(input_fn: &ItemFn, options: &ActivityOptions) -> syn::Result<Output> {
+    let sig = &input_fn.sig;
+    let mut errors = Vec::new();
+
+    if sig.asyncness.is_none() {
+        errors.push(syn::Error::new_spanned(
+            sig.fn_token,
+            "the #[activity] function must be async",
+        ));
+    }
+
+    if !sig.generics.params.is_empty() || sig.generics.where_clause.is_some() {
+        errors.push(syn::Error::new_spanned(
+            &sig.generics,
+            "the #[activity] function can't be generic",
+        ));
+    }
+
+    let expected_inputs = usize::from(options.client_id.is_some());
+    if sig.inputs.len() != expected_inputs {
+        let message = if expected_inputs == 0 {
+            "the #[activity] function can't take parameters, add `client_id = ...` to receive the DiscordSDK"
+        } else {
+            "the #[activity] function must take exactly one parameter: `sdk: DiscordSDK`"
+        };
+
+        let err = match sig.inputs.iter().nth(expected_inputs) {
+            Some(extra) => syn::Error::new_spanned(extra, message),
+            None => syn::Error::new(sig.paren_token.span.join(), message),
+        };
+        errors.push(err);
+    }
+
+    if let Some(syn::FnArg::Receiver(receiver)) = sig.inputs.first() {
+        errors.push(syn::Error::new_spanned(
+            receiver,
+            "the #[activity] function can't take `self`",
+        ));
+    }
+
+    let output = match &sig.output {
+        ReturnType::Default => Some(Output::Unit),
+        ReturnType::Type(_, ty) => match &**ty {
+            Type::Tuple(tuple) if tuple.elems.is_empty() => Some(Output::Unit),
+            Type::Path(path) => path
+                .path
+                .segments
+                .last()
+                .filter(|segment| segment.ident == "Result")
+                .and_then(|segment| match &segment.arguments {
+                    PathArguments::AngleBracketed(args) => args.args.first(),
+                    _ => None,
+                })
+                .filter(|ok| matches!(ok, GenericArgument::Type(Type::Tuple(t)) if t.elems.is_empty()))
+                .map(|_| Output::Result(ty.span())),
+            _ => None,
+        },
+    };
+
+    if output.is_none() {
+        errors.push(syn::Error::new_spanned(
+            &sig.output,
+            "the #[activity] function must return `()` or `Result<(), E>` where `E: Into<activity::Error>`",
+        ));
+    }
+
+    match errors.into_iter().reduce(|mut acc, e| {
+        acc.combine(e);
+        acc
+    }) {
+        Some(err) => Err(err),
+        None => Ok(output.unwrap()),
+    }
+}
+
+pub fn expand_macro(attr: TokenStream, item: TokenStream) -> TokenStream {
+    let mut options = ActivityOptions::default();
+    let options_parser = syn::meta::parser(|meta| options.parse_meta(meta));
+    parse_macro_input!(attr with options_parser);
 
-pub fn expand_macro(_attr: TokenStream, item: TokenStream) -> TokenStream {
     let mut input_fn = parse_macro_input!(item as ItemFn);
 
+    if options.auto_ready && options.client_id.is_none() {
+        return syn::Error::new_spanned(
+            &input_fn.sig.ident,
+            "`auto_ready` requires a `client_id` to create the DiscordSDK with",
+        )
+        .to_compile_error()
+        .into();
+    }
+
+    let output = match validate_signature(&input_fn, &options) {
+        Ok(output) => output,
+        Err(e) => {
+            // still emit the function so that errors in its body are reported as well
+            let e = e.to_compile_error();
+            return quote! { #e #input_fn }.into();
+        }
+    };
+
     let input_fn_ident = Ident::new(
         &(input_fn.sig.ident.to_string() + "_start_glue"),
         input_fn.sig.ident.span(),
@@ -13,15 +186,83 @@ pub fn expand_macro(_attr: TokenStream, item: TokenStream) -> TokenStream {
     // rename the original attributed fn
     input_fn.sig.ident = input_fn_ident.clone();
 
+    let export = options
+        .export
+        .unwrap_or_else(|| LitStr::new("start", input_fn.sig.ident.span()));
+
+    let panic_hook = options
+        .panic_hook
+        .then(|| quote! { ::activity::install_panic_hook(); });
+
+    let coredump_hook = options
+        .coredump_endpoint
+        .map(|endpoint| quote! { ::activity::install_coredump_hook(#endpoint); });
+
+    let logger = options.log_level.map(|level| {
+        quote! { ::activity::init_logger(::activity::log::LevelFilter::#level); }
+    });
+
+    // when a client id is given, the SDK is created for the user and passed to their function
+    let (sdk, call): (TokenStream2, TokenStream2) = match options.client_id {
+        Some(client_id) => {
+            let ready = options
+                .auto_ready
+                .then(|| quote! { sdk.ready().await?; });
+
+            // the call is spanned to the parameter type so that a wrong one is reported there
+            let sdk_span = match input_fn.sig.inputs.first() {
+                Some(syn::FnArg::Typed(arg)) => arg.ty.span(),
+                _ => Span::call_site(),
+            };
+
+            (
+                quote! {
+                    let sdk = ::activity::DiscordSDK::new(#client_id)?;
+                    #ready
+                },
+                quote_spanned! {sdk_span=> #input_fn_ident(sdk).await },
+            )
+        }
+        None => (quote! {}, quote! { #input_fn_ident().await }),
+    };
+
+    let call = match output {
+        Output::Unit => quote! {
+            #call;
+            Ok(())
+        },
+        // the bound is spanned to the return type so that errors point at it
+        Output::Result(span) => {
+            let into_js = quote_spanned! {span=>
+                fn into_js<E: ::core::convert::Into<::activity::Error>>(
+                    e: E,
+                ) -> ::activity::wasm_bindgen::JsValue {
+                    e.into().into()
+                }
+            };
+
+            let map_err = quote_spanned! {span=> .map_err(into_js) };
+
+            quote! {
+                #into_js
+                #call #map_err
+            }
+        }
+    };
+
     // create a new "main" function that calls the original attributed function
     let wrapper_fn = quote! {
-        pub async fn #wrapper_fn_ident() -> Result<(), ::activity::wasm_bindgen::JsValue> {
-            #input_fn_ident().await
+        pub async fn #wrapper_fn_ident() -> ::core::result::Result<(), ::activity::wasm_bindgen::JsValue> {
+            #panic_hook
+            #coredump_hook
+            #logger
+            #sdk
+            #call
         }
     };
 
     let wasm_bindgen_code =
-        wasm_bindgen_macro_support::expand(TokenStream::new().into(), wrapper_fn)
+        wasm_bindgen_macro_support::expand(quote! { js_name = #export }, wrapper_fn)
             .expect("wasm_bindgen macro failed to expand");
 
     let output = quote! {
@@ -29,6 +270,8 @@ pub fn expand_macro(_attr: TokenStream, item: TokenStream) -> TokenStream {
 
         mod _activity_start {
             use ::activity::{wasm_bindgen, wasm_bindgen_futures};
+            #[allow(unused_imports)]
+            use super::*;
             use super::#input_fn_ident;
             #wasm_bindgen_code
         }
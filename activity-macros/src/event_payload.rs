@@ -0,0 +1,68 @@
+use proc_macro::TokenStream;
+use quote::quote;
+use syn::{
+    parse::{Parse, ParseStream},
+    parse_macro_input, DeriveInput, LitStr, Token, Type,
+};
+
+/// The contents of `#[event("EVENT_NAME", args = ArgsType)]`.
+struct EventAttr {
+    name: LitStr,
+    args: Option<Type>,
+}
+
+impl Parse for EventAttr {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let name = input.parse()?;
+
+        let mut args = None;
+        if input.parse::<Option<Token![,]>>()?.is_some() && !input.is_empty() {
+            let key: syn::Ident = input.parse()?;
+            if key != "args" {
+                return Err(syn::Error::new_spanned(key, "expected `args = ...`"));
+            }
+            input.parse::<Token![=]>()?;
+            args = Some(input.parse()?);
+            input.parse::<Option<Token![,]>>()?;
+        }
+
+        Ok(Self { name, args })
+    }
+}
+
+pub fn expand_derive(item: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(item as DeriveInput);
+
+    let Some(attr) = input.attrs.iter().find(|a| a.path().is_ident("event")) else {
+        return syn::Error::new_spanned(
+            &input.ident,
+            "missing event name, add #[event(\"EVENT_NAME\")]",
+        )
+        .to_compile_error()
+        .into();
+    };
+
+    let EventAttr { name, args } = match attr.parse_args() {
+        Ok(attr) => attr,
+        Err(e) => return e.to_compile_error().into(),
+    };
+    let args = args.map_or_else(|| quote!(()), |args| quote!(#args));
+
+    let ident = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+
+    let output = quote! {
+        impl #impl_generics ::activity::EventPayload for #ident #ty_generics #where_clause {
+            type Args = #args;
+
+            fn event_type() -> ::activity::EventType {
+                match <::activity::EventType as ::std::str::FromStr>::from_str(#name) {
+                    Ok(event_type) => event_type,
+                    Err(e) => match e {},
+                }
+            }
+        }
+    };
+
+    TokenStream::from(output)
+}
@@ -1,8 +1,14 @@
 use proc_macro::TokenStream;
 
 mod activity;
+mod event_payload;
 
 #[proc_macro_attribute]
 pub fn activity(attr: TokenStream, item: TokenStream) -> TokenStream {
     activity::expand_macro(attr, item)
 }
+
+#[proc_macro_derive(EventPayload, attributes(event))]
+pub fn event_payload(item: TokenStream) -> TokenStream {
+    event_payload::expand_derive(item)
+}
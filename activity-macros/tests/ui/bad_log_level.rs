@@ -0,0 +1,6 @@
+use activity::*;
+
+#[activity(log_level = "verbose")]
+pub async fn start() {}
+
+fn main() {}
@@ -0,0 +1,8 @@
+use activity::*;
+
+#[activity]
+pub async fn start() -> Option<()> {
+    None
+}
+
+fn main() {}
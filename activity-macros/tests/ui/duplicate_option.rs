@@ -0,0 +1,6 @@
+use activity::*;
+
+#[activity(client_id = "123", panic_hook, panic_hook)]
+pub async fn start(_sdk: DiscordSDK) {}
+
+fn main() {}
@@ -0,0 +1,6 @@
+use activity::*;
+
+#[activity]
+pub fn start() {}
+
+fn main() {}
@@ -0,0 +1,8 @@
+use activity::*;
+
+#[activity]
+pub async fn start(sdk: DiscordSDK) {
+    drop(sdk);
+}
+
+fn main() {}
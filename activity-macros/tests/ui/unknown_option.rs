@@ -0,0 +1,6 @@
+use activity::*;
+
+#[activity(client_id = "123", auto_start)]
+pub async fn start(_sdk: DiscordSDK) {}
+
+fn main() {}
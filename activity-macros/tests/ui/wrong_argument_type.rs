@@ -0,0 +1,8 @@
+use activity::*;
+
+#[activity(client_id = "123")]
+pub async fn start(sdk: String) {
+    drop(sdk);
+}
+
+fn main() {}
@@ -1,6 +1,8 @@
 pub use web_sys;
 
+mod commands;
 pub mod sdk;
+pub mod timers;
 
 #[macro_export]
 macro_rules! console_debug {
@@ -0,0 +1,111 @@
+use std::{
+    cell::{Cell, RefCell},
+    collections::BTreeMap,
+    future::Future,
+    pin::Pin,
+    rc::Rc,
+    task::{Context, Poll, Waker},
+};
+
+/// Cancels pending commands of a [`DiscordSDK`](crate::DiscordSDK) created with
+/// [`DiscordSDK::with_abort_handle`](crate::DiscordSDK::with_abort_handle).
+///
+/// Aborted calls return [`Error::Aborted`](crate::Error::Aborted). The Discord client isn't
+/// notified, so a command might still be executed after it was aborted.
+#[derive(Clone, Default)]
+pub struct AbortHandle {
+    inner: Rc<AbortInner>,
+}
+
+#[derive(Default)]
+struct AbortInner {
+    aborted: Cell<bool>,
+    /// The wakers of the pending [`Aborted`] futures by their key.
+    wakers: RefCell<BTreeMap<u64, Waker>>,
+    next_key: Cell<u64>,
+}
+
+impl AbortHandle {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Aborts all pending and future calls that use this handle.
+    pub fn abort(&self) {
+        self.inner.aborted.set(true);
+        for waker in self.inner.wakers.take().into_values() {
+            waker.wake();
+        }
+    }
+
+    pub fn is_aborted(&self) -> bool {
+        self.inner.aborted.get()
+    }
+
+    pub(crate) fn aborted(&self) -> Aborted<'_> {
+        Aborted {
+            handle: self,
+            key: None,
+        }
+    }
+}
+
+/// Resolves when the handle is aborted. Its waker is removed when it's dropped, so calls that
+/// complete don't leave it behind.
+pub(crate) struct Aborted<'a> {
+    handle: &'a AbortHandle,
+    key: Option<u64>,
+}
+
+impl Future for Aborted<'_> {
+    type Output = ();
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<()> {
+        let inner = &self.handle.inner;
+        if inner.aborted.get() {
+            return Poll::Ready(());
+        }
+
+        let key = *self.key.get_or_insert_with(|| {
+            let key = inner.next_key.get();
+            inner.next_key.set(key + 1);
+            key
+        });
+        inner.wakers.borrow_mut().insert(key, cx.waker().clone());
+        Poll::Pending
+    }
+}
+
+impl Drop for Aborted<'_> {
+    fn drop(&mut self) {
+        if let Some(key) = self.key {
+            self.handle.inner.wakers.borrow_mut().remove(&key);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn dropped_futures_remove_their_waker() {
+        let handle = AbortHandle::new();
+        let mut cx = Context::from_waker(Waker::noop());
+
+        let mut first = Box::pin(handle.aborted());
+        let mut second = Box::pin(handle.aborted());
+        assert!(first.as_mut().poll(&mut cx).is_pending());
+        assert!(first.as_mut().poll(&mut cx).is_pending());
+        assert!(second.as_mut().poll(&mut cx).is_pending());
+        assert_eq!(handle.inner.wakers.borrow().len(), 2);
+
+        drop(first);
+        assert_eq!(handle.inner.wakers.borrow().len(), 1);
+
+        handle.abort();
+        assert!(second.as_mut().poll(&mut cx).is_ready());
+        drop(second);
+        assert!(handle.inner.wakers.borrow().is_empty());
+    }
+}
@@ -0,0 +1,28 @@
+use serde::{de::DeserializeOwned, Serialize};
+
+/// A command that can be sent to the Discord client.
+///
+/// Implement this for commands that aren't covered by this crate yet and send them with
+/// [`DiscordSDK::send`](crate::DiscordSDK::send).
+pub trait Command {
+    /// The name of the command on the wire, e.g. `GET_CHANNEL`.
+    const NAME: &'static str;
+
+    type Args: Serialize;
+    type Response: DeserializeOwned;
+}
+
+macro_rules! command {
+    ($name:ident, $wire:literal, $args:ty, $res:ty) => {
+        pub struct $name;
+
+        impl $crate::Command for $name {
+            const NAME: &'static str = $wire;
+
+            type Args = $args;
+            type Response = $res;
+        }
+    };
+}
+
+pub(crate) use command;
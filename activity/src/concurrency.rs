@@ -0,0 +1,109 @@
+use std::{
+    cell::{Cell, RefCell},
+    collections::VecDeque,
+    future::Future,
+    rc::Rc,
+};
+
+use wasm_bindgen_futures::spawn_local;
+
+use crate::{abort::AbortHandle, errors::*, time::with_deadline};
+
+/// How [`DiscordSDK::subscribe_async`](crate::DiscordSDK::subscribe_async) runs a handler
+/// when a new event arrives while the previous one is still being handled.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Concurrency {
+    /// Events are queued and handled one after another, in order.
+    #[default]
+    Sequential,
+    /// The pending handler is cancelled in favor of the new event.
+    LatestWins,
+    /// Every event is handled right away.
+    Concurrent,
+}
+
+pub(crate) type ErrorSink = Rc<dyn Fn(Error)>;
+
+/// Runs an async handler for incoming events according to a [`Concurrency`] policy.
+pub(crate) struct AsyncHandler<T, F> {
+    inner: Rc<AsyncHandlerInner<T, F>>,
+}
+
+struct AsyncHandlerInner<T, F> {
+    handler: RefCell<F>,
+    concurrency: Concurrency,
+    error_sink: ErrorSink,
+    queue: RefCell<VecDeque<T>>,
+    running: Cell<bool>,
+    latest: RefCell<Option<AbortHandle>>,
+}
+
+impl<T, F, Fut> AsyncHandler<T, F>
+where
+    T: 'static,
+    F: FnMut(T) -> Fut + 'static,
+    Fut: Future<Output = Result<()>> + 'static,
+{
+    pub(crate) fn new(handler: F, concurrency: Concurrency, error_sink: ErrorSink) -> Self {
+        Self {
+            inner: Rc::new(AsyncHandlerInner {
+                handler: RefCell::new(handler),
+                concurrency,
+                error_sink,
+                queue: RefCell::new(VecDeque::new()),
+                running: Cell::new(false),
+                latest: RefCell::new(None),
+            }),
+        }
+    }
+
+    pub(crate) fn handle(&self, event: T) {
+        let inner = self.inner.clone();
+
+        match inner.concurrency {
+            Concurrency::Concurrent => {
+                let future = (inner.handler.borrow_mut())(event);
+                spawn_local(async move {
+                    if let Err(e) = future.await {
+                        (inner.error_sink)(e);
+                    }
+                });
+            }
+            Concurrency::LatestWins => {
+                let abort = AbortHandle::new();
+                if let Some(previous) = inner.latest.replace(Some(abort.clone())) {
+                    previous.abort();
+                }
+
+                let future = (inner.handler.borrow_mut())(event);
+                spawn_local(async move {
+                    match with_deadline(future, None, Some(&abort)).await {
+                        Err(_) if abort.is_aborted() => {}
+                        Err(e) => (inner.error_sink)(e),
+                        Ok(()) => {}
+                    }
+                });
+            }
+            Concurrency::Sequential => {
+                inner.queue.borrow_mut().push_back(event);
+                if inner.running.replace(true) {
+                    return;
+                }
+
+                spawn_local(async move {
+                    loop {
+                        let Some(event) = inner.queue.borrow_mut().pop_front() else {
+                            break;
+                        };
+
+                        let future = (inner.handler.borrow_mut())(event);
+                        if let Err(e) = future.await {
+                            (inner.error_sink)(e);
+                        }
+                    }
+                    inner.running.set(false);
+                });
+            }
+        }
+    }
+}
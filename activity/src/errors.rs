@@ -1,15 +1,91 @@
+use std::fmt::Display;
+
+use js_sys::{Object, Reflect};
 use wasm_bindgen::JsValue;
 
-pub struct Error {}
+pub type Result<T, E = Error> = std::result::Result<T, E>;
+
+#[derive(Debug, Clone)]
+pub enum Error {
+    /// The Discord client rejected a command.
+    Sdk(SdkError),
+    /// Any other JavaScript exception.
+    Js(JsValue),
+    /// The Discord client didn't reply in time.
+    Timeout,
+    /// The call was cancelled through an [`AbortHandle`](crate::AbortHandle).
+    Aborted,
+}
+
+impl Error {
+    /// The error code returned by the Discord client, if any.
+    pub fn code(&self) -> Option<i64> {
+        match self {
+            Error::Sdk(e) => Some(e.code),
+            _ => None,
+        }
+    }
+}
+
+impl Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Error::Sdk(e) => write!(f, "{} ({})", e.message, e.code),
+            Error::Js(v) => write!(f, "{:?}", v),
+            Error::Timeout => write!(f, "timed out waiting for the Discord client"),
+            Error::Aborted => write!(f, "aborted"),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+#[derive(Debug, Clone)]
+pub struct SdkError {
+    pub code: i64,
+    pub message: String,
+}
+
+impl SdkError {
+    pub const INVALID_PAYLOAD: i64 = 4000;
+    pub const RATELIMITED: i64 = 4002;
+}
 
 impl From<JsValue> for Error {
-    fn from(_value: JsValue) -> Self {
-        Error {}
+    fn from(value: JsValue) -> Self {
+        // Errors sent by the Discord client are plain objects with a code and message.
+        let code = Reflect::get(&value, &"code".into()).ok().and_then(|c| c.as_f64());
+        let message = Reflect::get(&value, &"message".into())
+            .ok()
+            .and_then(|m| m.as_string());
+
+        match (code, message) {
+            (Some(code), Some(message)) => Error::Sdk(SdkError {
+                code: code as i64,
+                message,
+            }),
+            _ => Error::Js(value),
+        }
+    }
+}
+
+impl From<serde_wasm_bindgen::Error> for Error {
+    fn from(value: serde_wasm_bindgen::Error) -> Self {
+        Error::Js(value.into())
     }
 }
 
 impl From<Error> for JsValue {
-    fn from(_value: Error) -> Self {
-        JsValue::NULL
+    fn from(value: Error) -> Self {
+        match value {
+            Error::Sdk(e) => {
+                let obj = Object::new();
+                let _ = Reflect::set(&obj, &"code".into(), &(e.code as f64).into());
+                let _ = Reflect::set(&obj, &"message".into(), &e.message.into());
+                obj.into()
+            }
+            Error::Js(v) => v,
+            e @ (Error::Timeout | Error::Aborted) => js_sys::Error::new(&e.to_string()).into(),
+        }
     }
 }
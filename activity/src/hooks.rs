@@ -0,0 +1,85 @@
+use activity_sys::{console_debug, console_error, console_log, console_warn};
+use log::{Level, LevelFilter, Log, Metadata, Record};
+use wasm_bindgen::prelude::*;
+
+/// Logs panics to the browser console instead of failing with an opaque `unreachable` trap.
+pub fn install_panic_hook() {
+    std::panic::set_hook(Box::new(|info| {
+        console_error!("{}", info);
+    }));
+}
+
+// The listeners only run JavaScript, the Rust code of a module that trapped can't be trusted to
+// run anymore.
+#[wasm_bindgen(inline_js = r#"
+export function install_coredump_hook(memory, endpoint) {
+  let uploaded = false;
+  const upload = (error) => {
+    if (uploaded || !(error instanceof WebAssembly.RuntimeError)) return;
+
+    // The rewritten module writes the coredump to the start of its memory when it traps.
+    const bytes = new Uint8Array(memory.buffer);
+    if (bytes[0] !== 0x00 || bytes[1] !== 0x61 || bytes[2] !== 0x73 || bytes[3] !== 0x6d) return;
+
+    uploaded = true;
+    fetch(endpoint, {
+      method: "POST",
+      headers: { "Content-Type": "application/wasm" },
+      body: bytes.slice(),
+    }).catch((e) => console.error("failed to upload the coredump", e));
+  };
+
+  addEventListener("error", (event) => upload(event.error));
+  addEventListener("unhandledrejection", (event) => upload(event.reason));
+}
+"#)]
+extern "C" {
+    #[wasm_bindgen(js_name = install_coredump_hook)]
+    fn install_coredump_hook_js(memory: JsValue, endpoint: &str);
+}
+
+/// Uploads the coredump to the endpoint with a `POST` request when the module traps.
+///
+/// Only modules built with `activity-build --coredump` write a coredump, the hook does nothing
+/// for others. Requests from activities go through Discord's proxy, so the endpoint is usually a
+/// `/.proxy/...` path of a URL mapping. Inspect the uploaded file with
+/// `activity-build coredump inspect`.
+pub fn install_coredump_hook(endpoint: &str) {
+    install_coredump_hook_js(wasm_bindgen::memory(), endpoint);
+}
+
+struct ConsoleLogger;
+
+impl Log for ConsoleLogger {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        metadata.level() <= log::max_level()
+    }
+
+    fn log(&self, record: &Record) {
+        if !self.enabled(record.metadata()) {
+            return;
+        }
+
+        match record.level() {
+            Level::Error => console_error!("[{}] {}", record.target(), record.args()),
+            Level::Warn => console_warn!("[{}] {}", record.target(), record.args()),
+            Level::Info => console_log!("[{}] {}", record.target(), record.args()),
+            Level::Debug | Level::Trace => {
+                console_debug!("[{}] {}", record.target(), record.args())
+            }
+        }
+    }
+
+    fn flush(&self) {}
+}
+
+static LOGGER: ConsoleLogger = ConsoleLogger;
+
+/// Sends records of the `log` crate to the browser console.
+///
+/// Does nothing if another logger has already been installed.
+pub fn init_logger(level: LevelFilter) {
+    if log::set_logger(&LOGGER).is_ok() {
+        log::set_max_level(level);
+    }
+}
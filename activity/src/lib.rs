@@ -1,8 +1,17 @@
+mod abort;
+mod commands;
+mod concurrency;
 mod errors;
+mod hooks;
+pub mod middleware;
+mod schema;
 mod sdk;
+mod time;
 mod types;
+mod version;
 
 pub use activity_sys;
+pub use log;
 pub use serde_wasm_bindgen;
 pub use wasm_bindgen;
 pub use wasm_bindgen_futures;
@@ -10,6 +19,35 @@ pub use wasm_bindgen_futures;
 pub use activity_macros::*;
 pub use activity_sys::console_log;
 
+pub use abort::*;
+pub use commands::*;
+pub use concurrency::Concurrency;
 pub use errors::*;
+pub use hooks::*;
+pub use schema::*;
 pub use sdk::*;
 pub use types::*;
+pub use version::*;
+
+/// Reads an environment variable at compile time, like [`env!`], with an error that explains how
+/// to set it when it's missing.
+///
+/// `activity-build` passes the variables of `.env`, `.env.<profile>` and the configuration to
+/// cargo, so they don't have to be exported in the shell.
+///
+/// ```ignore
+/// #[activity(client_id = build_env!("CLIENT_ID"))]
+/// ```
+#[macro_export]
+macro_rules! build_env {
+    ($name:literal) => {
+        ::core::env!(
+            $name,
+            ::core::concat!(
+                "the environment variable `",
+                $name,
+                "` is not set, add it to `.env` or export it before building"
+            )
+        )
+    };
+}
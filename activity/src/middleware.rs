@@ -0,0 +1,238 @@
+//! Middleware that wraps every command sent through [`DiscordSDK`](crate::DiscordSDK).
+//!
+//! Middleware is configured with [`DiscordSDKBuilder::middleware`](crate::DiscordSDKBuilder::middleware)
+//! and runs in the order it was added, the first one being the outermost.
+
+use std::{future::Future, pin::Pin, rc::Rc, time::Duration};
+
+use activity_sys::console_debug;
+use js_sys::{Object, Reflect};
+use wasm_bindgen::JsValue;
+
+use crate::{
+    errors::*,
+    time::{sleep, Stopwatch},
+};
+
+pub type LocalBoxFuture<'a, T> = Pin<Box<dyn Future<Output = T> + 'a>>;
+
+/// A command on its way to the Discord client.
+#[derive(Debug, Clone)]
+pub struct CommandRequest {
+    pub cmd: String,
+    pub args: JsValue,
+}
+
+pub trait CommandMiddleware {
+    /// Handles the request, usually by passing it on to `next` at some point.
+    fn call<'a>(
+        &'a self,
+        req: CommandRequest,
+        next: Next<'a>,
+    ) -> LocalBoxFuture<'a, Result<JsValue>>;
+}
+
+/// The rest of the middleware chain, ending with the Discord client.
+///
+/// It can be run more than once, e.g. to retry a command.
+#[derive(Clone, Copy)]
+pub struct Next<'a> {
+    pub(crate) sdk: &'a activity_sys::sdk::DiscordSDK,
+    pub(crate) middleware: &'a [Box<dyn CommandMiddleware>],
+}
+
+impl<'a> Next<'a> {
+    pub fn run(self, req: CommandRequest) -> LocalBoxFuture<'a, Result<JsValue>> {
+        match self.middleware.split_first() {
+            Some((first, rest)) => first.call(
+                req,
+                Next {
+                    sdk: self.sdk,
+                    middleware: rest,
+                },
+            ),
+            None => Box::pin(send_command(self.sdk, req)),
+        }
+    }
+}
+
+async fn send_command(sdk: &activity_sys::sdk::DiscordSDK, req: CommandRequest) -> Result<JsValue> {
+    let payload = Object::new();
+    Reflect::set(&payload, &"cmd".into(), &req.cmd.into())?;
+    Reflect::set(&payload, &"args".into(), &req.args)?;
+
+    let reply = sdk.send_command(payload.into()).await?;
+    Ok(Reflect::get(&reply, &"data".into())?)
+}
+
+/// Creates middleware from a closure.
+///
+/// This is handy for mocking commands or injecting faults in tests:
+///
+/// ```ignore
+/// let sdk = DiscordSDK::builder(client_id)
+///     .middleware(from_fn(|req, next| {
+///         Box::pin(async move {
+///             if req.cmd == "GET_CHANNEL" {
+///                 return Err(Error::Sdk(SdkError { code: 4005, message: "Invalid channel".into() }));
+///             }
+///             next.run(req).await
+///         })
+///     }))
+///     .build()?;
+/// ```
+pub fn from_fn<F>(f: F) -> FnMiddleware<F>
+where
+    F: for<'a> Fn(CommandRequest, Next<'a>) -> LocalBoxFuture<'a, Result<JsValue>>,
+{
+    FnMiddleware(f)
+}
+
+pub struct FnMiddleware<F>(F);
+
+impl<F> CommandMiddleware for FnMiddleware<F>
+where
+    F: for<'a> Fn(CommandRequest, Next<'a>) -> LocalBoxFuture<'a, Result<JsValue>>,
+{
+    fn call<'a>(
+        &'a self,
+        req: CommandRequest,
+        next: Next<'a>,
+    ) -> LocalBoxFuture<'a, Result<JsValue>> {
+        (self.0)(req, next)
+    }
+}
+
+/// Logs the arguments and response of every command to the console.
+///
+/// Add it conditionally, e.g. `if cfg!(debug_assertions)`, to keep it out of release builds.
+pub struct Logging;
+
+impl CommandMiddleware for Logging {
+    fn call<'a>(
+        &'a self,
+        req: CommandRequest,
+        next: Next<'a>,
+    ) -> LocalBoxFuture<'a, Result<JsValue>> {
+        Box::pin(async move {
+            let cmd = req.cmd.clone();
+            console_debug!("Sending command {}: {:?}", cmd, req.args);
+
+            let res = next.run(req).await;
+            match &res {
+                Ok(data) => console_debug!("Command {} succeeded: {:?}", cmd, data),
+                Err(e) => console_debug!("Command {} failed: {}", cmd, e),
+            }
+
+            res
+        })
+    }
+}
+
+/// Measures how long every command takes and reports it to a callback.
+pub struct Timing<F> {
+    report: F,
+}
+
+impl<F> Timing<F>
+where
+    F: Fn(&str, Duration, &Result<JsValue>),
+{
+    pub fn new(report: F) -> Self {
+        Self { report }
+    }
+}
+
+impl<F> CommandMiddleware for Timing<F>
+where
+    F: Fn(&str, Duration, &Result<JsValue>),
+{
+    fn call<'a>(
+        &'a self,
+        req: CommandRequest,
+        next: Next<'a>,
+    ) -> LocalBoxFuture<'a, Result<JsValue>> {
+        Box::pin(async move {
+            let cmd = req.cmd.clone();
+            let stopwatch = Stopwatch::start();
+
+            let res = next.run(req).await;
+            (self.report)(&cmd, stopwatch.elapsed(), &res);
+
+            res
+        })
+    }
+}
+
+/// Retries commands that failed with one of the given error codes, with exponential backoff.
+///
+/// By default only rate limited commands are retried.
+pub struct Retry {
+    max_retries: u32,
+    backoff: Duration,
+    max_backoff: Duration,
+    codes: Rc<[i64]>,
+}
+
+impl Retry {
+    pub fn new(max_retries: u32) -> Self {
+        Self {
+            max_retries,
+            backoff: Duration::from_millis(500),
+            max_backoff: Duration::from_secs(30),
+            codes: Rc::new([SdkError::RATELIMITED]),
+        }
+    }
+
+    /// The delay before the first retry, it's doubled for every following one.
+    pub fn backoff(mut self, backoff: Duration) -> Self {
+        self.backoff = backoff;
+        self
+    }
+
+    /// The longest delay between two retries, 30 seconds by default.
+    pub fn max_backoff(mut self, max_backoff: Duration) -> Self {
+        self.max_backoff = max_backoff;
+        self
+    }
+
+    /// The error codes that should be retried.
+    pub fn codes(mut self, codes: &[i64]) -> Self {
+        self.codes = codes.into();
+        self
+    }
+}
+
+impl CommandMiddleware for Retry {
+    fn call<'a>(
+        &'a self,
+        req: CommandRequest,
+        next: Next<'a>,
+    ) -> LocalBoxFuture<'a, Result<JsValue>> {
+        Box::pin(async move {
+            let mut backoff = self.backoff.min(self.max_backoff);
+            let mut attempt = 0;
+
+            loop {
+                match next.run(req.clone()).await {
+                    Err(e)
+                        if attempt < self.max_retries
+                            && e.code().is_some_and(|c| self.codes.contains(&c)) =>
+                    {
+                        console_debug!(
+                            "Command {} failed with {}, retrying in {:?}",
+                            req.cmd,
+                            e,
+                            backoff
+                        );
+
+                        sleep(backoff).await;
+                        backoff = backoff.saturating_mul(2).min(self.max_backoff);
+                        attempt += 1;
+                    }
+                    res => return res,
+                }
+            }
+        })
+    }
+}
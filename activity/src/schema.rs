@@ -0,0 +1,1117 @@
+// This file is generated by `cargo xtask codegen` from the Discord SDK 1.9.0, don't edit it.
+
+use std::{convert::Infallible, str::FromStr};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    commands::command,
+    types::{sdk_enum, EmptyRes, EventPayload, GuildSubscribeArgs, SubscribeArgs},
+};
+
+/// The version of the Discord SDK the commands and events of this crate are written for.
+pub const BINDINGS_SDK_VERSION: &str = "1.9.0";
+
+command!(
+    Authenticate,
+    "AUTHENTICATE",
+    AuthenticateArgs,
+    AuthenticateRes
+);
+command!(Authorize, "AUTHORIZE", AuthorizeArgs, AuthorizeRes);
+command!(CaptureLog, "CAPTURE_LOG", CaptureLogArgs, EmptyRes);
+command!(
+    EncourageHardwareAcceleration,
+    "ENCOURAGE_HW_ACCELERATION",
+    (),
+    EncourageHardwareAccelerationRes
+);
+command!(GetChannel, "GET_CHANNEL", GetChannelArgs, GetChannelRes);
+command!(
+    GetChannelPermissions,
+    "GET_CHANNEL_PERMISSIONS",
+    (),
+    GetChannelPermissionsRes
+);
+command!(
+    GetEntitlements,
+    "GET_ENTITLEMENTS_EMBEDDED",
+    (),
+    GetEntitlementsRes
+);
+command!(
+    GetPlatformBehaviors,
+    "GET_PLATFORM_BEHAVIORS",
+    (),
+    GetPlatformBehaviorsRes
+);
+command!(GetSkus, "GET_SKUS_EMBEDDED", (), GetSkusRes);
+command!(
+    OpenExternalLink,
+    "OPEN_EXTERNAL_LINK",
+    OpenExternalLinkArgs,
+    OpenExternalLinkRes
+);
+command!(OpenInviteDialog, "OPEN_INVITE_DIALOG", (), EmptyRes);
+command!(
+    OpenShareMomentDialog,
+    "OPEN_SHARE_MOMENT_DIALOG",
+    OpenShareMomentDialogArgs,
+    EmptyRes
+);
+command!(SetActivity, "SET_ACTIVITY", SetActivityArgs, SetActivityRes);
+command!(SetConfig, "SET_CONFIG", SetConfigArgs, SetConfigRes);
+command!(
+    SetOrientationLockState,
+    "SET_ORIENTATION_LOCK_STATE",
+    SetOrientationLockStateArgs,
+    EmptyRes
+);
+command!(ShareLink, "SHARE_LINK", ShareLinkArgs, ShareLinkRes);
+command!(
+    StartPurchase,
+    "START_PURCHASE",
+    StartPurchaseArgs,
+    StartPurchaseRes
+);
+command!(
+    UserSettingsGetLocale,
+    "USER_SETTINGS_GET_LOCALE",
+    (),
+    UserSettingsGetLocaleRes
+);
+command!(
+    InitiateImageUpload,
+    "INITIATE_IMAGE_UPLOAD",
+    (),
+    InitiateImageUploadRes
+);
+command!(
+    GetInstanceConnectedParticipants,
+    "GET_ACTIVITY_INSTANCE_CONNECTED_PARTICIPANTS",
+    (),
+    GetInstanceConnectedParticipantsRes
+);
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum EventType {
+    Ready,
+    VoiceStateUpdate,
+    SpeakingStart,
+    SpeakingStop,
+    ActivityLayoutModeUpdate,
+    OrientationUpdate,
+    CurrentUserUpdate,
+    CurrentGuildMemberUpdate,
+    EntitlementCreate,
+    ThermalStateUpdate,
+    ActivityInstanceParticipantsUpdate,
+    Error,
+    /// An event that isn't modeled by this crate yet.
+    Other(String),
+}
+
+impl EventType {
+    pub fn as_str(&self) -> &str {
+        match self {
+            EventType::Ready => "READY",
+            EventType::VoiceStateUpdate => "VOICE_STATE_UPDATE",
+            EventType::SpeakingStart => "SPEAKING_START",
+            EventType::SpeakingStop => "SPEAKING_STOP",
+            EventType::ActivityLayoutModeUpdate => "ACTIVITY_LAYOUT_MODE_UPDATE",
+            EventType::OrientationUpdate => "ORIENTATION_UPDATE",
+            EventType::CurrentUserUpdate => "CURRENT_USER_UPDATE",
+            EventType::CurrentGuildMemberUpdate => "CURRENT_GUILD_MEMBER_UPDATE",
+            EventType::EntitlementCreate => "ENTITLEMENT_CREATE",
+            EventType::ThermalStateUpdate => "THERMAL_STATE_UPDATE",
+            EventType::ActivityInstanceParticipantsUpdate => {
+                "ACTIVITY_INSTANCE_PARTICIPANTS_UPDATE"
+            }
+            EventType::Error => "ERROR",
+            EventType::Other(name) => name,
+        }
+    }
+}
+
+impl FromStr for EventType {
+    type Err = Infallible;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(match s {
+            "READY" => EventType::Ready,
+            "VOICE_STATE_UPDATE" => EventType::VoiceStateUpdate,
+            "SPEAKING_START" => EventType::SpeakingStart,
+            "SPEAKING_STOP" => EventType::SpeakingStop,
+            "ACTIVITY_LAYOUT_MODE_UPDATE" => EventType::ActivityLayoutModeUpdate,
+            "ORIENTATION_UPDATE" => EventType::OrientationUpdate,
+            "CURRENT_USER_UPDATE" => EventType::CurrentUserUpdate,
+            "CURRENT_GUILD_MEMBER_UPDATE" => EventType::CurrentGuildMemberUpdate,
+            "ENTITLEMENT_CREATE" => EventType::EntitlementCreate,
+            "THERMAL_STATE_UPDATE" => EventType::ThermalStateUpdate,
+            "ACTIVITY_INSTANCE_PARTICIPANTS_UPDATE" => {
+                EventType::ActivityInstanceParticipantsUpdate
+            }
+            "ERROR" => EventType::Error,
+            other => EventType::Other(other.to_string()),
+        })
+    }
+}
+
+impl EventPayload for ReadyEvent {
+    type Args = ();
+
+    fn event_type() -> EventType {
+        EventType::Ready
+    }
+}
+
+impl EventPayload for VoiceStateUpdateEvent {
+    type Args = SubscribeArgs;
+
+    fn event_type() -> EventType {
+        EventType::VoiceStateUpdate
+    }
+}
+
+impl EventPayload for SpeakingStartEvent {
+    type Args = SubscribeArgs;
+
+    fn event_type() -> EventType {
+        EventType::SpeakingStart
+    }
+}
+
+impl EventPayload for SpeakingStopEvent {
+    type Args = SubscribeArgs;
+
+    fn event_type() -> EventType {
+        EventType::SpeakingStop
+    }
+}
+
+impl EventPayload for ActivityLayoutModeUpdateEvent {
+    type Args = ();
+
+    fn event_type() -> EventType {
+        EventType::ActivityLayoutModeUpdate
+    }
+}
+
+impl EventPayload for OrientationUpdateEvent {
+    type Args = ();
+
+    fn event_type() -> EventType {
+        EventType::OrientationUpdate
+    }
+}
+
+impl EventPayload for CurrentUserUpdateEvent {
+    type Args = ();
+
+    fn event_type() -> EventType {
+        EventType::CurrentUserUpdate
+    }
+}
+
+impl EventPayload for CurrentGuildMemberUpdateEvent {
+    type Args = GuildSubscribeArgs;
+
+    fn event_type() -> EventType {
+        EventType::CurrentGuildMemberUpdate
+    }
+}
+
+impl EventPayload for EntitlementCreateEvent {
+    type Args = ();
+
+    fn event_type() -> EventType {
+        EventType::EntitlementCreate
+    }
+}
+
+impl EventPayload for ThermalStateUpdateEvent {
+    type Args = ();
+
+    fn event_type() -> EventType {
+        EventType::ThermalStateUpdate
+    }
+}
+
+impl EventPayload for ActivityInstanceParticipantsUpdateEvent {
+    type Args = ();
+
+    fn event_type() -> EventType {
+        EventType::ActivityInstanceParticipantsUpdate
+    }
+}
+
+impl EventPayload for ErrorEvent {
+    type Args = ();
+
+    fn event_type() -> EventType {
+        EventType::Error
+    }
+}
+
+pub type SetActivityRes = Activity;
+
+pub type StartPurchaseRes = Option<Vec<Entitlement>>;
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct VoiceStateUpdateEvent {
+    #[serde(flatten)]
+    pub user_voice_state: UserVoiceState,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct CurrentUserUpdateEvent {
+    #[serde(flatten)]
+    pub user: User,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct CurrentGuildMemberUpdateEvent {
+    #[serde(flatten)]
+    pub member: Member,
+}
+
+#[derive(Serialize, Deserialize, Debug, Default)]
+pub struct AuthenticateArgs {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub access_token: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct AuthenticateRes {
+    pub access_token: String,
+    pub user: AuthenticateResUser,
+    pub scopes: Vec<Scope>,
+    pub expires: String,
+    pub application: Application,
+}
+
+#[derive(Serialize, Deserialize, Debug, Default)]
+pub struct AuthorizeArgs {
+    pub client_id: String,
+    pub scope: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub response_type: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub code_challenge: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub state: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub prompt: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub code_challenge_method: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Default)]
+pub struct AuthorizeRes {
+    pub code: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, Default)]
+pub struct CaptureLogArgs {
+    pub level: String,
+    pub message: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, Default)]
+pub struct EncourageHardwareAccelerationRes {
+    pub enabled: bool,
+}
+
+#[derive(Serialize, Deserialize, Debug, Default)]
+pub struct GetChannelArgs {
+    pub channel_id: String,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct GetChannelRes {
+    pub id: String,
+    #[serde(rename = "type")]
+    pub kind: ChannelType,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub guild_id: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub topic: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub bitrate: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub user_limit: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub position: Option<i32>,
+    pub voice_states: Vec<UserVoiceState>,
+    pub messages: Vec<Message>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Default)]
+pub struct GetChannelPermissionsRes {
+    #[serde(with = "crate::types::big_int")]
+    pub permissions: u64,
+}
+
+#[derive(Serialize, Deserialize, Debug, Default)]
+pub struct GetEntitlementsRes {
+    pub entitlements: Vec<Entitlement>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Default)]
+pub struct GetPlatformBehaviorsRes {
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        rename = "iosKeyboardResizesView"
+    )]
+    pub ios_keyboard_resizes_view: Option<bool>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Default)]
+pub struct GetSkusRes {
+    pub skus: Vec<Sku>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Default)]
+pub struct OpenExternalLinkArgs {
+    pub url: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, Default)]
+pub struct OpenExternalLinkRes {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub opened: Option<bool>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Default)]
+pub struct OpenShareMomentDialogArgs {
+    #[serde(rename = "mediaUrl")]
+    pub media_url: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, Default)]
+pub struct SetActivityArgs {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub activity: Option<PartialActivity>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Default)]
+pub struct Activity {
+    pub name: String,
+    #[serde(rename = "type")]
+    pub kind: u8,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub url: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub created_at: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timestamps: Option<ActivityTimestamps>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub application_id: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub details: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub state: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub emoji: Option<Emoji>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub party: Option<ActivityParty>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub assets: Option<ActivityAssets>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub secrets: Option<ActivitySecrets>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub instance: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub flags: Option<u64>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Default)]
+pub struct SetConfigArgs {
+    pub use_interactive_pip: bool,
+}
+
+#[derive(Serialize, Deserialize, Debug, Default)]
+pub struct SetConfigRes {
+    pub use_interactive_pip: bool,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct SetOrientationLockStateArgs {
+    pub lock_state: OrientationLockStateType,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub picture_in_picture_lock_state: Option<OrientationLockStateType>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub grid_lock_state: Option<OrientationLockStateType>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Default)]
+pub struct ShareLinkArgs {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub referrer_id: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub custom_id: Option<String>,
+    pub message: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, Default)]
+pub struct ShareLinkRes {
+    pub success: bool,
+}
+
+#[derive(Serialize, Deserialize, Debug, Default)]
+pub struct StartPurchaseArgs {
+    pub sku_id: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pid: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct Entitlement {
+    pub id: String,
+    pub sku_id: String,
+    pub application_id: String,
+    pub user_id: String,
+    pub gift_code_flags: u64,
+    #[serde(rename = "type")]
+    pub kind: EntitlementType,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub gifter_user_id: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub branches: Option<Vec<String>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub starts_at: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ends_at: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub parent_id: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub consumed: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub deleted: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub gift_code_batch_id: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Default)]
+pub struct UserSettingsGetLocaleRes {
+    pub locale: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, Default)]
+pub struct InitiateImageUploadRes {
+    pub image_url: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, Default)]
+pub struct GetInstanceConnectedParticipantsRes {
+    pub participants: Vec<Participant>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct ReadyEvent {
+    pub v: u8,
+    pub config: ReadyEventConfig,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub user: Option<ReadyEventUser>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct UserVoiceState {
+    pub mute: bool,
+    pub nick: String,
+    pub user: User,
+    pub voice_state: VoiceState,
+    pub volume: u32,
+}
+
+#[derive(Serialize, Deserialize, Debug, Default)]
+pub struct SpeakingStartEvent {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub lobby_id: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub channel_id: Option<String>,
+    pub user_id: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, Default)]
+pub struct SpeakingStopEvent {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub lobby_id: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub channel_id: Option<String>,
+    pub user_id: String,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct ActivityLayoutModeUpdateEvent {
+    pub layout_mode: LayoutModeType,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct OrientationUpdateEvent {
+    pub screen_orientation: OrientationType,
+    #[serde(default, skip_serializing_if = "serde_json::Value::is_null")]
+    pub orientation: serde_json::Value,
+}
+
+#[derive(Serialize, Deserialize, Debug, Default)]
+pub struct User {
+    pub id: String,
+    pub username: String,
+    pub discriminator: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub global_name: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub avatar: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub avatar_decoration_data: Option<UserAvatarDecorationData>,
+    pub bot: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub flags: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub premium_type: Option<u8>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Default)]
+pub struct Member {
+    pub user_id: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub nick: Option<String>,
+    pub guild_id: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub avatar: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub avatar_decoration_data: Option<MemberAvatarDecorationData>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub color_string: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct EntitlementCreateEvent {
+    pub entitlement: Entitlement,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct ThermalStateUpdateEvent {
+    pub thermal_state: ThermalStateType,
+}
+
+#[derive(Serialize, Deserialize, Debug, Default)]
+pub struct ActivityInstanceParticipantsUpdateEvent {
+    pub participants: Vec<Participant>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Default)]
+pub struct ErrorEvent {
+    pub code: u64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub message: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Default)]
+pub struct AuthenticateResUser {
+    pub username: String,
+    pub discriminator: String,
+    pub id: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub avatar: Option<String>,
+    pub public_flags: u64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub global_name: Option<String>,
+}
+
+sdk_enum! {
+pub enum Scope {
+Identify = "identify",
+Email = "email",
+Connections = "connections",
+Guilds = "guilds",
+GuildsJoin = "guilds.join",
+GuildsMembersRead = "guilds.members.read",
+GuildsChannelsRead = "guilds.channels.read",
+GdmJoin = "gdm.join",
+Bot = "bot",
+Rpc = "rpc",
+RpcNotificationsRead = "rpc.notifications.read",
+RpcVoiceRead = "rpc.voice.read",
+RpcVoiceWrite = "rpc.voice.write",
+RpcVideoRead = "rpc.video.read",
+RpcVideoWrite = "rpc.video.write",
+RpcScreenshareRead = "rpc.screenshare.read",
+RpcScreenshareWrite = "rpc.screenshare.write",
+RpcActivitiesWrite = "rpc.activities.write",
+WebhookIncoming = "webhook.incoming",
+MessagesRead = "messages.read",
+ApplicationsBuildsUpload = "applications.builds.upload",
+ApplicationsBuildsRead = "applications.builds.read",
+ApplicationsCommands = "applications.commands",
+ApplicationsCommandsPermissionsUpdate = "applications.commands.permissions.update",
+ApplicationsCommandsUpdate = "applications.commands.update",
+ApplicationsStoreUpdate = "applications.store.update",
+ApplicationsEntitlements = "applications.entitlements",
+ActivitiesRead = "activities.read",
+ActivitiesWrite = "activities.write",
+RelationshipsRead = "relationships.read",
+RelationshipsWrite = "relationships.write",
+Voice = "voice",
+DmChannelsRead = "dm_channels.read",
+RoleConnectionsWrite = "role_connections.write",
+PresencesRead = "presences.read",
+PresencesWrite = "presences.write",
+Openid = "openid",
+DmChannelsMessagesRead = "dm_channels.messages.read",
+DmChannelsMessagesWrite = "dm_channels.messages.write",
+GatewayConnect = "gateway.connect",
+AccountGlobalNameUpdate = "account.global_name.update",
+PaymentSourcesCountryCode = "payment_sources.country_code",
+SdkSocialLayer = "sdk.social_layer",
+}
+}
+
+#[derive(Serialize, Deserialize, Debug, Default)]
+pub struct Application {
+    pub description: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub icon: Option<String>,
+    pub id: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rpc_origins: Option<Vec<String>>,
+    pub name: String,
+}
+
+sdk_enum! {
+pub enum ChannelType {
+Dm = 1,
+GroupDm = 3,
+GuildText = 0,
+GuildVoice = 2,
+GuildCategory = 4,
+GuildAnnouncement = 5,
+GuildStore = 6,
+AnnouncementThread = 10,
+PublicThread = 11,
+PrivateThread = 12,
+GuildStageVoice = 13,
+GuildDirectory = 14,
+GuildForum = 15,
+}
+}
+
+#[derive(Serialize, Deserialize, Debug, Default)]
+pub struct Message {
+    pub id: String,
+    pub channel_id: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub guild_id: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub author: Option<User>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub member: Option<GuildMember>,
+    pub content: String,
+    pub timestamp: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub edited_timestamp: Option<String>,
+    pub tts: bool,
+    pub mention_everyone: bool,
+    pub mentions: Vec<User>,
+    pub mention_roles: Vec<String>,
+    pub mention_channels: Vec<ChannelMention>,
+    pub attachments: Vec<Attachment>,
+    pub embeds: Vec<Embed>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reactions: Option<Vec<Reaction>>,
+    #[serde(default, skip_serializing_if = "serde_json::Value::is_null")]
+    pub nonce: serde_json::Value,
+    pub pinned: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub webhook_id: Option<String>,
+    #[serde(rename = "type")]
+    pub kind: u8,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub activity: Option<MessageActivity>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub application: Option<MessageApplication>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub message_reference: Option<MessageReference>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub flags: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub stickers: Option<Vec<serde_json::Value>>,
+    #[serde(default, skip_serializing_if = "serde_json::Value::is_null")]
+    pub referenced_message: serde_json::Value,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct Sku {
+    pub id: String,
+    pub name: String,
+    #[serde(rename = "type")]
+    pub kind: SkuType,
+    pub price: SkuPrice,
+    pub application_id: String,
+    pub flags: u64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub release_date: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Default)]
+pub struct PartialActivity {
+    #[serde(default, skip_serializing_if = "Option::is_none", rename = "type")]
+    pub kind: Option<u8>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timestamps: Option<ActivityTimestamps>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub details: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub state: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub party: Option<ActivityParty>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub assets: Option<ActivityAssets>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub secrets: Option<ActivitySecrets>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub instance: Option<bool>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Default)]
+pub struct ActivityTimestamps {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub start: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub end: Option<u64>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Default)]
+pub struct Emoji {
+    pub id: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub roles: Option<Vec<String>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub user: Option<User>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub require_colons: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub managed: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub animated: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub available: Option<bool>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Default)]
+pub struct ActivityParty {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub id: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub size: Option<Vec<u32>>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Default)]
+pub struct ActivityAssets {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub large_image: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub large_text: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub small_image: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub small_text: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Default)]
+pub struct ActivitySecrets {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub join: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub r#match: Option<String>,
+}
+
+sdk_enum! {
+pub enum OrientationLockStateType {
+Unlocked = 1,
+Portrait = 2,
+Landscape = 3,
+}
+}
+
+sdk_enum! {
+pub enum EntitlementType {
+Purchase = 1,
+PremiumSubscription = 2,
+DeveloperGift = 3,
+TestModePurchase = 4,
+FreePurchase = 5,
+UserGift = 6,
+PremiumPurchase = 7,
+}
+}
+
+#[derive(Serialize, Deserialize, Debug, Default)]
+pub struct Participant {
+    pub id: String,
+    pub username: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub global_name: Option<String>,
+    pub discriminator: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub avatar: Option<String>,
+    pub flags: u64,
+    pub bot: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub avatar_decoration_data: Option<ParticipantAvatarDecorationData>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub premium_type: Option<u8>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub nickname: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Default)]
+pub struct ReadyEventConfig {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cdn_host: Option<String>,
+    pub api_endpoint: String,
+    pub environment: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, Default)]
+pub struct ReadyEventUser {
+    pub id: String,
+    pub username: String,
+    pub discriminator: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub avatar: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Default)]
+pub struct VoiceState {
+    pub mute: bool,
+    pub deaf: bool,
+    pub self_mute: bool,
+    pub self_deaf: bool,
+    pub suppress: bool,
+}
+
+sdk_enum! {
+pub enum LayoutModeType {
+Focused = 0,
+Pip = 1,
+Grid = 2,
+}
+}
+
+sdk_enum! {
+pub enum OrientationType {
+Portrait = 0,
+Landscape = 1,
+}
+}
+
+#[derive(Serialize, Deserialize, Debug, Default)]
+pub struct UserAvatarDecorationData {
+    pub asset: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sku_id: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Default)]
+pub struct MemberAvatarDecorationData {
+    pub asset: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sku_id: Option<String>,
+}
+
+sdk_enum! {
+pub enum ThermalStateType {
+Nominal = 0,
+Fair = 1,
+Serious = 2,
+Critical = 3,
+}
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct GuildMember {
+    pub user: User,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub nick: Option<String>,
+    pub roles: Vec<String>,
+    pub joined_at: String,
+    pub deaf: bool,
+    pub mute: bool,
+}
+
+#[derive(Serialize, Deserialize, Debug, Default)]
+pub struct ChannelMention {
+    pub id: String,
+    pub guild_id: String,
+    #[serde(rename = "type")]
+    pub kind: u8,
+    pub name: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, Default)]
+pub struct Attachment {
+    pub id: String,
+    pub filename: String,
+    pub size: u64,
+    pub url: String,
+    pub proxy_url: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub height: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub width: Option<u32>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Default)]
+pub struct Embed {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none", rename = "type")]
+    pub kind: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub url: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timestamp: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub color: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub footer: Option<EmbedFooter>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub image: Option<Image>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub thumbnail: Option<Image>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub video: Option<Video>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub provider: Option<EmbedProvider>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub author: Option<EmbedAuthor>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fields: Option<Vec<EmbedField>>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct Reaction {
+    pub count: u32,
+    pub me: bool,
+    pub emoji: Emoji,
+}
+
+#[derive(Serialize, Deserialize, Debug, Default)]
+pub struct MessageActivity {
+    #[serde(rename = "type")]
+    pub kind: u8,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub party_id: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Default)]
+pub struct MessageApplication {
+    pub id: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cover_image: Option<String>,
+    pub description: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub icon: Option<String>,
+    pub name: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, Default)]
+pub struct MessageReference {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub message_id: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub channel_id: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub guild_id: Option<String>,
+}
+
+sdk_enum! {
+pub enum SkuType {
+Application = 1,
+Dlc = 2,
+Consumable = 3,
+Bundle = 4,
+Subscription = 5,
+}
+}
+
+#[derive(Serialize, Deserialize, Debug, Default)]
+pub struct SkuPrice {
+    pub amount: u64,
+    pub currency: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, Default)]
+pub struct ParticipantAvatarDecorationData {
+    pub asset: String,
+    #[serde(default, skip_serializing_if = "Option::is_none", rename = "skuId")]
+    pub sku_id: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Default)]
+pub struct EmbedFooter {
+    pub text: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub icon_url: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub proxy_icon_url: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Default)]
+pub struct Image {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub url: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub proxy_url: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub height: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub width: Option<u32>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Default)]
+pub struct Video {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub url: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub height: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub width: Option<u32>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Default)]
+pub struct EmbedProvider {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub url: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Default)]
+pub struct EmbedAuthor {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub url: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub icon_url: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub proxy_icon_url: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Default)]
+pub struct EmbedField {
+    pub name: String,
+    pub value: String,
+    pub inline: bool,
+}
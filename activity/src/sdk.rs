@@ -1,23 +1,64 @@
-use std::sync::Arc;
-
-use activity_sys::console_debug;
-use serde::de::DeserializeOwned;
+use std::{future::Future, rc::Rc, time::Duration};
+
+use activity_sys::{
+    console_debug, console_error,
+    web_sys::{EventTarget, MessageEvent},
+};
+use js_sys::{Array, Object, Reflect};
+use serde::{de::DeserializeOwned, Serialize};
 use wasm_bindgen::prelude::*;
-
+use wasm_bindgen_futures::spawn_local;
+
+use crate::abort::AbortHandle;
+use crate::commands::*;
+use crate::concurrency::{AsyncHandler, Concurrency, ErrorSink};
+use crate::errors::*;
+use crate::middleware::{CommandMiddleware, CommandRequest, Next};
+use crate::schema::*;
+use crate::time::with_deadline;
 use crate::types::*;
 
 #[derive(Clone)]
 pub struct DiscordSDK {
-    internal: Arc<activity_sys::sdk::DiscordSDK>,
+    internal: Rc<activity_sys::sdk::DiscordSDK>,
+    middleware: Rc<[Box<dyn CommandMiddleware>]>,
+    timeout: Option<Duration>,
+    abort: Option<AbortHandle>,
+    error_sink: ErrorSink,
 }
 
 impl DiscordSDK {
-    pub fn new(client_id: &str) -> Result<Self, JsValue> {
-        let internal = activity_sys::sdk::DiscordSDK::new(client_id)?;
+    pub fn new(client_id: &str) -> Result<Self> {
+        Self::builder(client_id).build()
+    }
 
-        Ok(Self {
-            internal: Arc::new(internal),
-        })
+    pub fn builder(client_id: &str) -> DiscordSDKBuilder {
+        DiscordSDKBuilder {
+            client_id: client_id.to_string(),
+            middleware: Vec::new(),
+            timeout: None,
+            error_sink: None,
+        }
+    }
+
+    /// Returns a copy of the SDK that uses the given timeout for `ready()` and all commands.
+    pub fn with_timeout(&self, timeout: Option<Duration>) -> Self {
+        Self {
+            timeout,
+            ..self.clone()
+        }
+    }
+
+    /// Returns a copy of the SDK whose pending calls can be cancelled with the given handle.
+    pub fn with_abort_handle(&self, abort: &AbortHandle) -> Self {
+        Self {
+            abort: Some(abort.clone()),
+            ..self.clone()
+        }
+    }
+
+    async fn deadline<T>(&self, future: impl Future<Output = Result<T>>) -> Result<T> {
+        with_deadline(future, self.timeout, self.abort.as_ref()).await
     }
 
     pub fn client_id(&self) -> String {
@@ -57,168 +98,340 @@ impl DiscordSDK {
             .expect("Failed to deserialize SdkConfiguration")
     }
 
-    pub async fn ready(&self) -> Result<(), JsValue> {
-        self.internal.ready().await
+    pub async fn ready(&self) -> Result<()> {
+        self.deadline(async { Ok(self.internal.ready().await?) })
+            .await
     }
 
-    pub async fn subscribe<F: 'static, T>(
+    /// Subscribes to an event, errors of the handler are logged to the console.
+    pub async fn subscribe<F, T>(
         &self,
         mut f: F,
-        args: SubscribeArgs,
-    ) -> Result<EventSubscription, JsValue>
+        args: T::Args,
+    ) -> Result<EventSubscription>
     where
         T: DeserializeOwned + EventPayload,
-        F: FnMut(T) -> Result<(), JsValue>,
+        F: FnMut(T) -> Result<()> + 'static,
     {
-        let closure = Closure::new(move |v: JsValue| {
+        let args_value = serde_wasm_bindgen::to_value(&args.with_defaults(self))?;
+
+        self.subscribe_js(T::event_type(), args_value, move |v| {
             let event: T = serde_wasm_bindgen::from_value(v)?;
-            f(event)
-        });
+            Ok(f(event)?)
+        })
+        .await
+    }
+
+    /// Subscribes to an event with an async handler.
+    ///
+    /// Handler errors are passed to the error sink configured with [`DiscordSDKBuilder::on_error`].
+    pub async fn subscribe_async<F, T, Fut>(
+        &self,
+        f: F,
+        args: T::Args,
+        concurrency: Concurrency,
+    ) -> Result<EventSubscription>
+    where
+        T: DeserializeOwned + EventPayload + 'static,
+        F: FnMut(T) -> Fut + 'static,
+        Fut: Future<Output = Result<()>> + 'static,
+    {
+        let handler = AsyncHandler::new(f, concurrency, self.error_sink.clone());
+
+        self.subscribe(
+            move |event: T| {
+                handler.handle(event);
+                Ok(())
+            },
+            args,
+        )
+        .await
+    }
 
+    /// Subscribes to an event by its name, including events this crate doesn't model yet.
+    pub async fn subscribe_raw<F, A>(
+        &self,
+        event: &str,
+        args: A,
+        mut f: F,
+    ) -> Result<EventSubscription>
+    where
+        A: Serialize,
+        F: FnMut(serde_json::Value) -> Result<()> + 'static,
+    {
         let args_value = serde_wasm_bindgen::to_value(&args)?;
+        let Ok(event_type) = event.parse();
 
-        let event_type = T::event_type();
+        self.subscribe_js(event_type, args_value, move |v| {
+            let event: serde_json::Value = serde_wasm_bindgen::from_value(v)?;
+            Ok(f(event)?)
+        })
+        .await
+    }
+
+    async fn subscribe_js<F>(
+        &self,
+        event_type: EventType,
+        args: JsValue,
+        mut f: F,
+    ) -> Result<EventSubscription>
+    where
+        F: FnMut(JsValue) -> Result<(), JsValue> + 'static,
+    {
+        // The bundled SDK drops dispatches for events it doesn't know, so we have to
+        // subscribe to them and listen for their messages ourselves.
+        let listener = if let EventType::Other(event) = &event_type {
+            let source = self.internal.source();
+            let event = event.clone();
+
+            let closure = Closure::new(move |msg: MessageEvent| {
+                let from_source = msg
+                    .source()
+                    .is_some_and(|s| Object::is(&s, &source));
+                if !from_source {
+                    return;
+                }
+
+                if let Some(data) = dispatch_data(&msg.data(), &event) {
+                    if let Err(e) = f(data) {
+                        console_error!("Event handler for {} failed: {:?}", event, e);
+                    }
+                }
+            });
+
+            let target: EventTarget = js_sys::global().unchecked_into();
+            target.add_event_listener_with_callback("message", closure.as_ref().unchecked_ref())?;
+
+            let listener = Listener::Dispatch {
+                closure,
+                args: args.clone(),
+            };
+
+            self.deadline(async {
+                Ok(self
+                    .internal
+                    .send_command(subscription_payload("SUBSCRIBE", &event_type, &args)?)
+                    .await?)
+            })
+            .await?;
+
+            listener
+        } else {
+            let closure = Closure::new(f);
 
-        self.internal
-            .subscribe(event_type.as_str(), &closure, args_value)
+            self.deadline(async {
+                Ok(self
+                    .internal
+                    .subscribe(event_type.as_str(), &closure, args)
+                    .await?)
+            })
             .await?;
 
+            Listener::Sdk { _closure: closure }
+        };
+
         Ok(EventSubscription {
             sdk: self.clone(),
-            event_type: event_type,
-            _closure: closure,
+            event_type,
+            listener,
         })
     }
 
-    pub async fn unsubscribe(&self, event: &str) -> Result<(), JsValue> {
-        self.internal.unsubscribe(event).await
+    pub async fn unsubscribe(&self, event: &str) -> Result<()> {
+        self.deadline(async { Ok(self.internal.unsubscribe(event).await?) })
+            .await
     }
 
     pub fn unsubscribe_nowait(&self, event: &str) {
         self.internal.unsubscribe_nowait(event)
     }
 
-    pub async fn authenticate(&self, args: AuthenticateArgs) -> Result<AuthenticateRes, JsValue> {
+    /// Sends a command to the Discord client and deserializes its response.
+    pub async fn send<C: Command>(&self, args: C::Args) -> Result<C::Response> {
         let args_value = serde_wasm_bindgen::to_value(&args)?;
 
-        let res = self.internal.commands().authenticate(args_value).await?;
+        let res = self.send_raw(C::NAME, args_value).await?;
         Ok(serde_wasm_bindgen::from_value(res)?)
     }
 
-    pub async fn authorize(&self, args: AuthorizeArgs) -> Result<AuthorizeRes, JsValue> {
-        let args_value = serde_wasm_bindgen::to_value(&args)?;
-
-        let res = self.internal.commands().authorize(args_value).await?;
-        Ok(serde_wasm_bindgen::from_value(res)?)
+    /// Sends a command by its wire name through the middleware and returns the raw `data` of the response.
+    pub async fn send_raw(&self, cmd: &str, args: JsValue) -> Result<JsValue> {
+        let next = Next {
+            sdk: &self.internal,
+            middleware: &self.middleware,
+        };
+
+        self.deadline(next.run(CommandRequest {
+            cmd: cmd.to_string(),
+            args,
+        }))
+        .await
     }
 
-    pub async fn capture_log(&self, args: CaputeLogArgs) -> Result<(), JsValue> {
-        let args_value = serde_wasm_bindgen::to_value(&args)?;
+    pub async fn authenticate(&self, args: AuthenticateArgs) -> Result<AuthenticateRes> {
+        self.send::<Authenticate>(args).await
+    }
 
-        self.internal.commands().capture_log(args_value).await
+    pub async fn authorize(&self, args: AuthorizeArgs) -> Result<AuthorizeRes> {
+        self.send::<Authorize>(args).await
     }
 
-    pub async fn encourage_hardware_acceleration(&self) -> Result<(), JsValue> {
-        self.internal
-            .commands()
-            .encourage_hardware_acceleration()
-            .await
+    pub async fn capture_log(&self, args: CaptureLogArgs) -> Result<()> {
+        self.send::<CaptureLog>(args).await?;
+        Ok(())
     }
 
-    pub async fn get_channel(&self, args: GetChannelArgs) -> Result<GetChannelRes, JsValue> {
-        let args_value = serde_wasm_bindgen::to_value(&args)?;
+    pub async fn encourage_hardware_acceleration(&self) -> Result<()> {
+        self.send::<EncourageHardwareAcceleration>(()).await?;
+        Ok(())
+    }
 
-        let res = self.internal.commands().get_channel(args_value).await?;
-        Ok(serde_wasm_bindgen::from_value(res)?)
+    pub async fn get_channel(&self, args: GetChannelArgs) -> Result<GetChannelRes> {
+        self.send::<GetChannel>(args).await
     }
 
-    pub async fn get_channel_permissions(&self) -> Result<GetChannelPermissionsRes, JsValue> {
-        let res = self.internal.commands().get_channel_permissions().await?;
-        Ok(serde_wasm_bindgen::from_value(res)?)
+    pub async fn get_channel_permissions(&self) -> Result<GetChannelPermissionsRes> {
+        self.send::<GetChannelPermissions>(()).await
     }
 
     pub async fn get_instance_connected_participants(
         &self,
-    ) -> Result<GetInstanceConnectedParticipantsRes, JsValue> {
-        let res = self
-            .internal
-            .commands()
-            .get_instance_connected_participants()
-            .await?;
-        Ok(serde_wasm_bindgen::from_value(res)?)
+    ) -> Result<GetInstanceConnectedParticipantsRes> {
+        self.send::<GetInstanceConnectedParticipants>(()).await
     }
 
-    pub async fn get_platform_behaviors(&self) -> Result<GetPlatformBehaviorsRes, JsValue> {
-        let res = self.internal.commands().get_platform_behaviors().await?;
-        Ok(serde_wasm_bindgen::from_value(res)?)
+    pub async fn get_platform_behaviors(&self) -> Result<GetPlatformBehaviorsRes> {
+        self.send::<GetPlatformBehaviors>(()).await
     }
 
-    pub async fn initiate_image_upload(&self) -> Result<InitiateImageUploadRes, JsValue> {
-        let res = self.internal.commands().initiate_image_upload().await?;
-        Ok(serde_wasm_bindgen::from_value(res)?)
+    pub async fn initiate_image_upload(&self) -> Result<InitiateImageUploadRes> {
+        self.send::<InitiateImageUpload>(()).await
     }
 
-    pub async fn open_external_link(&self, args: OpenExternalLinkArgs) -> Result<OpenExternalLinkRes, JsValue> {
-        let args_value = serde_wasm_bindgen::to_value(&args)?;
-
-        let res = self.internal
-            .commands()
-            .open_external_link(args_value)
-            .await?;
-
-        Ok(serde_wasm_bindgen::from_value(res)?)
+    pub async fn open_external_link(
+        &self,
+        args: OpenExternalLinkArgs,
+    ) -> Result<OpenExternalLinkRes> {
+        self.send::<OpenExternalLink>(args).await
     }
 
-    pub async fn open_invite_dialog(&self) -> Result<(), JsValue> {
-        self.internal.commands().open_invite_dialog().await
+    pub async fn open_invite_dialog(&self) -> Result<()> {
+        self.send::<OpenInviteDialog>(()).await?;
+        Ok(())
     }
 
     pub async fn open_share_moment_dialog(
         &self,
         args: OpenShareMomentDialogArgs,
-    ) -> Result<(), JsValue> {
-        let args_value = serde_wasm_bindgen::to_value(&args)?;
-
-        self.internal
-            .commands()
-            .open_share_moment_dialog(args_value)
-            .await
+    ) -> Result<()> {
+        self.send::<OpenShareMomentDialog>(args).await?;
+        Ok(())
     }
 
-    pub async fn set_activity(&self, args: SetActivityArgs) -> Result<(), JsValue> {
-        let args_value = serde_wasm_bindgen::to_value(&args)?;
-
-        self.internal.commands().set_activity(args_value).await
+    pub async fn set_activity(&self, args: SetActivityArgs) -> Result<()> {
+        self.send::<SetActivity>(args).await?;
+        Ok(())
     }
 
-    pub async fn set_config(&self, args: SetConfigArgs) -> Result<(), JsValue> {
-        let args_value = serde_wasm_bindgen::to_value(&args)?;
-
-        self.internal.commands().set_config(args_value).await
+    pub async fn set_config(&self, args: SetConfigArgs) -> Result<()> {
+        self.send::<SetConfig>(args).await?;
+        Ok(())
     }
 
     pub async fn set_orientation_lock_state(
         &self,
         args: SetOrientationLockStateArgs,
-    ) -> Result<(), JsValue> {
+    ) -> Result<()> {
         let args_value = serde_wasm_bindgen::to_value(&args)?;
 
-        self.internal
-            .commands()
-            .set_orientation_lock_state(args_value)
+        match self
+            .send_raw(SetOrientationLockState::NAME, args_value.clone())
             .await
+        {
+            // Older clients don't know about grid_lock_state and reject the whole payload.
+            Err(e) if e.code() == Some(SdkError::INVALID_PAYLOAD) => {
+                Reflect::delete_property(args_value.unchecked_ref(), &"grid_lock_state".into())?;
+                self.send_raw(SetOrientationLockState::NAME, args_value)
+                    .await?;
+            }
+            res => {
+                res?;
+            }
+        }
+
+        Ok(())
     }
 
-    pub async fn user_settings_get_locale(&self) -> Result<UserSettingsGetLocaleRes, JsValue> {
-        let res = self.internal.commands().user_settings_get_locale().await?;
-        Ok(serde_wasm_bindgen::from_value(res)?)
+    pub async fn user_settings_get_locale(&self) -> Result<UserSettingsGetLocaleRes> {
+        self.send::<UserSettingsGetLocale>(()).await
+    }
+}
+
+pub struct DiscordSDKBuilder {
+    client_id: String,
+    middleware: Vec<Box<dyn CommandMiddleware>>,
+    timeout: Option<Duration>,
+    error_sink: Option<ErrorSink>,
+}
+
+impl DiscordSDKBuilder {
+    /// Adds middleware that wraps every command, see [`middleware`](crate::middleware).
+    pub fn middleware<M>(mut self, middleware: M) -> Self
+    where
+        M: CommandMiddleware + 'static,
+    {
+        self.middleware.push(Box::new(middleware));
+        self
+    }
+
+    /// The default timeout for `ready()` and all commands, by default there is none.
+    ///
+    /// Keep in mind that some commands like `authorize` wait for the user to respond.
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// Receives errors of async event handlers, by default they are logged to the console.
+    pub fn on_error<F>(mut self, f: F) -> Self
+    where
+        F: Fn(Error) + 'static,
+    {
+        self.error_sink = Some(Rc::new(f));
+        self
+    }
+
+    pub fn build(self) -> Result<DiscordSDK> {
+        crate::version::warn_if_sdk_incompatible();
+
+        let internal = activity_sys::sdk::DiscordSDK::new(&self.client_id)?;
+
+        Ok(DiscordSDK {
+            internal: Rc::new(internal),
+            middleware: self.middleware.into(),
+            timeout: self.timeout,
+            abort: None,
+            error_sink: self.error_sink.unwrap_or_else(|| {
+                Rc::new(|e| console_error!("Event handler failed: {}", e))
+            }),
+        })
     }
 }
 
 pub struct EventSubscription {
     sdk: DiscordSDK,
     event_type: EventType,
-    _closure: Closure<dyn FnMut(JsValue) -> Result<(), JsValue>>,
+    listener: Listener,
+}
+
+enum Listener {
+    Sdk {
+        _closure: Closure<dyn FnMut(JsValue) -> Result<(), JsValue>>,
+    },
+    Dispatch {
+        closure: Closure<dyn FnMut(MessageEvent)>,
+        args: JsValue,
+    },
 }
 
 impl Drop for EventSubscription {
@@ -227,6 +440,56 @@ impl Drop for EventSubscription {
             "EventSubscription dropped, unsubscribing from event: {}",
             self.event_type
         );
-        let _ = self.sdk.unsubscribe_nowait(self.event_type.as_str());
+
+        match &self.listener {
+            Listener::Sdk { .. } => self.sdk.unsubscribe_nowait(self.event_type.as_str()),
+            Listener::Dispatch { closure, args } => {
+                let target: EventTarget = js_sys::global().unchecked_into();
+                let _ = target.remove_event_listener_with_callback(
+                    "message",
+                    closure.as_ref().unchecked_ref(),
+                );
+
+                let Ok(payload) = subscription_payload("UNSUBSCRIBE", &self.event_type, args)
+                else {
+                    return;
+                };
+
+                let internal = self.sdk.internal.clone();
+                spawn_local(async move {
+                    let _ = internal.send_command(payload).await;
+                });
+            }
+        }
+    }
+}
+
+fn subscription_payload(cmd: &str, event_type: &EventType, args: &JsValue) -> Result<JsValue> {
+    let payload = Object::new();
+    Reflect::set(&payload, &"cmd".into(), &cmd.into())?;
+    Reflect::set(&payload, &"evt".into(), &event_type.as_str().into())?;
+    Reflect::set(&payload, &"args".into(), args)?;
+    Ok(payload.into())
+}
+
+/// Returns the event data if the message is a dispatch frame (`[1, { cmd: "DISPATCH", ... }]`)
+/// for the given event.
+fn dispatch_data(message: &JsValue, event: &str) -> Option<JsValue> {
+    if !Array::is_array(message) {
+        return None;
     }
+
+    let frame = Array::from(message);
+    if frame.get(0).as_f64() != Some(1.0) {
+        return None;
+    }
+
+    let payload = frame.get(1);
+    let cmd = Reflect::get(&payload, &"cmd".into()).ok()?.as_string()?;
+    let evt = Reflect::get(&payload, &"evt".into()).ok()?.as_string()?;
+    if cmd != "DISPATCH" || evt != event {
+        return None;
+    }
+
+    Reflect::get(&payload, &"data".into()).ok()
 }
@@ -0,0 +1,97 @@
+use std::{
+    future::{pending, poll_fn, Future},
+    pin::{pin, Pin},
+    task::{Context, Poll},
+    time::Duration,
+};
+
+use activity_sys::timers::{clear_timeout, set_timeout};
+use js_sys::{Date, Promise};
+use wasm_bindgen::JsValue;
+use wasm_bindgen_futures::JsFuture;
+
+use crate::{abort::AbortHandle, errors::*};
+
+/// Resolves after the given duration has passed, based on `setTimeout`.
+///
+/// The timer is cleared when the future is dropped early.
+pub(crate) fn sleep(duration: Duration) -> Sleep {
+    let ms = duration.as_millis().min(i32::MAX as u128) as i32;
+
+    let mut handle = JsValue::UNDEFINED;
+    let promise = Promise::new(&mut |resolve, _| {
+        handle = set_timeout(&resolve, ms);
+    });
+
+    Sleep {
+        future: JsFuture::from(promise),
+        handle,
+    }
+}
+
+pub(crate) struct Sleep {
+    future: JsFuture,
+    handle: JsValue,
+}
+
+impl Future for Sleep {
+    type Output = ();
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<()> {
+        Pin::new(&mut self.future).poll(cx).map(|_| ())
+    }
+}
+
+impl Drop for Sleep {
+    fn drop(&mut self) {
+        clear_timeout(&self.handle);
+    }
+}
+
+/// Runs the future until it completes, the timeout elapses or the abort handle is triggered.
+pub(crate) async fn with_deadline<T>(
+    future: impl Future<Output = Result<T>>,
+    timeout: Option<Duration>,
+    abort: Option<&AbortHandle>,
+) -> Result<T> {
+    let mut future = pin!(future);
+    let mut timer = pin!(async {
+        match timeout {
+            Some(timeout) => sleep(timeout).await,
+            None => pending().await,
+        }
+    });
+    let mut aborted = pin!(async {
+        match abort {
+            Some(abort) => abort.aborted().await,
+            None => pending().await,
+        }
+    });
+
+    poll_fn(|cx| {
+        if let Poll::Ready(res) = future.as_mut().poll(cx) {
+            return Poll::Ready(res);
+        }
+        if aborted.as_mut().poll(cx).is_ready() {
+            return Poll::Ready(Err(Error::Aborted));
+        }
+        if timer.as_mut().poll(cx).is_ready() {
+            return Poll::Ready(Err(Error::Timeout));
+        }
+        Poll::Pending
+    })
+    .await
+}
+
+/// `std::time::Instant` isn't available on wasm32-unknown-unknown, so we measure with `Date`.
+pub(crate) struct Stopwatch(f64);
+
+impl Stopwatch {
+    pub(crate) fn start() -> Self {
+        Self(Date::now())
+    }
+
+    pub(crate) fn elapsed(&self) -> Duration {
+        Duration::from_secs_f64((Date::now() - self.0).max(0.0) / 1000.0)
+    }
+}
@@ -1,386 +1,279 @@
 use std::fmt::Display;
 
-use serde::{Deserialize, Serialize};
+use serde::{de::IgnoredAny, Deserialize, Deserializer, Serialize, Serializer};
 
-#[derive(Serialize, Deserialize, Debug)]
-pub struct User {
-    pub id: String,
-    pub username: String,
-    pub discriminator: String,
-    #[serde(default)]
-    pub avatar: Option<String>,
-    #[serde(default)]
-    pub public_flags: u64,
-    #[serde(default)]
-    pub flags: u64,
-    #[serde(default)]
-    pub premium_type: u8,
-    #[serde(default)]
-    pub global_name: Option<String>,
-    #[serde(default)]
-    pub bot: bool,
-}
-
-#[derive(Serialize, Deserialize, Debug)]
-pub struct Member {
-    pub user_id: String,
-    pub guild_id: String,
-    #[serde(default)]
-    pub nick: Option<String>,
-    #[serde(default)]
-    pub avatar: Option<String>,
-    #[serde(default)]
-    pub color_string: Option<String>,
-    #[serde(default)]
-    pub avatar_decoration_data: Option<MemberAvatarDecorationData>,
-}
+use crate::{sdk::DiscordSDK, CaptureLogArgs, EventType};
 
-#[derive(Serialize, Deserialize, Debug)]
-pub struct MemberAvatarDecorationData {
-    pub asset: String,
-    #[serde(default)]
-    pub sku_id: Option<String>,
-}
+/// Response of commands that don't return anything useful, the payload is ignored.
+#[derive(Serialize, Debug, Default)]
+pub struct EmptyRes {}
 
-#[derive(Serialize, Deserialize, Debug)]
-pub struct Application {
-    pub id: String,
-    pub name: String,
-    pub description: String,
-    #[serde(default)]
-    pub icon: Option<String>,
+impl<'de> Deserialize<'de> for EmptyRes {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        IgnoredAny::deserialize(deserializer)?;
+        Ok(EmptyRes {})
+    }
 }
 
-#[derive(Serialize, Deserialize, Debug)]
-pub struct Channel {}
-
-#[derive(Serialize, Deserialize, Debug)]
-pub struct Activity {}
-
 #[derive(Serialize, Deserialize, Debug)]
 pub struct SdkConfiguration {
     #[serde(rename = "disableConsoleLogOverride")]
     pub disable_console_log_override: bool,
 }
 
-#[derive(Serialize, Deserialize, Debug)]
-pub struct AuthorizeArgs {
-    pub client_id: String,
-    pub response_type: String,
-    pub state: String,
-    pub prompt: String,
-    pub scope: Vec<String>,
-}
-
-#[derive(Serialize, Deserialize, Debug)]
-pub struct AuthorizeRes {
-    pub code: String,
-}
-
-#[derive(Serialize, Deserialize, Debug)]
-pub struct AuthenticateArgs {
-    pub access_token: String,
-}
-
-#[derive(Serialize, Deserialize, Debug)]
-pub struct AuthenticateRes {
-    pub access_token: String,
-    pub scopes: Vec<String>,
-    pub expires: String,
-    pub user: User,
-    pub application: Application,
-}
-
-#[derive(Serialize, Deserialize, Debug)]
-pub struct CaputeLogArgs {
-    pub level: String,
-    pub message: String,
-}
-
-#[derive(Serialize, Deserialize, Debug)]
-pub struct GetChannelArgs {
-    pub channel_id: String,
-}
-
-#[derive(Serialize, Deserialize, Debug)]
-pub struct GetChannelRes {
-    #[serde(flatten)]
-    pub channel: Channel,
-}
-
-#[derive(Serialize, Deserialize, Debug)]
-pub struct GetChannelPermissionsRes {
-    #[serde(default)]
-    pub permissions: u64,
-}
-
-#[derive(Serialize, Deserialize, Debug)]
-pub struct GetInstanceConnectedParticipantsRes {
-    pub participants: Vec<User>,
-}
+#[deprecated(note = "renamed to `CaptureLogArgs`")]
+pub type CaputeLogArgs = CaptureLogArgs;
 
-#[derive(Serialize, Deserialize, Debug)]
-pub struct GetPlatformBehaviorsRes {
-    #[serde(default, rename = "iosKeyboardResizesView")]
-    pub ios_keyboard_resizes_view: Option<bool>,
+/// The value of an enum of the SDK on the wire, a number or a string.
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+#[serde(untagged)]
+pub(crate) enum EnumValue {
+    Number(i64),
+    String(String),
 }
 
-#[derive(Serialize, Deserialize, Debug)]
-pub struct InitiateImageUploadRes {
-    pub image_url: String,
+impl EnumValue {
+    /// What the SDK sends for values it doesn't know.
+    pub(crate) const UNHANDLED: EnumValue = EnumValue::Number(-1);
 }
 
-#[derive(Serialize, Deserialize, Debug)]
-pub struct OpenExternalLinkArgs {
-    pub url: String,
+impl From<i64> for EnumValue {
+    fn from(value: i64) -> Self {
+        EnumValue::Number(value)
+    }
 }
 
-#[derive(Serialize, Deserialize, Debug)]
-pub struct OpenExternalLinkRes {
-    #[serde(default)]
-    pub opened: bool,
+impl From<&str> for EnumValue {
+    fn from(value: &str) -> Self {
+        EnumValue::String(value.to_string())
+    }
 }
 
-#[derive(Serialize, Deserialize, Debug)]
-pub struct OpenShareMomentDialogArgs {
-    #[serde(rename = "mediaUrl")]
-    pub media_url: String,
-}
+/// Declares an enum of the SDK, values that this version of the bindings doesn't know are
+/// `Unhandled` instead of failing to deserialize.
+macro_rules! sdk_enum {
+    (pub enum $name:ident { $($variant:ident = $value:literal,)* }) => {
+        #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+        #[non_exhaustive]
+        pub enum $name {
+            $($variant,)*
+            Unhandled,
+        }
 
-#[derive(Serialize, Deserialize, Debug)]
-pub struct SetActivityArgs {
-    pub activity: Activity,
-}
+        impl serde::Serialize for $name {
+            fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+            where
+                S: serde::Serializer,
+            {
+                let value = match self {
+                    $($name::$variant => $crate::types::EnumValue::from($value),)*
+                    $name::Unhandled => $crate::types::EnumValue::UNHANDLED,
+                };
+                serde::Serialize::serialize(&value, serializer)
+            }
+        }
 
-#[derive(Serialize, Deserialize, Debug)]
-pub struct SetConfigArgs {
-    pub user_interactive_pip: bool,
+        impl<'de> serde::Deserialize<'de> for $name {
+            fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+            where
+                D: serde::Deserializer<'de>,
+            {
+                let value: $crate::types::EnumValue = serde::Deserialize::deserialize(deserializer)?;
+                $(
+                    if value == $crate::types::EnumValue::from($value) {
+                        return Ok($name::$variant);
+                    }
+                )*
+                Ok($name::Unhandled)
+            }
+        }
+    };
 }
 
-#[derive(Serialize, Deserialize, Debug)]
-pub struct SetOrientationLockStateArgs {
-    pub lock_state: String,
-    pub picture_in_picture_lock_state: String,
-    pub grid_lock_state: String,
-}
+pub(crate) use sdk_enum;
 
-#[derive(Serialize, Deserialize, Debug)]
-pub struct UserSettingsGetLocaleRes {
-    // TODO: Implement this struct
-}
+/// (De)serializes 64 bit integers like permissions, which the SDK has as a `bigint` or a string.
+pub(crate) mod big_int {
+    use std::fmt;
 
-#[derive(Serialize, Deserialize, Debug)]
-pub struct ReadyEvent {
-    pub v: u8,
-    pub config: ReadyEventConfig,
-}
+    use serde::{de, Deserializer, Serializer};
 
-impl EventPayload for ReadyEvent {
-    fn event_type() -> EventType {
-        EventType::Ready
+    pub fn serialize<S>(value: &u64, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.serialize_str(&value.to_string())
     }
-}
-
-#[derive(Serialize, Deserialize, Debug)]
-pub struct ReadyEventConfig {
-    cdn_host: String,
-    api_endpoint: String,
-    environment: String,
-}
 
-#[derive(Serialize, Deserialize, Debug)]
-pub struct ErrorEvent {
-    pub code: u64,
-    pub message: String,
-}
+    pub fn deserialize<'de, D>(deserializer: D) -> Result<u64, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        struct Visitor;
 
-impl EventPayload for ErrorEvent {
-    fn event_type() -> EventType {
-        EventType::Error
-    }
-}
+        impl<'de> de::Visitor<'de> for Visitor {
+            type Value = u64;
 
-#[derive(Serialize, Deserialize, Debug)]
-pub struct VoiceStateUpdateEvent {
-    pub voice_state: VoiceState,
-    pub user: User,
-    #[serde(default)]
-    pub nick: Option<String>,
-    pub volume: u32,
-    pub mute: bool,
-    #[serde(default)]
-    pub pan: Option<VoiceStateUpdatePan>,
-}
+            fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                f.write_str("an unsigned integer or a string of one")
+            }
 
-impl EventPayload for VoiceStateUpdateEvent {
-    fn event_type() -> EventType {
-        EventType::VoiceStateUpdate
-    }
-}
+            fn visit_u64<E: de::Error>(self, value: u64) -> Result<u64, E> {
+                Ok(value)
+            }
 
-#[derive(Serialize, Deserialize, Debug)]
-pub struct VoiceState {
-    pub mute: bool,
-    pub deaf: bool,
-    pub self_mute: bool,
-    pub self_deaf: bool,
-    pub suppress: bool,
-}
+            fn visit_i64<E: de::Error>(self, value: i64) -> Result<u64, E> {
+                u64::try_from(value).map_err(E::custom)
+            }
 
-#[derive(Serialize, Deserialize, Debug)]
-pub struct VoiceStateUpdatePan {
-    pub left: f32,
-    pub right: f32,
-}
+            fn visit_f64<E: de::Error>(self, value: f64) -> Result<u64, E> {
+                if value.fract() != 0.0 || value < 0.0 || value > u64::MAX as f64 {
+                    return Err(E::invalid_value(de::Unexpected::Float(value), &self));
+                }
+                Ok(value as u64)
+            }
 
-#[derive(Serialize, Deserialize, Debug)]
-pub struct SpeakingStartEvent {
-    pub channel_id: String,
-    pub user_id: String,
-}
+            fn visit_str<E: de::Error>(self, value: &str) -> Result<u64, E> {
+                value
+                    .parse()
+                    .map_err(|_| E::invalid_value(de::Unexpected::Str(value), &self))
+            }
+        }
 
-impl EventPayload for SpeakingStartEvent {
-    fn event_type() -> EventType {
-        EventType::SpeakingStart
+        deserializer.deserialize_any(Visitor)
     }
 }
 
-#[derive(Serialize, Deserialize, Debug)]
-pub struct SpeakingStopEvent {
-    pub channel_id: String,
-    pub user_id: String,
-}
-
-impl EventPayload for SpeakingStopEvent {
-    fn event_type() -> EventType {
-        EventType::SpeakingStop
+impl Serialize for EventType {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.serialize_str(self.as_str())
     }
 }
 
-#[derive(Serialize, Deserialize, Debug)]
-pub struct ActivityLayoutModeUpdateEvent {
-    pub layout_mode: u8,
-}
-
-impl EventPayload for ActivityLayoutModeUpdateEvent {
-    fn event_type() -> EventType {
-        EventType::ActivityLayoutModeUpdate
+impl<'de> Deserialize<'de> for EventType {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let name = String::deserialize(deserializer)?;
+        let Ok(event_type) = name.parse();
+        Ok(event_type)
     }
 }
 
-#[derive(Serialize, Deserialize, Debug)]
-pub struct OrientationUpdateEvent {
-    pub screen_orientation: u8,
-}
-
-impl EventPayload for OrientationUpdateEvent {
-    fn event_type() -> EventType {
-        EventType::OrientationUpdate
+impl Display for EventType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.as_str())
     }
 }
 
-#[derive(Serialize, Deserialize, Debug)]
-pub struct CurrentUserUpdateEvent {
-    #[serde(flatten)]
-    pub user: User,
-}
-
-impl EventPayload for CurrentUserUpdateEvent {
-    fn event_type() -> EventType {
-        EventType::CurrentUserUpdate
-    }
-}
+pub trait EventPayload {
+    /// The arguments needed to subscribe to the event, `()` if it doesn't take any.
+    type Args: EventArgs;
 
-#[derive(Serialize, Deserialize, Debug)]
-pub struct CurrentGuildMemberUpdateEvent {
-    #[serde(flatten)]
-    pub member: Member,
+    fn event_type() -> EventType;
 }
 
-impl EventPayload for CurrentGuildMemberUpdateEvent {
-    fn event_type() -> EventType {
-        EventType::CurrentGuildMemberUpdate
+pub trait EventArgs: Serialize {
+    /// Fills in arguments that default to the state of the SDK, like the current channel.
+    fn with_defaults(self, _sdk: &DiscordSDK) -> Self
+    where
+        Self: Sized,
+    {
+        self
     }
 }
 
-#[derive(Serialize, Deserialize, Debug)]
-pub struct ThermalStateUpdateEvent {
-    pub thermal_state: u8,
-}
+impl EventArgs for () {}
 
-impl EventPayload for ThermalStateUpdateEvent {
-    fn event_type() -> EventType {
-        EventType::ThermalStateUpdate
-    }
+/// Arguments for events that are scoped to a channel, like voice state updates.
+///
+/// The channel defaults to the one the activity was launched in.
+#[derive(Serialize, Deserialize, Debug, Default, Clone)]
+pub struct SubscribeArgs {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub channel_id: Option<String>,
 }
 
-#[derive(Serialize, Deserialize, Debug)]
-pub struct ActivityInstanceParticipantsUpdateEvent {
-    pub participants: Vec<User>,
+impl SubscribeArgs {
+    pub fn channel_id<T>(channel_id: T) -> Self
+    where
+        T: Into<String>,
+    {
+        Self {
+            channel_id: Some(channel_id.into()),
+        }
+    }
 }
 
-impl EventPayload for ActivityInstanceParticipantsUpdateEvent {
-    fn event_type() -> EventType {
-        EventType::ActivityInstanceParticipantsUpdate
+impl EventArgs for SubscribeArgs {
+    fn with_defaults(mut self, sdk: &DiscordSDK) -> Self {
+        if self.channel_id.is_none() {
+            self.channel_id = sdk.channel_id();
+        }
+        self
     }
 }
 
-pub enum EventType {
-    Ready,
-    Error,
-    VoiceStateUpdate,
-    SpeakingStart,
-    SpeakingStop,
-    ActivityLayoutModeUpdate,
-    OrientationUpdate,
-    CurrentUserUpdate,
-    CurrentGuildMemberUpdate,
-    ThermalStateUpdate,
-    ActivityInstanceParticipantsUpdate,
+/// Arguments for events that are scoped to a guild, like updates of the current guild member.
+///
+/// The guild defaults to the one the activity was launched in.
+#[derive(Serialize, Deserialize, Debug, Default, Clone)]
+pub struct GuildSubscribeArgs {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub guild_id: Option<String>,
 }
 
-impl EventType {
-    pub fn as_str(&self) -> &str {
-        match self {
-            EventType::Ready => "READY",
-            EventType::Error => "ERROR",
-            EventType::VoiceStateUpdate => "VOICE_STATE_UPDATE",
-            EventType::SpeakingStart => "SPEAKING_START",
-            EventType::SpeakingStop => "SPEAKING_STOP",
-            EventType::ActivityLayoutModeUpdate => "ACTIVITY_LAYOUT_MODE_UPDATE",
-            EventType::OrientationUpdate => "ORIENTATION_UPDATE",
-            EventType::CurrentUserUpdate => "CURRENT_USER_UPDATE",
-            EventType::CurrentGuildMemberUpdate => "CURRENT_GUILD_MEMBER_UPDATE",
-            EventType::ThermalStateUpdate => "THERMAL_STATE_UPDATE",
-            EventType::ActivityInstanceParticipantsUpdate => {
-                "ACTIVITY_INSTANCE_PARTICIPANTS_UPDATE"
-            }
+impl GuildSubscribeArgs {
+    pub fn guild_id<T>(guild_id: T) -> Self
+    where
+        T: Into<String>,
+    {
+        Self {
+            guild_id: Some(guild_id.into()),
         }
     }
 }
 
-impl Display for EventType {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{}", self.as_str())
+impl EventArgs for GuildSubscribeArgs {
+    fn with_defaults(mut self, sdk: &DiscordSDK) -> Self {
+        if self.guild_id.is_none() {
+            self.guild_id = sdk.guild_id();
+        }
+        self
     }
 }
 
-pub trait EventPayload {
-    fn event_type() -> EventType;
-}
-
-#[derive(Serialize, Deserialize, Debug)]
-pub struct SubscribeArgs {
-    pub channel_id: String,
-}
-
-impl SubscribeArgs {
-    pub fn channel_id<T>(channel_id: T) -> Self
-    where
-        T: Into<String>,
-    {
-        Self {
-            channel_id: channel_id.into(),
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn event_types_round_trip() {
+        for name in [
+            "READY",
+            "VOICE_STATE_UPDATE",
+            "CURRENT_GUILD_MEMBER_UPDATE",
+            "ACTIVITY_INSTANCE_PARTICIPANTS_UPDATE",
+            "ERROR",
+        ] {
+            let event_type: EventType = name.parse().unwrap();
+            assert!(!matches!(event_type, EventType::Other(_)), "{name}");
+            assert_eq!(event_type.to_string(), name);
         }
+
+        let event_type: EventType = "NEW_EVENT".parse().unwrap();
+        assert_eq!(event_type, EventType::Other("NEW_EVENT".into()));
+        assert_eq!(event_type.to_string(), "NEW_EVENT");
+        assert_eq!(
+            "ready".parse::<EventType>().unwrap(),
+            EventType::Other("ready".into())
+        );
     }
 }
@@ -0,0 +1,82 @@
+use activity_sys::console_warn;
+
+use crate::BINDINGS_SDK_VERSION;
+
+/// The version of the Discord SDK the activity is bundled with.
+///
+/// `activity-build` sets it when it compiles the crate, other builds assume the version the
+/// bindings are written for, [`BINDINGS_SDK_VERSION`].
+pub const SDK_VERSION: &str = match option_env!("ACTIVITY_SDK_VERSION") {
+    Some(version) => version,
+    None => BINDINGS_SDK_VERSION,
+};
+
+/// Whether the bundled SDK has the commands and events of the bindings, which is the case for
+/// the same major version (minor version before 1.0) that isn't older than the bindings.
+pub fn is_sdk_compatible() -> bool {
+    is_compatible(SDK_VERSION, BINDINGS_SDK_VERSION)
+}
+
+fn is_compatible(bundled: &str, bindings: &str) -> bool {
+    let (Some(bundled), Some(bindings)) = (parse_version(bundled), parse_version(bindings)) else {
+        return false;
+    };
+
+    let same_major = match bindings.0 {
+        0 => bundled.0 == 0 && bundled.1 == bindings.1,
+        major => bundled.0 == major,
+    };
+    same_major && bundled >= bindings
+}
+
+pub(crate) fn warn_if_sdk_incompatible() {
+    if !is_sdk_compatible() {
+        console_warn!(
+            "The activity is bundled with the Discord SDK {}, but the bindings are written for {}. \
+             Commands and events may not work as expected.",
+            SDK_VERSION,
+            BINDINGS_SDK_VERSION
+        );
+    }
+}
+
+// Pre-release and build suffixes are ignored.
+fn parse_version(version: &str) -> Option<(u64, u64, u64)> {
+    let version = version.split(['-', '+']).next()?;
+    let mut parts = version.split('.').map(|part| part.parse().ok());
+    Some((parts.next()??, parts.next()??, parts.next()??))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn versions() {
+        assert_eq!(parse_version("1.9.0"), Some((1, 9, 0)));
+        assert_eq!(parse_version("1.10.2-beta.1"), Some((1, 10, 2)));
+        assert_eq!(parse_version("2.0.0+build.5"), Some((2, 0, 0)));
+        assert_eq!(parse_version("1.9"), None);
+        assert_eq!(parse_version("1.x.0"), None);
+        assert_eq!(parse_version(""), None);
+    }
+
+    #[test]
+    fn compatible_versions() {
+        assert!(is_compatible("1.9.0", "1.9.0"));
+        assert!(is_compatible("1.10.1", "1.9.0"));
+        assert!(is_compatible("0.4.2", "0.4.0"));
+
+        assert!(!is_compatible("1.8.0", "1.9.0"));
+        assert!(!is_compatible("2.0.0", "1.9.0"));
+        assert!(!is_compatible("0.5.0", "0.4.0"));
+        assert!(!is_compatible("1.9", "1.9.0"));
+    }
+
+    #[test]
+    fn bindings_are_compatible_without_activity_build() {
+        if option_env!("ACTIVITY_SDK_VERSION").is_none() {
+            assert!(is_sdk_compatible());
+        }
+    }
+}
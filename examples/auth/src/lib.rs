@@ -1,4 +1,4 @@
-use std::{mem::forget, option_env};
+use std::mem::forget;
 
 use serde::{Deserialize, Serialize};
 
@@ -7,14 +7,8 @@ use wasm_bindgen::prelude::*;
 use wasm_bindgen_futures::JsFuture;
 use web_sys::{Request, RequestInit, Response};
 
-#[activity]
-pub async fn start() -> Result<(), JsValue> {
-    console_log!("Starting activity...");
-
-    let client_id = option_env!("CLIENT_ID").expect("CLIENT_ID environment variable must be set");
-    let sdk = DiscordSDK::new(client_id)?;
-    sdk.ready().await?;
-
+#[activity(client_id = build_env!("CLIENT_ID"), auto_ready, panic_hook)]
+pub async fn start(sdk: DiscordSDK) -> Result<(), JsValue> {
     console_log!("Activity ready!");
 
     authenticate_user(&sdk).await?;
@@ -25,7 +19,7 @@ pub async fn start() -> Result<(), JsValue> {
                 console_log!("Voice state update: {:?}", e);
                 Ok(())
             },
-            SubscribeArgs::channel_id(sdk.channel_id().unwrap()),
+            SubscribeArgs::default(),
         )
         .await?;
 
@@ -38,7 +32,7 @@ pub async fn start() -> Result<(), JsValue> {
                 console_log!("Speaking start: {:?}", e);
                 Ok(())
             },
-            SubscribeArgs::channel_id(sdk.channel_id().unwrap()),
+            SubscribeArgs::default(),
         )
         .await?;
 
@@ -50,7 +44,7 @@ pub async fn start() -> Result<(), JsValue> {
                 console_log!("Speaking stop: {:?}", e);
                 Ok(())
             },
-            SubscribeArgs::channel_id(sdk.channel_id().unwrap()),
+            SubscribeArgs::default(),
         )
         .await?;
 
@@ -63,20 +57,24 @@ async fn authenticate_user(sdk: &DiscordSDK) -> Result<(), JsValue> {
     let res = sdk
         .authorize(AuthorizeArgs {
             client_id: sdk.client_id(),
-            response_type: "code".to_string(),
-            state: "".to_string(),
-            prompt: "none".to_string(),
+            response_type: Some("code".to_string()),
+            prompt: Some("none".to_string()),
             scope: vec![
                 "identify".to_string(),
                 "guilds".to_string(),
                 "rpc.voice.read".to_string(),
             ],
+            ..Default::default()
         })
         .await?;
 
     let access_token = exchange_token(&res.code).await?;
 
-    let res = sdk.authenticate(AuthenticateArgs { access_token }).await?;
+    let res = sdk
+        .authenticate(AuthenticateArgs {
+            access_token: Some(access_token),
+        })
+        .await?;
 
     console_log!("Authenticated user: {:?}", res.user);
 
@@ -109,7 +107,7 @@ async fn exchange_token(code: &str) -> Result<String, JsValue> {
         return Err(JsValue::from_str("Failed to exchange token"));
     }
 
-    return Ok(resp.data.access_token);
+    Ok(resp.data.access_token)
 }
 
 #[derive(Serialize, Deserialize, Debug, Default)]
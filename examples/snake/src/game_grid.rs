@@ -29,20 +29,20 @@ fn get_random_position() -> PositionCoords {
 }
 
 enum Direction {
-    UP,
-    DOWN,
-    LEFT,
-    RIGHT,
+    Up,
+    Down,
+    Left,
+    Right,
 }
 
 impl Clone for Direction {
     fn clone(&self) -> Direction {
         use Direction as D;
         match self {
-            D::UP => D::UP,
-            D::DOWN => D::DOWN,
-            D::LEFT => D::LEFT,
-            D::RIGHT => D::RIGHT,
+            D::Up => D::Up,
+            D::Down => D::Down,
+            D::Left => D::Left,
+            D::Right => D::Right,
         }
     }
 }
@@ -104,10 +104,10 @@ impl GameGridComponent {
     }
     fn handle_keydown(&mut self, event: KeyboardEvent) {
         match event.key().as_str() {
-            "ArrowUp" => self.update_direction(Direction::UP),
-            "ArrowDown" => self.update_direction(Direction::DOWN),
-            "ArrowLeft" => self.update_direction(Direction::LEFT),
-            "ArrowRight" => self.update_direction(Direction::RIGHT),
+            "ArrowUp" => self.update_direction(Direction::Up),
+            "ArrowDown" => self.update_direction(Direction::Down),
+            "ArrowLeft" => self.update_direction(Direction::Left),
+            "ArrowRight" => self.update_direction(Direction::Right),
             " " => self.update_pause(!self.paused), // spacebar
             _ => {}
         }
@@ -115,10 +115,10 @@ impl GameGridComponent {
     fn handle_tick(&mut self) {
         let pos = PositionCoords::new(self.x, self.y);
         match self.current_direction {
-            Direction::UP => self.move_up(),
-            Direction::DOWN => self.move_down(),
-            Direction::LEFT => self.move_left(),
-            Direction::RIGHT => self.move_right(),
+            Direction::Up => self.move_up(),
+            Direction::Down => self.move_down(),
+            Direction::Left => self.move_left(),
+            Direction::Right => self.move_right(),
         }
         // update body segments
         for i in (0..self.body_segments.len()).rev() {
@@ -142,7 +142,7 @@ impl GameGridComponent {
     fn restart(&mut self) {
         self.x = 1;
         self.y = 1;
-        self.current_direction = Direction::RIGHT;
+        self.current_direction = Direction::Right;
         self.score = 0;
         self.paused = false;
         self.food_position = PositionCoords::random();
@@ -165,10 +165,8 @@ impl GameGridComponent {
 }
 
 fn is_boundary(x: HNum, y: HNum) -> bool {
-    x < BOUNDARY_THICKNESS
-        || x >= GRID_WIDTH + BOUNDARY_THICKNESS
-        || y < BOUNDARY_THICKNESS
-        || y >= GRID_HEIGHT + BOUNDARY_THICKNESS
+    !(BOUNDARY_THICKNESS..GRID_WIDTH + BOUNDARY_THICKNESS).contains(&x)
+        || !(BOUNDARY_THICKNESS..GRID_HEIGHT + BOUNDARY_THICKNESS).contains(&y)
 }
 
 impl Component for GameGridComponent {
@@ -183,7 +181,7 @@ impl Component for GameGridComponent {
         Self {
             x: spawn_position.x,
             y: spawn_position.y,
-            current_direction: Direction::RIGHT,
+            current_direction: Direction::Right,
             score: 0,
             paused: true,
             food_position: PositionCoords::random(),
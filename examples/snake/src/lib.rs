@@ -5,12 +5,11 @@ use yew::Renderer;
 
 mod game_grid;
 
-#[activity]
-pub async fn start() -> Result<(), JsValue> {
+#[activity(client_id = build_env!("CLIENT_ID"), panic_hook)]
+pub async fn start(sdk: DiscordSDK) -> Result<(), JsValue> {
     Renderer::<GameGridComponent>::new().render();
 
-    let activity = DiscordSDK::new(env!("CLIENT_ID"))?;
-    activity.ready().await?;
+    sdk.ready().await?;
 
     // TODO: implement multiplier
     Ok(())
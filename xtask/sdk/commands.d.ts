@@ -0,0 +1,50 @@
+// The inputs of commands that the SDK only has TypeScript types for, copied by `cargo xtask codegen`
+// from the SDK package 1.9.0, don't edit it.
+
+import * as zod from 'zod';
+
+export interface AuthorizeInput {
+  client_id: string;
+  scope: string[];
+  response_type?: 'code';
+  code_challenge?: string;
+  state?: string;
+  prompt?: 'none';
+  code_challenge_method?: 'S256';
+}
+
+export interface CaptureLogInput {
+  level: ConsoleLevel;
+  message: string;
+}
+
+export type ConsoleLevel = 'log' | 'warn' | 'debug' | 'info' | 'error';
+
+export interface GetChannelInput {
+  channel_id: string;
+}
+
+export interface OpenExternalLinkInput {
+  url: string;
+}
+
+export type OrientationLockState = zod.infer<typeof OrientationLockState>;
+
+export interface SetActivityInput {
+  activity: zod.infer<typeof SetActivity>;
+}
+
+export interface SetConfigInput {
+  use_interactive_pip: boolean;
+}
+
+export interface SetOrientationLockStateInput {
+  lock_state: OrientationLockState;
+  picture_in_picture_lock_state?: OrientationLockState | null;
+  grid_lock_state?: OrientationLockState | null;
+}
+
+export interface StartPurchaseInput {
+  sku_id: string;
+  pid?: string;
+}
@@ -0,0 +1,390 @@
+// A reader for the TypeScript declarations of command inputs: interfaces and type aliases made of
+// primitive types, literals, arrays, unions, object types and the zod schemas of the SDK. They are
+// read from the `.d.ts` files of the SDK package, or from the copy of the used ones in the repo.
+
+use std::{
+    cell::RefCell,
+    collections::BTreeMap,
+    fs,
+    path::{Path, PathBuf},
+};
+
+use anyhow::{Context, Result};
+
+use crate::{
+    js::{Lexer, Token},
+    schema::{Literal, Schema, Zod},
+};
+
+#[derive(Debug, Clone)]
+enum Type {
+    Name(String),
+    Literal(Literal),
+    /// `zod.infer<typeof X>`.
+    Infer(String),
+    Array(Box<Type>),
+    Union(Vec<Type>),
+    Object(Vec<Member>),
+}
+
+#[derive(Debug, Clone)]
+struct Member {
+    key: String,
+    optional: bool,
+    ty: Type,
+}
+
+/// The `interface` and `type` declarations of `.d.ts` files. They are only parsed when they are
+/// used, so the files can have declarations that aren't supported.
+pub struct Declarations<'a> {
+    zod: &'a Zod<'a>,
+    /// The source of the declarations by name and the files they are in.
+    sources: BTreeMap<String, Vec<(PathBuf, String)>>,
+    schemas: RefCell<BTreeMap<String, Schema>>,
+}
+
+impl<'a> Declarations<'a> {
+    pub fn parse(source: &str, zod: &'a Zod<'a>) -> Self {
+        let mut declarations = Self {
+            zod,
+            sources: BTreeMap::new(),
+            schemas: RefCell::default(),
+        };
+        declarations.add(Path::new(""), source);
+        declarations
+    }
+
+    /// The declarations of all `.d.ts` files of an npm package.
+    pub fn package(dir: &Path, zod: &'a Zod<'a>) -> Result<Self> {
+        let mut declarations = Self::parse("", zod);
+        for path in dts_files(dir)? {
+            let source = fs::read_to_string(&path)
+                .with_context(|| format!("failed to read {}", path.display()))?;
+            declarations.add(&path, &source);
+        }
+        Ok(declarations)
+    }
+
+    fn add(&mut self, path: &Path, source: &str) {
+        for (name, source) in scan(source) {
+            self.sources
+                .entry(name)
+                .or_default()
+                .push((path.to_path_buf(), source));
+        }
+    }
+
+    /// The schema of the declared type, `None` if there is no such declaration.
+    pub fn get(&self, name: &str) -> Result<Option<Schema>> {
+        if let Some(schema) = self.schemas.borrow().get(name) {
+            return Ok(Some(schema.clone()));
+        }
+        let Some(source) = self.source(name)? else {
+            return Ok(None);
+        };
+
+        let ty = parse_declaration(source)
+            .with_context(|| format!("failed to parse the declaration of `{name}`"))?;
+        let schema = match &ty {
+            Type::Object(members) => Schema::object(Some(name.to_string()), self.members(members)?),
+            ty => self.schema(ty)?,
+        };
+        self.schemas
+            .borrow_mut()
+            .insert(name.to_string(), schema.clone());
+        Ok(Some(schema))
+    }
+
+    fn source(&self, name: &str) -> Result<Option<&str>> {
+        let Some(sources) = self.sources.get(name) else {
+            return Ok(None);
+        };
+        let (path, source) = &sources[0];
+        if let Some((other, _)) = sources.iter().find(|(_, other)| other != source) {
+            anyhow::bail!(
+                "`{name}` is declared differently in {} and {}",
+                path.display(),
+                other.display()
+            );
+        }
+        Ok(Some(source))
+    }
+
+    /// The source of the declarations that were used, in the order of their names.
+    pub fn used(&self) -> Result<String> {
+        let mut out = String::new();
+        for name in self.schemas.borrow().keys() {
+            let source = self.source(name)?.expect("used declarations exist");
+            out.push_str("\nexport ");
+            out.push_str(source);
+            out.push('\n');
+        }
+        Ok(out)
+    }
+
+    fn schema(&self, ty: &Type) -> Result<Schema> {
+        Ok(match ty {
+            Type::Name(name) => match name.as_str() {
+                "string" => Schema::String,
+                "number" => Schema::Number,
+                "boolean" => Schema::Boolean,
+                "null" | "undefined" => Schema::Null,
+                "void" => Schema::Void,
+                "unknown" | "any" | "object" => Schema::Unknown,
+                name => self
+                    .get(name)?
+                    .with_context(|| format!("`{name}` isn't declared"))?,
+            },
+            Type::Literal(literal) => Schema::Literal(literal.clone()),
+            Type::Infer(name) => self.zod.var(name)?,
+            Type::Array(element) => Schema::Array(Box::new(self.schema(element)?)),
+            Type::Union(options) => {
+                let options = options
+                    .iter()
+                    .map(|option| self.schema(option))
+                    .collect::<Result<Vec<_>>>()?;
+                let nullable = options.iter().any(|option| matches!(option, Schema::Null));
+                let mut options: Vec<_> = options
+                    .into_iter()
+                    .filter(|option| !matches!(option, Schema::Null))
+                    .collect();
+
+                let schema = match options.len() {
+                    1 => options.remove(0),
+                    _ => Schema::Union(options),
+                };
+                if nullable {
+                    Schema::Optional(Box::new(schema))
+                } else {
+                    schema
+                }
+            }
+            Type::Object(members) => Schema::object(None, self.members(members)?),
+        })
+    }
+
+    fn members(&self, members: &[Member]) -> Result<Vec<(String, Schema)>> {
+        members
+            .iter()
+            .map(|member| {
+                let schema = self.schema(&member.ty)?;
+                let schema = match schema {
+                    Schema::Optional(_) => schema,
+                    schema if member.optional => Schema::Optional(Box::new(schema)),
+                    schema => schema,
+                };
+                Ok((member.key.clone(), schema))
+            })
+            .collect()
+    }
+}
+
+// An `interface` or `type` declaration as found by `scan`.
+fn parse_declaration(source: &str) -> Result<Type> {
+    let mut lexer = Lexer::new(source, 0);
+    let keyword = lexer.ident()?;
+    lexer.ident()?;
+    if keyword == "interface" {
+        lexer.expect("{")?;
+        Ok(Type::Object(parse_members(&mut lexer)?))
+    } else {
+        lexer.expect("=")?;
+        parse_type(&mut lexer)
+    }
+}
+
+// The source of the top-level `interface` and `type` declarations by name, from the keyword to
+// the end of the declaration. Everything else is skipped.
+fn scan(source: &str) -> Vec<(String, String)> {
+    let mut lexer = Lexer::new(source, 0);
+    let mut declarations = Vec::new();
+    let mut depth = 0usize;
+
+    while let Some(token) = next_token(&mut lexer) {
+        match token {
+            Token::Punct("{" | "(" | "[") => depth += 1,
+            Token::Punct("}" | ")" | "]") => depth = depth.saturating_sub(1),
+            Token::Ident(keyword)
+                if depth == 0 && (keyword == "interface" || keyword == "type") =>
+            {
+                let start = lexer.pos - keyword.len();
+                let mut ahead = Lexer::new(source, lexer.pos);
+                let Some(Token::Ident(name)) = next_token(&mut ahead) else {
+                    continue;
+                };
+                let after_name = ahead.pos;
+                let is_declaration = match (keyword.as_str(), next_token(&mut ahead)) {
+                    ("interface", Some(Token::Punct("{" | "<"))) => true,
+                    ("interface", Some(Token::Ident(extends))) => extends == "extends",
+                    ("type", Some(Token::Punct("=" | "<"))) => true,
+                    _ => false,
+                };
+                if !is_declaration {
+                    continue;
+                }
+
+                let end =
+                    declaration_end(&mut Lexer::new(source, after_name), keyword == "interface");
+                let mut declaration = source[start..end].to_string();
+                if keyword == "type" && !declaration.ends_with(';') {
+                    declaration.push(';');
+                }
+                declarations.push((name, declaration));
+                lexer.pos = end;
+            }
+            _ => {}
+        }
+    }
+
+    declarations
+}
+
+// Where an interface ends after its closing brace, or a type alias after its semicolon.
+fn declaration_end(lexer: &mut Lexer, interface: bool) -> usize {
+    let mut depth = 0usize;
+    while let Some(token) = next_token(lexer) {
+        match token {
+            Token::Punct("{" | "(" | "[") => depth += 1,
+            Token::Punct("}" | ")" | "]") => {
+                depth = depth.saturating_sub(1);
+                if interface && depth == 0 {
+                    break;
+                }
+            }
+            Token::Punct(";") if !interface && depth == 0 => break,
+            _ => {}
+        }
+    }
+    lexer.pos
+}
+
+// The next token, skipping syntax the lexer doesn't support like template literals.
+fn next_token(lexer: &mut Lexer) -> Option<Token> {
+    loop {
+        match lexer.next() {
+            Ok(token) => return token,
+            Err(_) => {
+                let rest = &lexer.source()[lexer.pos..];
+                let mut chars = rest.chars();
+                lexer.pos += match chars.next()? {
+                    '`' => rest[1..].find('`').map_or(rest.len(), |end| end + 2),
+                    c => c.len_utf8(),
+                };
+            }
+        }
+    }
+}
+
+// The `.d.ts` files of a package, without the ones of its dependencies.
+fn dts_files(dir: &Path) -> Result<Vec<PathBuf>> {
+    let mut files = Vec::new();
+    let mut entries: Vec<_> = fs::read_dir(dir)
+        .with_context(|| format!("failed to read {}", dir.display()))?
+        .collect::<Result<_, _>>()?;
+    entries.sort_by_key(|entry| entry.path());
+
+    for entry in entries {
+        let path = entry.path();
+        if entry.file_type()?.is_dir() {
+            if entry.file_name() != "node_modules" {
+                files.extend(dts_files(&path)?);
+            }
+        } else if path.to_string_lossy().ends_with(".d.ts") {
+            files.push(path);
+        }
+    }
+    Ok(files)
+}
+
+// The members of an object type after the opening brace.
+fn parse_members(lexer: &mut Lexer) -> Result<Vec<Member>> {
+    let mut members = Vec::new();
+    while !lexer.eat("}")? {
+        let mut key = match lexer.next()? {
+            Some(Token::Ident(key)) | Some(Token::Str(key)) => key,
+            token => anyhow::bail!("expected a member, found {token:?}"),
+        };
+        if key == "readonly" && !lexer.eat(":")? && lexer.peek()? != Some(Token::Punct("?")) {
+            key = lexer.ident()?;
+        }
+        let optional = lexer.eat("?")?;
+        lexer.expect(":")?;
+        let ty = parse_type(lexer)?;
+        members.push(Member { key, optional, ty });
+
+        if !lexer.eat(";")? && !lexer.eat(",")? {
+            lexer.expect("}")?;
+            break;
+        }
+    }
+    Ok(members)
+}
+
+fn parse_type(lexer: &mut Lexer) -> Result<Type> {
+    lexer.eat("|")?;
+    let mut options = vec![parse_array_type(lexer)?];
+    while lexer.eat("|")? {
+        options.push(parse_array_type(lexer)?);
+    }
+
+    Ok(match options.len() {
+        1 => options.remove(0),
+        _ => Type::Union(options),
+    })
+}
+
+fn parse_array_type(lexer: &mut Lexer) -> Result<Type> {
+    let mut ty = parse_primary_type(lexer)?;
+    while lexer.eat("[")? {
+        lexer.expect("]")?;
+        ty = Type::Array(Box::new(ty));
+    }
+    Ok(ty)
+}
+
+fn parse_primary_type(lexer: &mut Lexer) -> Result<Type> {
+    let token = lexer
+        .next()?
+        .context("unexpected end of the declarations")?;
+
+    Ok(match token {
+        Token::Str(value) => Type::Literal(Literal::Str(value)),
+        Token::Num(value) => Type::Literal(Literal::Num(value)),
+        Token::Punct("-") => match lexer.next()? {
+            Some(Token::Num(value)) => Type::Literal(Literal::Num(-value)),
+            token => anyhow::bail!("expected a number, found {token:?}"),
+        },
+        Token::Punct("{") => Type::Object(parse_members(lexer)?),
+        Token::Punct("(") => {
+            let ty = parse_type(lexer)?;
+            lexer.expect(")")?;
+            ty
+        }
+        Token::Ident(name) => match name.as_str() {
+            "true" => Type::Literal(Literal::Bool(true)),
+            "false" => Type::Literal(Literal::Bool(false)),
+            "Array" => {
+                lexer.expect("<")?;
+                let element = parse_type(lexer)?;
+                lexer.expect(">")?;
+                Type::Array(Box::new(element))
+            }
+            // `zod.infer<typeof X>` or `z.infer<typeof X>`.
+            _ if lexer.eat(".")? => {
+                let member = lexer.ident()?;
+                if member != "infer" {
+                    anyhow::bail!("unsupported type `{name}.{member}`");
+                }
+                lexer.expect("<")?;
+                if lexer.ident()? != "typeof" {
+                    anyhow::bail!("expected `{name}.infer<typeof ...>`");
+                }
+                let schema = lexer.ident()?;
+                lexer.expect(">")?;
+                Type::Infer(schema)
+            }
+            _ => Type::Name(name),
+        },
+        token => anyhow::bail!("unexpected {token:?} in a type"),
+    })
+}
@@ -0,0 +1,380 @@
+// A parser for the part of JavaScript the SDK's schemas are written in: literals, calls, member
+// access and arrow functions with an expression body. Declarations are only parsed when they are
+// looked up, so everything else in the module can be arbitrary JavaScript.
+
+use std::collections::BTreeMap;
+
+use anyhow::{Context, Result};
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Expr {
+    Ident(String),
+    Str(String),
+    Num(f64),
+    Bool(bool),
+    Null,
+    Array(Vec<Expr>),
+    Object(Vec<Prop>),
+    Member(Box<Expr>, String),
+    Index(Box<Expr>, Box<Expr>),
+    Call(Box<Expr>, Vec<Expr>),
+    /// Only the body matters for the schemas.
+    Arrow(Box<Expr>),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Prop {
+    KeyValue(String, Expr),
+    Spread(Expr),
+}
+
+impl Expr {
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            Expr::Str(s) => Some(s),
+            _ => None,
+        }
+    }
+
+    pub fn prop(&self, key: &str) -> Option<&Expr> {
+        let Expr::Object(props) = self else {
+            return None;
+        };
+        props.iter().find_map(|prop| match prop {
+            Prop::KeyValue(k, value) if k == key => Some(value),
+            _ => None,
+        })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Token {
+    Ident(String),
+    Str(String),
+    Num(f64),
+    Punct(&'static str),
+}
+
+const PUNCTUATION: &[&str] = &[
+    "...", "=>", "||", "??", "{", "}", "(", ")", "[", "]", ",", ":", ";", ".", "=", "?", "|", "<",
+    ">", "-", "&", "*",
+];
+
+pub struct Lexer<'a> {
+    source: &'a str,
+    pub pos: usize,
+}
+
+impl<'a> Lexer<'a> {
+    pub fn new(source: &'a str, pos: usize) -> Self {
+        Self { source, pos }
+    }
+
+    pub fn source(&self) -> &'a str {
+        self.source
+    }
+
+    fn rest(&self) -> &'a str {
+        &self.source[self.pos..]
+    }
+
+    fn skip_trivia(&mut self) -> Result<()> {
+        loop {
+            let rest = self.rest();
+            let trimmed = rest.trim_start();
+            self.pos += rest.len() - trimmed.len();
+
+            if trimmed.starts_with("//") {
+                self.pos += trimmed.find('\n').unwrap_or(trimmed.len());
+            } else if let Some(comment) = trimmed.strip_prefix("/*") {
+                let end = comment.find("*/").context("unterminated comment")?;
+                self.pos += end + 4;
+            } else {
+                return Ok(());
+            }
+        }
+    }
+
+    pub fn peek(&mut self) -> Result<Option<Token>> {
+        let pos = self.pos;
+        let token = self.next();
+        self.pos = pos;
+        token
+    }
+
+    pub fn next(&mut self) -> Result<Option<Token>> {
+        self.skip_trivia()?;
+        let rest = self.rest();
+        let Some(c) = rest.chars().next() else {
+            return Ok(None);
+        };
+
+        if c.is_ascii_alphabetic() || c == '_' || c == '$' {
+            let len = rest
+                .find(|c: char| !(c.is_ascii_alphanumeric() || c == '_' || c == '$'))
+                .unwrap_or(rest.len());
+            self.pos += len;
+            return Ok(Some(Token::Ident(rest[..len].to_string())));
+        }
+
+        if c.is_ascii_digit() {
+            let len = rest
+                .find(|c: char| !(c.is_ascii_alphanumeric() || c == '.'))
+                .unwrap_or(rest.len());
+            let number = rest[..len]
+                .parse()
+                .with_context(|| format!("invalid number `{}`", &rest[..len]))?;
+            self.pos += len;
+            return Ok(Some(Token::Num(number)));
+        }
+
+        if c == '"' || c == '\'' {
+            let mut value = String::new();
+            let mut chars = rest.char_indices().skip(1);
+            while let Some((i, ch)) = chars.next() {
+                match ch {
+                    '\\' => {
+                        let (_, escaped) = chars.next().context("unterminated string")?;
+                        value.push(match escaped {
+                            'n' => '\n',
+                            't' => '\t',
+                            c => c,
+                        });
+                    }
+                    ch if ch == c => {
+                        self.pos += i + 1;
+                        return Ok(Some(Token::Str(value)));
+                    }
+                    ch => value.push(ch),
+                }
+            }
+            anyhow::bail!("unterminated string");
+        }
+
+        for punct in PUNCTUATION {
+            if rest.starts_with(punct) {
+                self.pos += punct.len();
+                return Ok(Some(Token::Punct(punct)));
+            }
+        }
+
+        anyhow::bail!("unsupported syntax `{}`", rest.lines().next().unwrap_or(""))
+    }
+
+    pub fn expect(&mut self, punct: &str) -> Result<()> {
+        match self.next()? {
+            Some(Token::Punct(p)) if p == punct => Ok(()),
+            token => anyhow::bail!("expected `{punct}`, found {token:?}"),
+        }
+    }
+
+    pub fn eat(&mut self, punct: &str) -> Result<bool> {
+        let matches = matches!(self.peek()?, Some(Token::Punct(p)) if p == punct);
+        if matches {
+            self.next()?;
+        }
+        Ok(matches)
+    }
+
+    pub fn ident(&mut self) -> Result<String> {
+        match self.next()? {
+            Some(Token::Ident(name)) => Ok(name),
+            token => anyhow::bail!("expected a name, found {token:?}"),
+        }
+    }
+}
+
+pub fn parse_expr(lexer: &mut Lexer) -> Result<Expr> {
+    let mut expr = parse_primary(lexer)?;
+
+    loop {
+        if lexer.eat(".")? {
+            expr = Expr::Member(Box::new(expr), lexer.ident()?);
+        } else if lexer.eat("[")? {
+            let index = parse_expr(lexer)?;
+            lexer.expect("]")?;
+            expr = Expr::Index(Box::new(expr), Box::new(index));
+        } else if lexer.eat("(")? {
+            let args = parse_list(lexer, ")")?;
+            expr = Expr::Call(Box::new(expr), args);
+        } else {
+            return Ok(expr);
+        }
+    }
+}
+
+fn parse_primary(lexer: &mut Lexer) -> Result<Expr> {
+    let token = lexer.next()?.context("unexpected end of the module")?;
+
+    Ok(match token {
+        Token::Ident(name) => match name.as_str() {
+            "true" => Expr::Bool(true),
+            "false" => Expr::Bool(false),
+            "null" => Expr::Null,
+            "async" | "function" | "class" | "new" => anyhow::bail!("unsupported `{name}`"),
+            _ if lexer.eat("=>")? => Expr::Arrow(Box::new(parse_arrow_body(lexer)?)),
+            _ => Expr::Ident(name),
+        },
+        Token::Str(value) => Expr::Str(value),
+        Token::Num(value) => Expr::Num(value),
+        Token::Punct("-") => match lexer.next()? {
+            Some(Token::Num(value)) => Expr::Num(-value),
+            token => anyhow::bail!("expected a number, found {token:?}"),
+        },
+        Token::Punct("[") => Expr::Array(parse_list(lexer, "]")?),
+        Token::Punct("{") => parse_object(lexer)?,
+        Token::Punct("(") => {
+            if let Some(body) = try_parse_arrow(lexer)? {
+                body
+            } else {
+                let expr = parse_expr(lexer)?;
+                lexer.expect(")")?;
+                expr
+            }
+        }
+        token => anyhow::bail!("unexpected {token:?}"),
+    })
+}
+
+// `(a, b) => body` after the opening parenthesis, `None` if it's a parenthesized expression.
+fn try_parse_arrow(lexer: &mut Lexer) -> Result<Option<Expr>> {
+    let start = lexer.pos;
+    loop {
+        match lexer.next()? {
+            Some(Token::Ident(_)) | Some(Token::Punct(",")) => continue,
+            Some(Token::Punct(")")) if lexer.eat("=>")? => {
+                return Ok(Some(Expr::Arrow(Box::new(parse_arrow_body(lexer)?))));
+            }
+            _ => {
+                lexer.pos = start;
+                return Ok(None);
+            }
+        }
+    }
+}
+
+fn parse_arrow_body(lexer: &mut Lexer) -> Result<Expr> {
+    if lexer.peek()? == Some(Token::Punct("{")) {
+        anyhow::bail!("unsupported arrow function with a block");
+    }
+    parse_expr(lexer)
+}
+
+fn parse_list(lexer: &mut Lexer, end: &str) -> Result<Vec<Expr>> {
+    let mut items = Vec::new();
+    while !lexer.eat(end)? {
+        items.push(parse_expr(lexer)?);
+        if !lexer.eat(",")? {
+            lexer.expect(end)?;
+            break;
+        }
+    }
+    Ok(items)
+}
+
+fn parse_object(lexer: &mut Lexer) -> Result<Expr> {
+    let mut props = Vec::new();
+    while !lexer.eat("}")? {
+        if lexer.eat("...")? {
+            props.push(Prop::Spread(parse_expr(lexer)?));
+        } else {
+            let key = match lexer.next()? {
+                Some(Token::Ident(key)) | Some(Token::Str(key)) => key,
+                Some(Token::Num(key)) => key.to_string(),
+                Some(Token::Punct("[")) => {
+                    let key = parse_expr(lexer)?;
+                    lexer.expect("]")?;
+                    key.as_str()
+                        .context("unsupported computed key")?
+                        .to_string()
+                }
+                token => anyhow::bail!("expected a key, found {token:?}"),
+            };
+            let value = if lexer.eat(":")? {
+                parse_expr(lexer)?
+            } else {
+                Expr::Ident(key.clone())
+            };
+            props.push(Prop::KeyValue(key, value));
+        }
+
+        if !lexer.eat(",")? {
+            lexer.expect("}")?;
+            break;
+        }
+    }
+    Ok(Expr::Object(props))
+}
+
+/// The top-level declarations of a bundled module, parsed when they are looked up.
+pub struct Module<'a> {
+    source: &'a str,
+    /// The position of the initializer of every `var`.
+    vars: BTreeMap<&'a str, usize>,
+}
+
+impl<'a> Module<'a> {
+    pub fn new(source: &'a str) -> Self {
+        let mut vars = BTreeMap::new();
+        let mut pos = 0;
+        for line in source.split_inclusive('\n') {
+            if let Some((name, _)) = line
+                .strip_prefix("var ")
+                .and_then(|rest| rest.split_once(" = "))
+            {
+                if name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_') {
+                    vars.insert(name, pos + "var ".len() + name.len() + " = ".len());
+                }
+            }
+            pos += line.len();
+        }
+
+        Self { source, vars }
+    }
+
+    pub fn var(&self, name: &str) -> Result<Expr> {
+        let pos = *self
+            .vars
+            .get(name)
+            .with_context(|| format!("`{name}` isn't declared"))?;
+        parse_expr(&mut Lexer::new(self.source, pos))
+            .with_context(|| format!("failed to parse `{name}`"))
+    }
+
+    /// The expression after the first occurrence of `prefix`, e.g. the object of a call.
+    pub fn after(&self, prefix: &str) -> Result<Expr> {
+        let pos = self
+            .source
+            .find(prefix)
+            .with_context(|| format!("`{prefix}` not found"))?;
+        parse_expr(&mut Lexer::new(self.source, pos + prefix.len()))
+            .with_context(|| format!("failed to parse `{prefix}`"))
+    }
+
+    /// The members of an enum that TypeScript compiled to `X[key] = value` assignments.
+    pub fn ts_enum(&self, name: &str) -> Result<Vec<(String, String)>> {
+        let pos = *self
+            .vars
+            .get(name)
+            .with_context(|| format!("`{name}` isn't declared"))?;
+
+        let mut members = Vec::new();
+        for line in self.source[pos..].lines().skip(1) {
+            let line = line.trim();
+            if line.starts_with("return ") {
+                return Ok(members);
+            }
+            let (key, value) = line
+                .split_once("[")
+                .and_then(|(_, rest)| rest.split_once("] = "))
+                .with_context(|| format!("unsupported member of `{name}`: {line}"))?;
+            let key = parse_expr(&mut Lexer::new(key, 0))?;
+            let value = parse_expr(&mut Lexer::new(value.trim_end_matches(';'), 0))?;
+            match (key, value) {
+                (Expr::Str(key), Expr::Str(value)) => members.push((key, value)),
+                _ => anyhow::bail!("unsupported member of `{name}`: {line}"),
+            }
+        }
+        anyhow::bail!("`{name}` doesn't end")
+    }
+}
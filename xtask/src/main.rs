@@ -0,0 +1,186 @@
+// Development tasks of the workspace, run with `cargo xtask <task>`.
+
+use std::{
+    fs,
+    io::Write,
+    path::{Path, PathBuf},
+    process::{Command, Stdio},
+};
+
+use anyhow::{Context, Result};
+use clap::{Parser, Subcommand};
+
+mod dts;
+mod js;
+mod rust;
+mod schema;
+mod sdk;
+
+/// The SDK that activity-build bundles, which the bindings are generated from.
+const SDK_PATH: &str = "activity-build/src/static/sdk.js";
+
+/// The inputs of commands that the SDK only has TypeScript types for, copied from the `.d.ts` files
+/// of the SDK package so the bindings can be generated without it.
+const COMMANDS_DTS_PATH: &str = "xtask/sdk/commands.d.ts";
+
+/// Where the SDK package is installed with `npm install`.
+const SDK_PACKAGE_PATH: &str = "node_modules/@discord/embedded-app-sdk";
+
+/// Where activity-build declares the version of the bundled SDK.
+const BUILD_SDK_PATH: &str = "activity-build/src/sdk.rs";
+
+#[derive(Parser)]
+struct Cli {
+    #[command(subcommand)]
+    command: Task,
+}
+
+#[derive(Subcommand)]
+enum Task {
+    /// Generates the commands, types and events of the bindings from the bundled SDK
+    Codegen {
+        /// Fails if the generated files are out of date instead of writing them
+        #[arg(long)]
+        check: bool,
+        /// The SDK package to read the TypeScript declarations from, `node_modules` by default
+        #[arg(long)]
+        sdk: Option<PathBuf>,
+    },
+}
+
+fn main() -> Result<()> {
+    match Cli::parse().command {
+        Task::Codegen { check, sdk } => codegen(check, sdk),
+    }
+}
+
+fn codegen(check: bool, package: Option<PathBuf>) -> Result<()> {
+    let root = workspace_root();
+
+    let source = read(&root.join(SDK_PATH))?;
+    let module = js::Module::new(&source);
+    let zod = schema::Zod::new(&module);
+
+    let package = package.or_else(|| {
+        let path = root.join(SDK_PACKAGE_PATH);
+        path.exists().then_some(path)
+    });
+    let declarations = match &package {
+        Some(package) => dts::Declarations::package(package, &zod)?,
+        None => {
+            eprintln!(
+                "warning: the SDK package isn't installed, {COMMANDS_DTS_PATH} is used as it is \
+                 and isn't checked. Install it with `npm install @discord/embedded-app-sdk@<version>` \
+                 or pass its path with `--sdk`"
+            );
+            dts::Declarations::parse(&read(&root.join(COMMANDS_DTS_PATH))?, &zod)
+        }
+    };
+    let sdk = sdk::load(&module, &zod, &declarations)
+        .with_context(|| format!("failed to read the commands and events of {SDK_PATH}"))?;
+
+    let built_in = format!("pub const BUILT_IN_VERSION: &str = \"{}\";", sdk.version);
+    if !read(&root.join(BUILD_SDK_PATH))?.contains(&built_in) {
+        anyhow::bail!(
+            "{SDK_PATH} is the Discord SDK {}, update `BUILT_IN_VERSION` in {BUILD_SDK_PATH}",
+            sdk.version
+        );
+    }
+
+    let mut files = vec![
+        (
+            "activity-sys/src/commands.rs",
+            rustfmt(&rust::sys_commands(&sdk))?,
+        ),
+        ("activity/src/schema.rs", rustfmt(&rust::schema(&sdk)?)?),
+    ];
+    if let Some(package) = &package {
+        let version = package_version(package)?;
+        if version != sdk.version {
+            anyhow::bail!(
+                "the SDK package in {} is version {version}, but {SDK_PATH} is {}",
+                package.display(),
+                sdk.version
+            );
+        }
+        files.push((
+            COMMANDS_DTS_PATH,
+            format!(
+                "// The inputs of commands that the SDK only has TypeScript types for, copied by \
+                 `cargo xtask codegen`\n// from the SDK package {version}, don't edit it.\n\n\
+                 import * as zod from 'zod';\n{}",
+                declarations.used()?
+            ),
+        ));
+    }
+
+    let mut stale = Vec::new();
+    for (path, contents) in files {
+        let full_path = root.join(path);
+        if fs::read_to_string(&full_path).ok().as_deref() == Some(contents.as_str()) {
+            continue;
+        }
+
+        if check {
+            stale.push(path);
+        } else {
+            fs::write(&full_path, contents)
+                .with_context(|| format!("failed to write {}", full_path.display()))?;
+            println!("Generated {path}");
+        }
+    }
+
+    if !stale.is_empty() {
+        anyhow::bail!(
+            "{} out of date with the Discord SDK {}, run `cargo xtask codegen`",
+            stale.join(", ") + if stale.len() == 1 { " is" } else { " are" },
+            sdk.version
+        );
+    }
+
+    Ok(())
+}
+
+fn workspace_root() -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR"))
+        .parent()
+        .expect("xtask is in the workspace")
+        .to_path_buf()
+}
+
+fn read(path: &Path) -> Result<String> {
+    fs::read_to_string(path).with_context(|| format!("failed to read {}", path.display()))
+}
+
+// The version in the `package.json` of a package, which is read like a JavaScript object.
+fn package_version(dir: &Path) -> Result<String> {
+    let path = dir.join("package.json");
+    let package = js::parse_expr(&mut js::Lexer::new(&read(&path)?, 0))
+        .with_context(|| format!("failed to parse {}", path.display()))?;
+    Ok(package
+        .prop("version")
+        .and_then(js::Expr::as_str)
+        .with_context(|| format!("{} has no version", path.display()))?
+        .to_string())
+}
+
+fn rustfmt(source: &str) -> Result<String> {
+    let mut child = Command::new("rustfmt")
+        .args(["--edition", "2021"])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .context("failed to run rustfmt, install it with `rustup component add rustfmt`")?;
+
+    child
+        .stdin
+        .take()
+        .expect("stdin is piped")
+        .write_all(source.as_bytes())?;
+    let output = child.wait_with_output()?;
+    if !output.status.success() {
+        anyhow::bail!("rustfmt failed on the generated code");
+    }
+
+    Ok(String::from_utf8(output.stdout)?)
+}
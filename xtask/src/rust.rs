@@ -0,0 +1,569 @@
+// Emits the Rust bindings: the raw commands of `DiscordSDK.commands` for activity-sys and the
+// commands, events and their types for the activity crate.
+
+use std::{
+    collections::{BTreeSet, HashMap, VecDeque},
+    fmt::Write,
+    rc::Rc,
+};
+
+use anyhow::Result;
+
+use crate::{
+    schema::{Enum, Literal, Object, Schema},
+    sdk::{pascal_case, snake_case, Sdk},
+};
+
+/// Types that the hand-written bindings had nicer names for than the SDK.
+const RENAMES: &[(&str, &str)] = &[
+    ("GuildMemberRPC", "Member"),
+    ("SetActivity", "PartialActivity"),
+    ("AuthenticateResApplication", "Application"),
+    ("AuthenticateResScope", "Scope"),
+    (
+        "GetInstanceConnectedParticipantsResParticipant",
+        "Participant",
+    ),
+];
+
+/// Rust types of numbers by type and field. The SDK's schemas don't say which numbers are integers,
+/// the rest are `f64`. Strings are 64 bit integers that the SDK has as a `bigint` or a string.
+const INTEGERS: &[(&str, &str, &str)] = &[
+    ("Activity", "created_at", "u64"),
+    ("Activity", "flags", "u64"),
+    ("Activity", "type", "u8"),
+    ("ActivityParty", "size", "u32"),
+    ("ActivityTimestamps", "end", "u64"),
+    ("ActivityTimestamps", "start", "u64"),
+    ("Attachment", "height", "u32"),
+    ("Attachment", "size", "u64"),
+    ("Attachment", "width", "u32"),
+    ("AuthenticateResUser", "public_flags", "u64"),
+    ("ChannelMention", "type", "u8"),
+    ("Embed", "color", "u32"),
+    ("Entitlement", "gift_code_flags", "u64"),
+    ("ErrorEvent", "code", "u64"),
+    ("GetChannelPermissionsRes", "permissions", "u64"),
+    ("GetChannelRes", "bitrate", "u32"),
+    ("GetChannelRes", "position", "i32"),
+    ("GetChannelRes", "user_limit", "u32"),
+    ("Image", "height", "u32"),
+    ("Image", "width", "u32"),
+    ("Message", "flags", "u64"),
+    ("Message", "type", "u8"),
+    ("MessageActivity", "type", "u8"),
+    ("PartialActivity", "type", "u8"),
+    ("Participant", "flags", "u64"),
+    ("Participant", "premium_type", "u8"),
+    ("Reaction", "count", "u32"),
+    ("ReadyEvent", "v", "u8"),
+    ("Sku", "flags", "u64"),
+    ("SkuPrice", "amount", "u64"),
+    ("User", "flags", "u64"),
+    ("User", "premium_type", "u8"),
+    ("UserVoiceState", "volume", "u32"),
+    ("Video", "height", "u32"),
+    ("Video", "width", "u32"),
+];
+
+/// The header of every generated file.
+pub fn header(version: &str) -> String {
+    format!(
+        "// This file is generated by `cargo xtask codegen` from the Discord SDK {version}, don't \
+         edit it.\n\n"
+    )
+}
+
+/// `activity-sys/src/commands.rs`
+pub fn sys_commands(sdk: &Sdk) -> String {
+    let mut out = header(&sdk.version);
+    out.push_str("use wasm_bindgen::prelude::*;\n\n");
+    out.push_str("#[wasm_bindgen]\nextern \"C\" {\n");
+    out.push_str("#[wasm_bindgen(extends = js_sys::Object)]\npub type DiscordSDKCommands;\n");
+
+    for command in &sdk.commands {
+        let name = snake_case(&command.function);
+        let js_name = if name == command.function {
+            String::new()
+        } else {
+            format!(", js_name = {}", command.function)
+        };
+        let args = if command.args.is_some() {
+            ", args: JsValue"
+        } else {
+            ""
+        };
+        let response = if is_empty(&command.response) {
+            "()"
+        } else {
+            "JsValue"
+        };
+
+        writeln!(
+            out,
+            "\n#[wasm_bindgen(method, catch{js_name})]\npub async fn {name}(this: \
+             &DiscordSDKCommands{args}) -> Result<{response}, JsValue>;"
+        )
+        .unwrap();
+    }
+
+    out.push_str("}\n");
+    out
+}
+
+/// `activity/src/schema.rs`
+pub fn schema(sdk: &Sdk) -> Result<String> {
+    let mut types = Types::default();
+    let mut commands = String::new();
+    let mut events = String::new();
+
+    for command in &sdk.commands {
+        let name = pascal_case(&command.function);
+        let args = match &command.args {
+            Some(args) => types.top_level(args, &format!("{name}Args"))?,
+            None => "()".into(),
+        };
+        let response = if is_empty(&command.response) {
+            types.uses.insert("EmptyRes");
+            "EmptyRes".into()
+        } else {
+            types.top_level(&command.response, &format!("{name}Res"))?
+        };
+
+        writeln!(
+            commands,
+            "command!({name}, \"{}\", {args}, {response});",
+            command.name
+        )?;
+    }
+
+    let mut variants = Vec::new();
+    for event in &sdk.events {
+        let variant = pascal_case(&event.name);
+        let payload = format!("{variant}Event");
+
+        match event.data.required() {
+            // Events that are a type of the SDK, like `User`, wrap it.
+            Schema::Object(object) if types.is_declared(object) => {
+                let name = types.object(object, &payload)?;
+                types.items.push(format!(
+                    "#[derive(Serialize, Deserialize, Debug)]\npub struct {payload} {{\n\
+                     #[serde(flatten)]\npub {}: {name},\n}}\n",
+                    snake_case(&name)
+                ));
+            }
+            Schema::Object(object) => {
+                types.name(object, &payload)?;
+            }
+            schema => anyhow::bail!("the data of `{}` isn't an object: {schema:?}", event.name),
+        }
+
+        let args = match &event.subscribe_args {
+            None => "()".to_string(),
+            // The channel defaults to the one of the activity.
+            Some(args) if args.field("channel_id").is_ok() => {
+                types.uses.insert("SubscribeArgs");
+                "SubscribeArgs".to_string()
+            }
+            // The guild defaults to the one of the activity.
+            Some(args) if args.field("guild_id").is_ok() => {
+                types.uses.insert("GuildSubscribeArgs");
+                "GuildSubscribeArgs".to_string()
+            }
+            Some(args) => {
+                let name = types.top_level(args, &format!("{variant}Args"))?;
+                types.uses.insert("EventArgs");
+                types
+                    .items
+                    .push(format!("impl EventArgs for {name} {{}}\n"));
+                name
+            }
+        };
+
+        writeln!(
+            events,
+            "impl EventPayload for {payload} {{\ntype Args = {args};\n\nfn event_type() -> \
+             EventType {{\nEventType::{variant}\n}}\n}}\n"
+        )?;
+        variants.push((variant, &event.name));
+    }
+
+    types.drain()?;
+    if let Some((name, key, _)) = INTEGERS
+        .iter()
+        .find(|entry| !types.integers.contains(entry))
+    {
+        anyhow::bail!("`{name}.{key}` in `INTEGERS` isn't a number of the SDK");
+    }
+
+    let mut out = header(&sdk.version);
+    out.push_str("use std::{convert::Infallible, str::FromStr};\n\n");
+    out.push_str("use serde::{Deserialize, Serialize};\n\n");
+    let mut uses = vec!["EventPayload", "sdk_enum"];
+    uses.extend(types.uses.iter().copied());
+    uses.sort_by_key(|name| name.to_lowercase());
+    writeln!(
+        out,
+        "use crate::{{commands::command, types::{{{}}}}};\n",
+        uses.join(", ")
+    )?;
+
+    writeln!(
+        out,
+        "/// The version of the Discord SDK the commands and events of this crate are written \
+         for.\npub const BINDINGS_SDK_VERSION: &str = \"{}\";\n",
+        sdk.version
+    )?;
+    out.push_str(&commands);
+    out.push('\n');
+
+    out.push_str(
+        "#[derive(Clone, Debug, PartialEq, Eq, Hash)]\n#[non_exhaustive]\npub enum EventType {\n",
+    );
+    for (variant, _) in &variants {
+        writeln!(out, "{variant},")?;
+    }
+    out.push_str("/// An event that isn't modeled by this crate yet.\nOther(String),\n}\n\n");
+
+    out.push_str("impl EventType {\npub fn as_str(&self) -> &str {\nmatch self {\n");
+    for (variant, name) in &variants {
+        writeln!(out, "EventType::{variant} => \"{name}\",")?;
+    }
+    out.push_str("EventType::Other(name) => name,\n}\n}\n}\n\n");
+
+    out.push_str(
+        "impl FromStr for EventType {\ntype Err = Infallible;\n\nfn from_str(s: &str) -> \
+         Result<Self, Self::Err> {\nOk(match s {\n",
+    );
+    for (variant, name) in &variants {
+        writeln!(out, "\"{name}\" => EventType::{variant},")?;
+    }
+    out.push_str("other => EventType::Other(other.to_string()),\n})\n}\n}\n\n");
+
+    out.push_str(&events);
+    for item in &types.items {
+        out.push('\n');
+        out.push_str(item);
+    }
+
+    Ok(out)
+}
+
+// Responses like `EmptyResponse` that are ignored.
+fn is_empty(schema: &Schema) -> bool {
+    matches!(schema.required(), Schema::Object(object) if object.fields.is_empty())
+}
+
+/// How a schema is represented in Rust.
+struct Type {
+    name: String,
+    kind: Kind,
+}
+
+#[derive(PartialEq)]
+enum Kind {
+    Required,
+    Optional,
+    /// Values that are passed on as JSON.
+    Json,
+}
+
+impl Type {
+    fn required(name: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            kind: Kind::Required,
+        }
+    }
+
+    fn json() -> Self {
+        Self {
+            name: "serde_json::Value".into(),
+            kind: Kind::Json,
+        }
+    }
+
+    fn full(&self) -> String {
+        match self.kind {
+            Kind::Optional => format!("Option<{}>", self.name),
+            _ => self.name.clone(),
+        }
+    }
+}
+
+enum Pending {
+    Struct(Rc<Object>),
+    Enum(Rc<Enum>),
+}
+
+/// The named types of the bindings, in the order they are first used.
+#[derive(Default)]
+struct Types {
+    names: HashMap<*const (), String>,
+    taken: BTreeSet<String>,
+    pending: VecDeque<(Pending, String)>,
+    items: Vec<String>,
+    /// The hand-written types the generated ones use.
+    uses: BTreeSet<&'static str>,
+    /// The entries of `INTEGERS` that were used.
+    integers: Vec<&'static (&'static str, &'static str, &'static str)>,
+}
+
+impl Types {
+    /// The type of the arguments or the response of a command, `name` unless the SDK declares a
+    /// type for it, which is aliased.
+    fn top_level(&mut self, schema: &Schema, name: &str) -> Result<String> {
+        if let Schema::Object(object) = schema.required() {
+            if !self.is_declared(object) {
+                return self.name(object, name);
+            }
+        }
+
+        let ty = self.rust_type(schema, name)?;
+        let ty = match schema {
+            // Optional objects are the same as empty ones for the bindings.
+            Schema::Optional(inner) if matches!(**inner, Schema::Object(_)) => ty.name,
+            _ => ty.full(),
+        };
+        self.items.push(format!("pub type {name} = {ty};\n"));
+        Ok(name.to_string())
+    }
+
+    // Whether the object is declared by the SDK as a type of its own, like `User`.
+    fn is_declared(&self, object: &Object) -> bool {
+        match &*object.name.borrow() {
+            Some(name) => ![
+                "Response",
+                "ResponseSchema",
+                "RequestSchema",
+                "Request",
+                "Input",
+            ]
+            .iter()
+            .any(|suffix| name.ends_with(suffix)),
+            None => false,
+        }
+    }
+
+    fn object(&mut self, object: &Rc<Object>, fallback: &str) -> Result<String> {
+        let name = match &*object.name.borrow() {
+            Some(name) if self.is_declared(object) => name.clone(),
+            _ => fallback.to_string(),
+        };
+        self.name(object, &name)
+    }
+
+    fn name(&mut self, object: &Rc<Object>, name: &str) -> Result<String> {
+        let key = Rc::as_ptr(object) as *const ();
+        if let Some(name) = self.names.get(&key) {
+            return Ok(name.clone());
+        }
+        let name = self.claim(key, name)?;
+        self.pending
+            .push_back((Pending::Struct(object.clone()), name.clone()));
+        Ok(name)
+    }
+
+    fn enumeration(&mut self, e: &Rc<Enum>, fallback: &str) -> Result<String> {
+        let key = Rc::as_ptr(e) as *const ();
+        if let Some(name) = self.names.get(&key) {
+            return Ok(name.clone());
+        }
+
+        // `ChannelTypesObject` is `ChannelType`.
+        let name = match &*e.name.borrow() {
+            Some(name) => {
+                let name = name.strip_suffix("Object").unwrap_or(name);
+                match name.strip_suffix("Types") {
+                    Some(name) => format!("{name}Type"),
+                    None => name.to_string(),
+                }
+            }
+            None => fallback.to_string(),
+        };
+        let name = self.claim(key, &name)?;
+        self.pending
+            .push_back((Pending::Enum(e.clone()), name.clone()));
+        Ok(name)
+    }
+
+    fn claim(&mut self, key: *const (), name: &str) -> Result<String> {
+        let name = RENAMES
+            .iter()
+            .find(|(from, _)| *from == name)
+            .map_or(name, |(_, to)| to)
+            .to_string();
+        if !self.taken.insert(name.clone()) {
+            anyhow::bail!("two different types are named `{name}`, add one to `RENAMES`");
+        }
+        self.names.insert(key, name.clone());
+        Ok(name)
+    }
+
+    fn rust_type(&mut self, schema: &Schema, context: &str) -> Result<Type> {
+        Ok(match schema {
+            Schema::Optional(inner) => {
+                let mut ty = self.rust_type(inner, context)?;
+                if ty.kind == Kind::Required {
+                    ty.kind = Kind::Optional;
+                }
+                ty
+            }
+            Schema::String | Schema::Literal(Literal::Str(_)) => Type::required("String"),
+            Schema::Number | Schema::Literal(Literal::Num(_)) => Type::required("f64"),
+            Schema::Boolean | Schema::Literal(Literal::Bool(_)) => Type::required("bool"),
+            Schema::Unknown | Schema::Null | Schema::Void => Type::json(),
+            Schema::Array(element) => {
+                let element = self.rust_type(element.required(), &singular(context))?;
+                Type::required(format!("Vec<{}>", element.full()))
+            }
+            Schema::Object(object) if object.fields.is_empty() => Type::json(),
+            Schema::Object(object) => Type::required(self.object(object, context)?),
+            Schema::Enum(e) => Type::required(self.enumeration(e, context)?),
+            Schema::Union(options) => {
+                let nullable = options
+                    .iter()
+                    .any(|option| matches!(option, Schema::Null | Schema::Void));
+                let options: Vec<_> = options
+                    .iter()
+                    .filter(|option| !matches!(option, Schema::Null | Schema::Void))
+                    .collect();
+                let enums: Vec<_> = options
+                    .iter()
+                    .filter_map(|option| match option {
+                        Schema::Enum(e) => Some(e),
+                        _ => None,
+                    })
+                    .collect();
+
+                let all = |f: fn(&Schema) -> bool| options.iter().all(|option| f(option));
+                let mut ty = if options.len() == 1 {
+                    self.rust_type(options[0], context)?
+                } else if all(|o| matches!(o, Schema::String | Schema::Literal(Literal::Str(_)))) {
+                    Type::required("String")
+                } else if all(|o| matches!(o, Schema::Number | Schema::Literal(Literal::Num(_)))) {
+                    Type::required("f64")
+                } else if all(|o| matches!(o, Schema::Boolean | Schema::Literal(Literal::Bool(_))))
+                {
+                    Type::required("bool")
+                } else if enums.len() == 1
+                    && all(|o| matches!(o, Schema::Enum(_) | Schema::Literal(Literal::Num(_))))
+                {
+                    // The SDK's `UNHANDLED` value, which is `Unhandled` in Rust.
+                    Type::required(self.enumeration(enums[0], context)?)
+                } else {
+                    Type::json()
+                };
+                if nullable && ty.kind == Kind::Required {
+                    ty.kind = Kind::Optional;
+                }
+                ty
+            }
+        })
+    }
+
+    // Emits the types that were named so far and the ones they use.
+    fn drain(&mut self) -> Result<()> {
+        while let Some((pending, name)) = self.pending.pop_front() {
+            let item = match pending {
+                Pending::Struct(object) => self.emit_struct(&object, &name)?,
+                Pending::Enum(e) => emit_enum(&e, &name),
+            };
+            self.items.push(item);
+        }
+        Ok(())
+    }
+
+    fn emit_struct(&mut self, object: &Object, name: &str) -> Result<String> {
+        let mut fields = String::new();
+        let mut default = !object.fields.is_empty();
+
+        for (key, schema) in &object.fields {
+            let mut ty = self.rust_type(schema, &format!("{name}{}", pascal_case(key)))?;
+            let integer = INTEGERS.iter().find(|(n, k, _)| *n == name && k == key);
+            if let Some(entry) = integer {
+                self.integers.push(entry);
+            }
+
+            let ident = match snake_case(key).as_str() {
+                "type" => "kind".to_string(),
+                ident if KEYWORDS.contains(&ident) => format!("r#{ident}"),
+                ident => ident.to_string(),
+            };
+
+            let mut attrs = Vec::new();
+            match integer {
+                Some((_, _, integer)) if ty.name == "String" && ty.kind == Kind::Required => {
+                    ty.name = integer.to_string();
+                    attrs.push("with = \"crate::types::big_int\"".to_string());
+                }
+                Some((_, _, integer)) if ty.name.contains("f64") => {
+                    ty.name = ty.name.replace("f64", integer);
+                }
+                Some(_) => anyhow::bail!("`{name}.{key}` in `INTEGERS` isn't a number"),
+                None => {}
+            }
+            match ty.kind {
+                Kind::Required => {
+                    default &= ["String", "f64", "bool"].contains(&ty.name.as_str())
+                        || INTEGERS.iter().any(|(_, _, integer)| *integer == ty.name)
+                        || ty.name.starts_with("Vec<")
+                }
+                Kind::Optional => {
+                    attrs.push("default".into());
+                    attrs.push("skip_serializing_if = \"Option::is_none\"".into());
+                }
+                Kind::Json => {
+                    attrs.push("default".into());
+                    attrs.push("skip_serializing_if = \"serde_json::Value::is_null\"".into());
+                }
+            }
+            if ident.trim_start_matches("r#") != key {
+                attrs.push(format!("rename = \"{key}\""));
+            }
+
+            if !attrs.is_empty() {
+                writeln!(fields, "#[serde({})]", attrs.join(", "))?;
+            }
+            writeln!(fields, "pub {ident}: {},", ty.full())?;
+        }
+
+        // Arguments with many optional fields can use `..Default::default()`.
+        let derives = if default {
+            "Serialize, Deserialize, Debug, Default"
+        } else {
+            "Serialize, Deserialize, Debug"
+        };
+        Ok(format!(
+            "#[derive({derives})]\npub struct {name} {{\n{fields}}}\n"
+        ))
+    }
+}
+
+fn emit_enum(e: &Enum, name: &str) -> String {
+    let mut variants = String::new();
+    for (key, value) in &e.values {
+        let value = match value {
+            Literal::Str(s) => format!("{s:?}"),
+            Literal::Num(n) => n.to_string(),
+            Literal::Bool(b) => b.to_string(),
+        };
+        variants.push_str(&format!("{} = {value},\n", pascal_case(key)));
+    }
+    format!("sdk_enum! {{\npub enum {name} {{\n{variants}}}\n}}\n")
+}
+
+// `participants` to `participant` for the type of the elements of an array.
+fn singular(name: &str) -> String {
+    match name.strip_suffix('s') {
+        Some(singular) if !singular.ends_with('s') => singular.to_string(),
+        _ => format!("{name}Element"),
+    }
+}
+
+const KEYWORDS: &[&str] = &[
+    "as", "async", "await", "break", "const", "continue", "crate", "dyn", "else", "enum", "extern",
+    "false", "fn", "for", "if", "impl", "in", "let", "loop", "match", "mod", "move", "mut", "pub",
+    "ref", "return", "self", "static", "struct", "super", "trait", "true", "type", "unsafe", "use",
+    "where", "while", "yield",
+];
@@ -0,0 +1,395 @@
+// The types of the SDK, evaluated from its zod schemas. Only what the bindings can express is kept:
+// validations like `.max()` are dropped and values that are `.nullable()`, `.optional()` or have a
+// default are all optional.
+
+use std::{cell::RefCell, collections::BTreeMap, rc::Rc};
+
+use anyhow::{Context, Result};
+
+use crate::js::{Expr, Module, Prop};
+
+#[derive(Debug, Clone)]
+pub enum Schema {
+    String,
+    Number,
+    Boolean,
+    /// Anything the bindings can't describe, like `unknown`.
+    Unknown,
+    Null,
+    Void,
+    Literal(Literal),
+    Array(Box<Schema>),
+    Optional(Box<Schema>),
+    Union(Vec<Schema>),
+    Object(Rc<Object>),
+    Enum(Rc<Enum>),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Literal {
+    Str(String),
+    Num(f64),
+    Bool(bool),
+}
+
+#[derive(Debug)]
+pub struct Object {
+    /// The declaration the object was created in, if any.
+    pub name: RefCell<Option<String>>,
+    pub fields: Vec<(String, Schema)>,
+}
+
+/// The values of an enum, which the SDK replaces with `UNHANDLED` (-1) when it doesn't know them.
+#[derive(Debug)]
+pub struct Enum {
+    pub name: RefCell<Option<String>>,
+    pub values: Vec<(String, Literal)>,
+}
+
+impl Schema {
+    pub fn object(name: Option<String>, fields: Vec<(String, Schema)>) -> Schema {
+        Schema::Object(Rc::new(Object {
+            name: RefCell::new(name),
+            fields,
+        }))
+    }
+
+    fn optional(self) -> Schema {
+        match self {
+            Schema::Optional(_) => self,
+            schema => Schema::Optional(Box::new(schema)),
+        }
+    }
+
+    /// The schema without `.optional()` and the like.
+    pub fn required(&self) -> &Schema {
+        match self {
+            Schema::Optional(inner) => inner.required(),
+            schema => schema,
+        }
+    }
+
+    /// The schema of a field of an object.
+    pub fn field(&self, key: &str) -> Result<&Schema> {
+        self.as_object()?
+            .fields
+            .iter()
+            .find(|(k, _)| k == key)
+            .map(|(_, schema)| schema)
+            .with_context(|| format!("the schema has no field `{key}`"))
+    }
+
+    fn as_object(&self) -> Result<&Rc<Object>> {
+        match self.required() {
+            Schema::Object(object) => Ok(object),
+            schema => anyhow::bail!("expected an object schema, found {schema:?}"),
+        }
+    }
+
+    // Declarations name the object or enum they create, not the ones they refer to.
+    fn set_name(&self, name: &str) {
+        let slot = match self.required() {
+            Schema::Object(object) => &object.name,
+            Schema::Enum(e) => &e.name,
+            _ => return,
+        };
+        slot.borrow_mut().get_or_insert_with(|| name.to_string());
+    }
+}
+
+/// The values of JavaScript expressions that schemas are built from.
+#[derive(Debug, Clone)]
+enum Value {
+    Schema(Schema),
+    /// `schema.shape`, whose properties are the schemas of the fields.
+    Shape(Rc<Object>),
+    Object(Vec<(String, Value)>),
+    Array(Vec<Value>),
+    Literal(Literal),
+    Null,
+}
+
+/// Evaluates the zod schemas of a module.
+pub struct Zod<'a> {
+    module: &'a Module<'a>,
+    vars: RefCell<BTreeMap<String, Value>>,
+    enums: RefCell<BTreeMap<String, Rc<Enum>>>,
+}
+
+impl<'a> Zod<'a> {
+    pub fn new(module: &'a Module<'a>) -> Self {
+        Self {
+            module,
+            vars: RefCell::default(),
+            enums: RefCell::default(),
+        }
+    }
+
+    /// The schema declared as `var <name> = ...`.
+    pub fn var(&self, name: &str) -> Result<Schema> {
+        match self.eval_var(name)? {
+            Value::Schema(schema) => Ok(schema),
+            // Objects of values are enums, e.g. `ChannelTypesObject`.
+            Value::Object(_) => Ok(Schema::Enum(self.enum_of(name)?)),
+            value => anyhow::bail!("`{name}` isn't a schema: {value:?}"),
+        }
+    }
+
+    pub fn schema(&self, expr: &Expr) -> Result<Schema> {
+        match self.eval(expr)? {
+            Value::Schema(schema) => Ok(schema),
+            value => anyhow::bail!("expected a schema, found {value:?}"),
+        }
+    }
+
+    fn eval_var(&self, name: &str) -> Result<Value> {
+        if let Some(value) = self.vars.borrow().get(name) {
+            return Ok(value.clone());
+        }
+
+        let value = self
+            .eval(&self.module.var(name)?)
+            .with_context(|| format!("failed to evaluate `{name}`"))?;
+        if let Value::Schema(schema) = &value {
+            schema.set_name(name);
+        }
+        self.vars
+            .borrow_mut()
+            .insert(name.to_string(), value.clone());
+        Ok(value)
+    }
+
+    fn enum_of(&self, name: &str) -> Result<Rc<Enum>> {
+        if let Some(e) = self.enums.borrow().get(name) {
+            return Ok(e.clone());
+        }
+
+        let Value::Object(props) = self.eval_var(name)? else {
+            anyhow::bail!("`{name}` isn't an object of values");
+        };
+        let mut values = Vec::new();
+        for (key, value) in props {
+            match value {
+                _ if key == "UNHANDLED" => {}
+                Value::Literal(literal) => values.push((key, literal)),
+                value => anyhow::bail!("`{name}.{key}` isn't a value: {value:?}"),
+            }
+        }
+
+        let e = Rc::new(Enum {
+            name: RefCell::new(Some(name.to_string())),
+            values,
+        });
+        self.enums.borrow_mut().insert(name.to_string(), e.clone());
+        Ok(e)
+    }
+
+    fn eval(&self, expr: &Expr) -> Result<Value> {
+        Ok(match expr {
+            Expr::Ident(name) => self.eval_var(name)?,
+            Expr::Str(s) => Value::Literal(Literal::Str(s.clone())),
+            Expr::Num(n) => Value::Literal(Literal::Num(*n)),
+            Expr::Bool(b) => Value::Literal(Literal::Bool(*b)),
+            Expr::Null => Value::Null,
+            Expr::Array(items) => Value::Array(
+                items
+                    .iter()
+                    .map(|item| self.eval(item))
+                    .collect::<Result<_>>()?,
+            ),
+            Expr::Object(props) => {
+                let mut values: Vec<(String, Value)> = Vec::new();
+                for prop in props {
+                    let entries = match prop {
+                        Prop::KeyValue(key, value) => vec![(key.clone(), self.eval(value)?)],
+                        Prop::Spread(spread) => match self.eval(spread)? {
+                            Value::Object(entries) => entries,
+                            value => anyhow::bail!("can't spread {value:?}"),
+                        },
+                    };
+                    for (key, value) in entries {
+                        values.retain(|(k, _)| *k != key);
+                        values.push((key, value));
+                    }
+                }
+                Value::Object(values)
+            }
+            Expr::Member(object, property) => match (self.eval(object)?, property.as_str()) {
+                (Value::Schema(schema), "shape") => Value::Shape(schema.as_object()?.clone()),
+                (Value::Schema(Schema::Array(element)), "element") => Value::Schema(*element),
+                (Value::Shape(object), field) => {
+                    Value::Schema(Schema::Object(object).field(field)?.clone())
+                }
+                (value, property) => anyhow::bail!("unsupported `{value:?}.{property}`"),
+            },
+            Expr::Call(callee, args) => self.call(callee, args)?,
+            expr => anyhow::bail!("unsupported expression {expr:?}"),
+        })
+    }
+
+    fn call(&self, callee: &Expr, args: &[Expr]) -> Result<Value> {
+        let arg = |i: usize| {
+            args.get(i)
+                .with_context(|| format!("missing argument {i} of {callee:?}"))
+        };
+
+        let builder = match callee {
+            // `z.object()` or `objectType()` if zod is bundled.
+            Expr::Member(namespace, name) if matches!(&**namespace, Expr::Ident(z) if z == "z" || z == "zod") => {
+                name.as_str()
+            }
+            Expr::Ident(name) => name.strip_suffix("Type").unwrap_or(name),
+            Expr::Member(object, method) => {
+                let schema = self.schema(object)?;
+                return self.method(schema, method, args).map(Value::Schema);
+            }
+            callee => anyhow::bail!("unsupported call of {callee:?}"),
+        };
+
+        let schema = match builder {
+            // Bigints are strings on the wire.
+            "string" | "bigInt" => Schema::String,
+            "number" => Schema::Number,
+            "boolean" => Schema::Boolean,
+            "null" => Schema::Null,
+            "void" | "undefined" => Schema::Void,
+            // Native enums of the SDK are deprecated in favor of `zodCoerceUnhandledValue`.
+            "unknown" | "any" | "custom" | "nativeEnum" => Schema::Unknown,
+            "object" => {
+                let Value::Object(props) = self.eval(arg(0)?)? else {
+                    anyhow::bail!("expected the fields of an object");
+                };
+                let fields = props
+                    .into_iter()
+                    .map(|(key, value)| match value {
+                        Value::Schema(schema) => Ok((key, schema)),
+                        value => anyhow::bail!("field `{key}` isn't a schema: {value:?}"),
+                    })
+                    .collect::<Result<_>>()?;
+                Schema::object(None, fields)
+            }
+            "array" => Schema::Array(Box::new(self.schema(arg(0)?)?)),
+            "optional" => self.schema(arg(0)?)?.optional(),
+            "union" => {
+                let Value::Array(options) = self.eval(arg(0)?)? else {
+                    anyhow::bail!("expected the options of a union");
+                };
+                Schema::Union(
+                    options
+                        .into_iter()
+                        .map(|option| match option {
+                            Value::Schema(schema) => Ok(schema),
+                            value => anyhow::bail!("option isn't a schema: {value:?}"),
+                        })
+                        .collect::<Result<_>>()?,
+                )
+            }
+            "literal" => match self.eval(arg(0)?)? {
+                Value::Literal(literal) => Schema::Literal(literal),
+                value => anyhow::bail!("unsupported literal {value:?}"),
+            },
+            "enum" => {
+                let Value::Array(values) = self.eval(arg(0)?)? else {
+                    anyhow::bail!("expected the values of an enum");
+                };
+                let values = values
+                    .into_iter()
+                    .map(|value| match value {
+                        Value::Literal(Literal::Str(s)) => Ok((s.clone(), Literal::Str(s))),
+                        value => anyhow::bail!("unsupported enum value {value:?}"),
+                    })
+                    .collect::<Result<_>>()?;
+                Schema::Enum(Rc::new(Enum {
+                    name: RefCell::new(None),
+                    values,
+                }))
+            }
+            // The function only runs before the validation.
+            "preprocess" => self.schema(arg(1)?)?,
+            // Helpers of the SDK.
+            "zodCoerceUnhandledValue" => match arg(0)? {
+                Expr::Ident(name) => Schema::Enum(self.enum_of(name)?),
+                expr => anyhow::bail!("unsupported enum {expr:?}"),
+            },
+            "fallbackToDefault" => self.schema(arg(0)?)?,
+            builder => anyhow::bail!("unsupported zod builder `{builder}`"),
+        };
+
+        Ok(Value::Schema(schema))
+    }
+
+    fn method(&self, schema: Schema, method: &str, args: &[Expr]) -> Result<Schema> {
+        let keys = |i: usize| -> Result<Vec<String>> {
+            match args.get(i).map(|arg| self.eval(arg)).transpose()? {
+                Some(Value::Object(props)) => Ok(props.into_iter().map(|(key, _)| key).collect()),
+                value => anyhow::bail!("expected an object of keys, found {value:?}"),
+            }
+        };
+
+        Ok(match method {
+            "optional" | "nullable" | "nullish" | "default" => schema.optional(),
+            "or" => {
+                let other = self.schema(args.first().context("missing schema")?)?;
+                let mut options = match schema {
+                    Schema::Union(options) => options,
+                    schema => vec![schema],
+                };
+                options.push(other);
+                Schema::Union(options)
+            }
+            "array" => Schema::Array(Box::new(schema)),
+            // Validations and transformations that don't change the type.
+            "passthrough" | "strict" | "strip" | "describe" | "max" | "min" | "gt" | "gte"
+            | "lt" | "lte" | "int" | "positive" | "nonnegative" | "length" | "url" | "email"
+            | "trim" => schema,
+            "partial" => {
+                let object = schema.as_object()?;
+                Schema::object(
+                    None,
+                    object
+                        .fields
+                        .iter()
+                        .map(|(key, schema)| (key.clone(), schema.clone().optional()))
+                        .collect(),
+                )
+            }
+            "pick" | "omit" => {
+                let keys = keys(0)?;
+                let object = schema.as_object()?;
+                Schema::object(
+                    None,
+                    object
+                        .fields
+                        .iter()
+                        .filter(|(key, _)| keys.contains(key) == (method == "pick"))
+                        .cloned()
+                        .collect(),
+                )
+            }
+            "extend" | "merge" => {
+                let object = schema.as_object()?;
+                let extension = match self.eval(args.first().context("missing fields")?)? {
+                    Value::Object(props) => props
+                        .into_iter()
+                        .map(|(key, value)| match value {
+                            Value::Schema(schema) => Ok((key, schema)),
+                            value => anyhow::bail!("field `{key}` isn't a schema: {value:?}"),
+                        })
+                        .collect::<Result<Vec<_>>>()?,
+                    Value::Schema(other) => other.as_object()?.fields.clone(),
+                    value => anyhow::bail!("can't extend with {value:?}"),
+                };
+
+                let mut fields = object.fields.clone();
+                for (key, schema) in extension {
+                    match fields.iter_mut().find(|(k, _)| *k == key) {
+                        Some(field) => field.1 = schema,
+                        None => fields.push((key, schema)),
+                    }
+                }
+                Schema::object(None, fields)
+            }
+            method => anyhow::bail!("unsupported zod method `{method}`"),
+        })
+    }
+}
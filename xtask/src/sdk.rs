@@ -0,0 +1,205 @@
+// The commands and events of the bundled SDK. The responses and events come from its zod schemas,
+// the inputs of commands without a request schema from the TypeScript declarations.
+
+use anyhow::{Context, Result};
+
+use crate::{
+    dts::Declarations,
+    js::{Expr, Module, Prop},
+    schema::{Schema, Zod},
+};
+
+pub struct Sdk {
+    pub version: String,
+    pub commands: Vec<Command>,
+    pub events: Vec<Event>,
+}
+
+pub struct Command {
+    /// The name of the function in `DiscordSDK.commands`, e.g. `getChannel`.
+    pub function: String,
+    /// The name on the wire, e.g. `GET_CHANNEL`.
+    pub name: String,
+    pub args: Option<Schema>,
+    pub response: Schema,
+}
+
+pub struct Event {
+    pub name: String,
+    pub data: Schema,
+    pub subscribe_args: Option<Schema>,
+}
+
+pub fn load(module: &Module, zod: &Zod, declarations: &Declarations) -> Result<Sdk> {
+    let version = module
+        .var("version")?
+        .as_str()
+        .context("the version of the SDK isn't a string")?
+        .to_string();
+
+    Ok(Sdk {
+        version,
+        commands: commands(module, zod, declarations)?,
+        events: events(module, zod)?,
+    })
+}
+
+fn commands(module: &Module, zod: &Zod, declarations: &Declarations) -> Result<Vec<Command>> {
+    let Expr::Object(props) = module.after("function commands(sendCommand) {\n  return ")? else {
+        anyhow::bail!("`commands()` doesn't return an object");
+    };
+    let schemas = module.var("Schemas")?;
+
+    let mut commands = Vec::new();
+    for prop in props {
+        let Prop::KeyValue(function, _) = prop else {
+            anyhow::bail!("unsupported spread in `commands()`");
+        };
+        let (name, request, response) = command_factory(&module.var(&function)?, &schemas)
+            .with_context(|| format!("unsupported command `{function}`"))?;
+
+        let input = format!("{}Input", pascal_case(&function));
+        let args = match request {
+            Some(request) => Some(zod.schema(&request)?),
+            None => declarations.get(&input)?,
+        }
+        .filter(|args| !matches!(args, Schema::Void));
+
+        commands.push(Command {
+            response: zod
+                .schema(&response)
+                .with_context(|| format!("failed to evaluate the response of `{function}`"))?,
+            function,
+            name,
+            args,
+        });
+    }
+
+    Ok(commands)
+}
+
+// The wire name, request and response schema of the three ways the SDK declares commands.
+fn command_factory(expr: &Expr, schemas: &Expr) -> Result<(String, Option<Expr>, Expr)> {
+    let Expr::Call(callee, args) = (match expr {
+        Expr::Arrow(body) => &**body,
+        expr => expr,
+    }) else {
+        anyhow::bail!("not a call of a command factory");
+    };
+    let Expr::Ident(factory) = &**callee else {
+        anyhow::bail!("not a call of a command factory");
+    };
+
+    match factory.as_str() {
+        // `commandFactory(sendCommand, cmd, response)`
+        "commandFactory" => {
+            let name = args
+                .get(1)
+                .and_then(Expr::as_str)
+                .context("missing `cmd`")?;
+            let response = args.get(2).context("missing `response`")?;
+            Ok((name.to_string(), None, response.clone()))
+        }
+        // `schemaCommandFactory(cmd)`, with both schemas in `Schemas`.
+        "schemaCommandFactory" => {
+            let name = args
+                .first()
+                .and_then(Expr::as_str)
+                .context("missing `cmd`")?;
+            let schema = schemas
+                .prop(name)
+                .with_context(|| format!("`{name}` isn't in `Schemas`"))?;
+            Ok((
+                name.to_string(),
+                schema.prop("request").cloned(),
+                schema
+                    .prop("response")
+                    .context("missing `response`")?
+                    .clone(),
+            ))
+        }
+        // `compatCommandFactory({ sendCommand, cmd, response, fallbackTransform })`
+        "compatCommandFactory" => {
+            let options = args.first().context("missing options")?;
+            let name = options
+                .prop("cmd")
+                .and_then(Expr::as_str)
+                .context("missing `cmd`")?;
+            let response = options.prop("response").context("missing `response`")?;
+            Ok((name.to_string(), None, response.clone()))
+        }
+        factory => anyhow::bail!("unsupported command factory `{factory}`"),
+    }
+}
+
+fn events(module: &Module, zod: &Zod) -> Result<Vec<Event>> {
+    let schemas = module.var("EventSchema")?;
+
+    let mut events = Vec::new();
+    for (_, name) in module.ts_enum("Events")? {
+        let schema = schemas
+            .prop(&name)
+            .with_context(|| format!("`{name}` isn't in `EventSchema`"))?;
+        let payload = zod.schema(schema.prop("payload").context("missing `payload`")?)?;
+        let subscribe_args = schema
+            .prop("subscribeArgs")
+            .map(|args| zod.schema(args))
+            .transpose()?;
+
+        events.push(Event {
+            data: payload.field("data")?.clone(),
+            name,
+            subscribe_args,
+        });
+    }
+
+    // Errors aren't in `Events`, the SDK dispatches them on their own.
+    let name = module
+        .var("ERROR")?
+        .as_str()
+        .context("`ERROR` isn't a string")?
+        .to_string();
+    events.push(Event {
+        data: zod.var("ErrorEvent")?.field("data")?.clone(),
+        name,
+        subscribe_args: None,
+    });
+
+    Ok(events)
+}
+
+/// `getChannel` or `GET_CHANNEL` to `GetChannel`.
+pub fn pascal_case(name: &str) -> String {
+    let mut result = String::new();
+    for word in name.split(|c: char| !c.is_ascii_alphanumeric()) {
+        let mut chars = word.chars();
+        if let Some(first) = chars.next() {
+            result.push(first.to_ascii_uppercase());
+            if word.chars().all(|c| !c.is_ascii_lowercase()) {
+                result.extend(chars.map(|c| c.to_ascii_lowercase()));
+            } else {
+                result.extend(chars);
+            }
+        }
+    }
+    result
+}
+
+/// `getChannel` or `iosKeyboardResizesView` to `get_channel`.
+pub fn snake_case(name: &str) -> String {
+    let mut result = String::new();
+    let mut previous_lower = false;
+    for c in name.chars() {
+        if c.is_ascii_uppercase() {
+            if previous_lower {
+                result.push('_');
+            }
+            result.push(c.to_ascii_lowercase());
+            previous_lower = false;
+        } else {
+            result.push(c);
+            previous_lower = c.is_ascii_lowercase() || c.is_ascii_digit();
+        }
+    }
+    result
+}